@@ -1,16 +1,21 @@
 use crate::arguments::Arguments;
-use sp_lib::datastore::{datastore, dividends, export, history, splits};
-use sp_lib::portfolio::{algorithms, stocks_config};
+use sp_lib::datastore::{adjust, datastore, dividends, export, fx_rates, history, splits};
+use sp_lib::portfolio::{algorithms, currency, stocks_config};
+use sp_lib::portfolio::target::TargetKey;
+use sp_lib::provider::provider_trait::MarketDataProvider;
+use sp_lib::provider::providers;
 use sp_lib::util::{common_app, datetime, misc};
 use sp_lib::util::error::Error;
-use sp_lib::yfinance::{query, types};
 use std::collections::{HashMap, HashSet};
 use std::fs;
 use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
 
 const UPDATE: &str = "update";
 const DROP: &str = "drop";
 const RESET: &str = "reset";
+const ADJUST: &str = "adjust";
 const CHECK: &str = "check";
 const CREATE: &str = "create";
 const DELETE: &str = "delete";
@@ -22,6 +27,9 @@ const EXPORT: &str = "export";
 const CONSYM: &str = "consym";
 const SYMS: &str = "syms";
 
+// Days of history fetched for symbol targets that are not held
+const TARGET_HISTORY_DAYS: i64 = 30;
+
 struct StatAgg {
     tot_size: u64,
     hist_size: u64,
@@ -32,11 +40,19 @@ struct StatAgg {
     splt_count: usize
 }
 
+struct UpdateResult {
+    symbol: String,
+    messages: Vec<String>,
+    error: Option<Error>
+}
+
 pub struct Application {
     args: Arguments,
     sym_dates: HashMap<String, datetime::SPDate>,
+    fx_dates: HashMap<String, datetime::SPDate>,
     config: stocks_config::StocksConfig,
-    ds: datastore::DataStore
+    ds: datastore::DataStore,
+    provider: Box<dyn MarketDataProvider>
 }
 
 impl common_app::AppTrait for Application {
@@ -44,11 +60,14 @@ impl common_app::AppTrait for Application {
         let args = Arguments::new();
         let config = stocks_config::StocksConfig::from_file(args.config_file()).expect("Missing config file");
         let ds = datastore::DataStore::new(config.ds_root(), config.ds_name());
+        let provider = providers::make_provider(&config).expect("Invalid market data provider");
         Application {
             args,
             sym_dates: HashMap::new(),
+            fx_dates: HashMap::new(),
             config,
-            ds
+            ds,
+            provider
         }
     }
 
@@ -71,6 +90,7 @@ impl common_app::AppTrait for Application {
             UPDATE => self.update()?,
             DROP => self.drop()?,
             RESET => self.reset()?,
+            ADJUST => self.adjust()?,
             CHECK => self.check()?,
             CREATE => self.create()?,
             DELETE => self.delete()?,
@@ -106,6 +126,21 @@ impl Application {
         }
 
         self.sym_dates = algorithms::stock_base_dates(self.config.stocks());
+
+        // Symbol targets not held need recent prices for rebalancing
+        let target_date = datetime::today_plus_days(-TARGET_HISTORY_DAYS);
+        for target in self.config.targets().iter() {
+            if let TargetKey::Symbol(symbol) = &target.key {
+                self.sym_dates.entry(symbol.clone()).or_insert(target_date);
+            }
+        }
+
+        // Currency pair rates are needed from the earliest lot in each foreign currency
+        let base = self.config.base_currency();
+        self.fx_dates = currency::currency_base_dates(self.config.stocks(), self.config.closed_positions(), base)
+            .into_iter()
+            .map(|(ccy, date)| (fx_rates::pair_symbol(&ccy, base), date))
+            .collect();
     }
 
     fn update(&self) -> Result<(), Error> {
@@ -117,25 +152,47 @@ impl Application {
         let mut upd_count: usize = 0;
         let mut err_count: usize = 0;
 
-        for (symbol, base_date) in self.sym_dates.iter() {
-            if !self.is_symbol_match(symbol) {
-                continue;
-            }
+        let mut symbols: Vec<(&String, &datetime::SPDate)> = self.sym_dates.iter()
+            .filter(|(symbol, _)| self.is_symbol_match(symbol))
+            .collect();
+        symbols.sort();
 
-            if self.args.is_verbose() {
-                println!("Update {}", symbol);
+        // Report in symbol order regardless of the order in which workers finish
+        for result in self.run_updates(&symbols) {
+            for msg in result.messages.iter() {
+                println!("{}", msg);
             }
 
-            match self.update_stock_data(symbol, base_date) {
-                Ok(_) => upd_count += 1,
-                Err(err) => {
-                    eprintln!("{}: {}", symbol, err);
+            match result.error {
+                None => upd_count += 1,
+                Some(err) => {
+                    eprintln!("{}: {}", result.symbol, err);
                     err_count += 1;
                 }
             };
         }
 
         println!("Updated {} out of {}", upd_count, misc::count_format(sym_count, "symbol"));
+
+        let mut pairs: Vec<(&String, &datetime::SPDate)> = self.fx_dates.iter()
+            .filter(|(pair, _)| self.is_symbol_match(pair))
+            .collect();
+        pairs.sort();
+
+        for (pair, base_date) in pairs.iter() {
+            if self.args.is_verbose() {
+                println!("Update fx rates {}", pair);
+            }
+
+            if let Err(err) = self.update_fx_rates(pair, base_date) {
+                eprintln!("{}: {}", pair, err);
+                err_count += 1;
+            }
+        }
+        if !pairs.is_empty() {
+            println!("Updated fx rates of {}", misc::count_format(pairs.len(), "currency pair"));
+        }
+
         if err_count == 0 {
             Ok(())
         } else {
@@ -143,21 +200,60 @@ impl Application {
         }
     }
 
-    fn perform_update(&self, symbol: &str, base_date: &datetime::SPDate) -> Result<bool, Error> {
+    fn run_updates(&self, symbols: &[(&String, &datetime::SPDate)]) -> Vec<UpdateResult> {
+        let jobs = self.args.jobs().min(symbols.len()).max(1);
+        let next = AtomicUsize::new(0);
+
+        // Each worker takes whole symbols, so a symbol's files are only written by one thread
+        let mut results: Vec<(usize, UpdateResult)> = thread::scope(|scope| {
+            let workers: Vec<_> = (0..jobs)
+                .map(|_| scope.spawn(|| {
+                    let mut done: Vec<(usize, UpdateResult)> = Vec::new();
+                    loop {
+                        let index = next.fetch_add(1, Ordering::Relaxed);
+                        if index >= symbols.len() {
+                            break;
+                        }
+
+                        // Workers do not print, messages are printed from the main thread in symbol order
+                        let (symbol, base_date) = symbols[index];
+                        let mut messages: Vec<String> = Vec::new();
+                        if self.args.is_verbose() {
+                            messages.push(format!("Update {}", symbol));
+                        }
+
+                        let error = self.update_stock_data(symbol, base_date, &mut messages).err();
+                        done.push((index, UpdateResult { symbol: symbol.clone(), messages, error }));
+                    }
+                    done
+                }))
+                .collect();
+
+            workers
+                .into_iter()
+                .flat_map(|worker| worker.join().expect("Update worker failed"))
+                .collect()
+        });
+
+        results.sort_by_key(|(index, _)| *index);
+        results.into_iter().map(|(_, result)| result).collect()
+    }
+
+    fn perform_update(&self, symbol: &str, base_date: &datetime::SPDate, msgs: &mut Vec<String>) -> Result<bool, Error> {
         self.update_stock_history(symbol, base_date)?;
-        let need_div_reset = self.update_stock_dividends(symbol, base_date)?;
-        let need_slt_reset = self.update_stock_splits(symbol, base_date)?;
+        let need_div_reset = self.update_stock_dividends(symbol, base_date, msgs)?;
+        let need_slt_reset = self.update_stock_splits(symbol, base_date, msgs)?;
         Ok(need_div_reset || need_slt_reset)
     }
 
-    fn update_stock_data(&self, symbol: &str, base_date: &datetime::SPDate) -> Result<(), Error> {
-        let need_reset = self.perform_update(symbol, base_date)?;
+    fn update_stock_data(&self, symbol: &str, base_date: &datetime::SPDate, msgs: &mut Vec<String>) -> Result<(), Error> {
+        let need_reset = self.perform_update(symbol, base_date, msgs)?;
         if need_reset && self.args.is_auto_reset() {
             let count = self.perform_drop(symbol)?;
-            println!("Auto Reset: dropped {} for symbol {}", misc::count_format(count, "file"), symbol);
+            msgs.push(format!("Auto Reset: dropped {} for symbol {}", misc::count_format(count, "file"), symbol));
 
-            self.perform_update(symbol, base_date)?;
-            println!("Auto Reset: Updated {}", symbol);
+            self.perform_update(symbol, base_date, msgs)?;
+            msgs.push(format!("Auto Reset: Updated {}", symbol));
         }
         Ok(())
     }
@@ -181,21 +277,40 @@ impl Application {
 
         let today = datetime::today();
         if begin_date <= today {
-            let mut query = query::HistoryQuery::new(
-                symbol.to_string(),
-                begin_date,
-                datetime::date_plus_days(&today, 1),
-                types::Interval::Daily,
-                types::Events::History
-            );
+            let csv = self.provider.history(symbol, &begin_date, &datetime::date_plus_days(&today, 1))?;
+            self.ds.insert_symbol(history::tag(), symbol, &csv)?;
+        }
+        Ok(())
+    }
+
+    fn update_fx_rates(&self, pair: &str, base_date: &datetime::SPDate) -> Result<(), Error> {
+        let fx = if self.ds.symbol_exists(fx_rates::tag(), pair) {
+            fx_rates::FxRates::ds_select_last(&self.ds, pair)?
+        } else {
+            fx_rates::FxRates::new(pair)
+        };
 
-            query.execute()?;
-            self.ds.insert_symbol(history::tag(), symbol, &query.result)?;
+        if fx.count() > 1 {
+            return Err(format!("Found unexpected fx rates query result size {}, expected 0 or 1", fx.count()).into());
+        }
+
+        let begin_date = if fx.count() == 1 {
+            datetime::date_plus_days(&fx.entries()[0].date, 1)
+        } else {
+            *base_date
+        };
+
+        let today = datetime::today();
+        if begin_date <= today {
+            let provider_symbol = fx_rates::provider_symbol(pair);
+            let csv = self.provider.history(&provider_symbol, &begin_date, &datetime::date_plus_days(&today, 1))?;
+            let hist = history::History::parse_csv(&provider_symbol, &csv)?;
+            self.ds.insert_symbol(fx_rates::tag(), pair, &fx_rates::csv_from_history(&hist))?;
         }
         Ok(())
     }
 
-    fn update_stock_dividends(&self, symbol: &str, base_date: &datetime::SPDate) -> Result<bool, Error> {
+    fn update_stock_dividends(&self, symbol: &str, base_date: &datetime::SPDate, msgs: &mut Vec<String>) -> Result<bool, Error> {
         let mut result = false;
 
         let div = if self.ds.symbol_exists(dividends::tag(), symbol) {
@@ -216,20 +331,12 @@ impl Application {
 
         let today = datetime::today();
         if begin_date <= today {
-            let mut query = query::HistoryQuery::new(
-                symbol.to_string(),
-                begin_date,
-                datetime::date_plus_days(&today, 1),
-                types::Interval::Daily,
-                types::Events::Dividend
-            );
-
-            query.execute()?;
-            if self.ds.insert_symbol(dividends::tag(), symbol, &query.result)? > 0 && !self.is_dsop_reset() {
+            let csv = self.provider.dividends(symbol, &begin_date, &datetime::date_plus_days(&today, 1))?;
+            if self.ds.insert_symbol(dividends::tag(), symbol, &csv)? > 0 && !self.is_dsop_reset() {
                 if self.args.is_auto_reset() {
                     result = true;
                 } else {
-                    println!("Dividends updated, check if {} data reset is needed", symbol);
+                    msgs.push(format!("Dividends updated, check if {} data reset is needed", symbol));
                 }
             }
         }
@@ -237,7 +344,7 @@ impl Application {
         Ok(result)
     }
 
-    fn update_stock_splits(&self, symbol: &str, base_date: &datetime::SPDate) -> Result<bool, Error> {
+    fn update_stock_splits(&self, symbol: &str, base_date: &datetime::SPDate, msgs: &mut Vec<String>) -> Result<bool, Error> {
         let mut result = false;
 
         let splt = if self.ds.symbol_exists(splits::tag(), symbol) {
@@ -258,20 +365,12 @@ impl Application {
 
         let today = datetime::today();
         if begin_date <= today {
-            let mut query = query::HistoryQuery::new(
-                symbol.to_string(),
-                begin_date,
-                datetime::date_plus_days(&today, 1),
-                types::Interval::Daily,
-                types::Events::Split
-            );
-
-            query.execute()?;
-            if self.ds.insert_symbol(splits::tag(), symbol, &query.result)? > 0 && !self.is_dsop_reset() {
+            let csv = self.provider.splits(symbol, &begin_date, &datetime::date_plus_days(&today, 1))?;
+            if self.ds.insert_symbol(splits::tag(), symbol, &csv)? > 0 && !self.is_dsop_reset() {
                 if self.args.is_auto_reset() {
                     result = true;
                 } else {
-                    println!("Splits updated, check if {} data reset is needed", symbol);
+                    msgs.push(format!("Splits updated, check if {} data reset is needed", symbol));
                 }
             }
         }
@@ -325,6 +424,41 @@ impl Application {
         Ok(())
     }
 
+    fn adjust(&self) -> Result<(), Error> {
+        if self.args.is_verbose() {
+            println!("Adjust history");
+        }
+
+        let mut symbols: Vec<&String> = self.sym_dates.keys()
+            .filter(|symbol| self.is_symbol_match(symbol) && self.ds.symbol_exists(history::tag(), symbol))
+            .collect();
+        symbols.sort();
+
+        let mut row_count: usize = 0;
+        let mut err_count: usize = 0;
+        for symbol in symbols.iter() {
+            match adjust::ds_adjust_symbol(&self.ds, symbol) {
+                Ok(count) => {
+                    if count > 0 || self.args.is_verbose() {
+                        println!("Adjusted {} for symbol {}", misc::count_format(count, "row"), symbol);
+                    }
+                    row_count += count;
+                },
+                Err(err) => {
+                    eprintln!("{}: {}", symbol, err);
+                    err_count += 1;
+                }
+            };
+        }
+
+        println!("Adjusted {} in {}", misc::count_format(row_count, "row"), misc::count_format(symbols.len(), "symbol"));
+        if err_count == 0 {
+            Ok(())
+        } else {
+            Err(format!("Failed to adjust {}", misc::count_format(err_count, "stock")).into())
+        }
+    }
+
     fn show_data(&self, tag: &str) -> Result<(), Error> {
         if self.args.is_verbose() {
             println!("Show {}", tag);
@@ -448,6 +582,8 @@ impl Application {
             dividends::Dividends::check_csv(&content)?;
         } else if fname.starts_with(splits::tag()) {
             splits::Splits::check_csv(&content)?;
+        } else if fname.starts_with(fx_rates::tag()) {
+            fx_rates::FxRates::check_csv(&content)?;
         } else {
             return Err("Unknown entry name".into());
         }
@@ -8,6 +8,7 @@ pub struct Arguments {
     config_file: String,
     symbol: Option<String>,
     export_file: Option<String>,
+    jobs: usize,
     verbose: bool,
     auto_reset: bool
 }
@@ -23,18 +24,19 @@ impl Arguments {
             .arg(common_args::stocks_config())
             .arg(common_args::symbol(
                 false,
-                Some("Stock symbol. Optional with update and check operations. Required with drop, reset, showh, showd, shows, consym and export operations")))
+                Some("Stock symbol. Optional with update, adjust and check operations. Required with drop, reset, showh, showd, shows, consym and export operations")))
             .arg(common_args::export_file(
                 Some("Export symbol history and dividends to csv file. Required with export operation")))
             .arg(Arg::with_name("ds_operation")
                  .short("o")
                  .long("dsop")
-                 .help("Datastore tool operation, one of create, delete, update, drop, reset, showh, showd, shows, export, check, stat.\n\
+                 .help("Datastore tool operation, one of create, delete, update, drop, reset, adjust, showh, showd, shows, export, check, stat.\n\
                         create : create empty datastore\n\
                         delete : delete existing datastore\n\
                         update : update history, dividend and split data\n\
                         drop   : drop a symbol\n\
                         reset  : Reset a symbol. Equivalent to drop + update\n\
+                        adjust : recompute history adj close from stored closes, dividends and splits\n\
                         showh  : show history for symbol\n\
                         showd  : show dividends for symbol\n\
                         shows  : show splits for symbol\n\
@@ -45,6 +47,11 @@ impl Arguments {
                         stat   : calculate files count and size")
                  .required(true)
                  .takes_value(true))
+            .arg(Arg::with_name("jobs")
+                 .short("j")
+                 .long("jobs")
+                 .help("Number of symbols to update concurrently with update and reset operations. Default 1")
+                 .takes_value(true))
 
             // Flags
             .arg(Arg::with_name("verbose")
@@ -63,6 +70,10 @@ impl Arguments {
             config_file: common_args::parsed_stocks_config(&parsed_args),
             symbol: common_args::parsed_symbol(&parsed_args),
             export_file: common_args::parsed_export_file(&parsed_args),
+            jobs: match parsed_args.value_of("jobs") {
+                Some(jobs) => jobs.parse::<usize>().expect("Invalid number of jobs").max(1),
+                None => 1
+            },
             verbose: parsed_args.is_present("verbose"),
             auto_reset: parsed_args.is_present("auto_reset")
         }
@@ -88,6 +99,11 @@ impl Arguments {
         self.export_file.as_ref()
     }
 
+    #[inline(always)]
+    pub fn jobs(&self) -> usize {
+        self.jobs
+    }
+
     #[inline(always)]
     pub fn is_verbose(&self) -> bool {
         self.verbose
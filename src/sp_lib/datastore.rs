@@ -4,3 +4,5 @@ pub mod history;
 pub mod dividends;
 pub mod splits;
 pub mod export;
+pub mod adjust;
+pub mod fx_rates;
@@ -0,0 +1,254 @@
+use crate::util::error::Error;
+use crate::datastore::datastore::DataStore;
+use crate::datastore::history::{self, History, HistoryEntry, Price};
+use crate::datastore::dividends::{self, Dividends};
+use crate::datastore::splits::{self, Splits};
+
+// --------------------------------------------------------------------------------
+// Adjust Factor
+
+// Multipliers that back-adjust a raw history entry to the basis of the latest entry.
+pub struct AdjustFactor {
+    pub split: Price,
+    pub total: Price
+}
+
+// Back-adjustment factors of history entries, one per entry in date order.
+// Splits and dividends apply to entries dated before them. A dividend scales prices by
+// 1 - dividend / close, using the raw close of the last entry before its ex-date.
+pub fn adjust_factors(hist: &History, div: &Dividends, splt: &Splits) -> Result<Vec<AdjustFactor>, Error> {
+    let mut factors: Vec<AdjustFactor> = Vec::with_capacity(hist.count());
+    let mut split: Price = 1.0;
+    let mut total: Price = 1.0;
+
+    let mut div_iter = div.entries().iter().rev().peekable();
+    let mut splt_iter = splt.entries().iter().rev().peekable();
+    for entry in hist.entries().iter().rev() {
+        while let Some(splt_entry) = splt_iter.next_if(|se| se.date > entry.date) {
+            let ratio = splt_entry.ratio()?;
+            split /= ratio;
+            total /= ratio;
+        }
+
+        while let Some(div_entry) = div_iter.next_if(|de| de.date > entry.date) {
+            if div_entry.price >= entry.close {
+                return Err(format!("Dividend {} on {} is not less than close {} on {}",
+                                   div_entry.price,
+                                   div_entry.date.format("%Y-%m-%d"),
+                                   entry.close,
+                                   entry.date.format("%Y-%m-%d")).into());
+            }
+            total *= 1.0 - div_entry.price / entry.close;
+        }
+
+        factors.push(AdjustFactor { split, total });
+    }
+
+    factors.reverse();
+    Ok(factors)
+}
+
+// History with adj close recomputed from the raw close, other fields unchanged.
+pub fn adjust_close(hist: &History, div: &Dividends, splt: &Splits) -> Result<History, Error> {
+    let factors = adjust_factors(hist, div, splt)?;
+    let mut adj_hist = History::new(hist.symbol());
+    for (entry, factor) in hist.entries().iter().zip(factors.iter()) {
+        adj_hist.add_entry(HistoryEntry::new(entry.date,
+                                             entry.open,
+                                             entry.high,
+                                             entry.low,
+                                             entry.close,
+                                             entry.close * factor.total,
+                                             entry.volume));
+    }
+    Ok(adj_hist)
+}
+
+// History with split and dividend adjusted open, high, low and close, and split adjusted volume.
+pub fn adjust_ohlc(hist: &History, div: &Dividends, splt: &Splits) -> Result<History, Error> {
+    let factors = adjust_factors(hist, div, splt)?;
+    let mut adj_hist = History::new(hist.symbol());
+    for (entry, factor) in hist.entries().iter().zip(factors.iter()) {
+        adj_hist.add_entry(HistoryEntry::new(entry.date,
+                                             entry.open * factor.total,
+                                             entry.high * factor.total,
+                                             entry.low * factor.total,
+                                             entry.close * factor.total,
+                                             entry.close * factor.total,
+                                             (entry.volume as Price / factor.split).round() as u64));
+    }
+    Ok(adj_hist)
+}
+
+// Rewrite the adj close column of symbol history in place from the stored raw closes,
+// dividends and splits. Other columns are left as stored. Returns the number of rows changed.
+pub fn ds_adjust_symbol(ds: &DataStore, symbol: &str) -> Result<usize, Error> {
+    let content = ds.select_symbol(history::tag(), symbol)?;
+    let hist = History::parse_csv(symbol, &content)?;
+    let div = if ds.symbol_exists(dividends::tag(), symbol) {
+        Dividends::ds_select_all(ds, symbol)?
+    } else {
+        Dividends::new(symbol)
+    };
+    let splt = if ds.symbol_exists(splits::tag(), symbol) {
+        Splits::ds_select_all(ds, symbol)?
+    } else {
+        Splits::new(symbol)
+    };
+
+    let adj_hist = adjust_close(&hist, &div, &splt)?;
+    let mut adj_iter = adj_hist.entries().iter();
+
+    let mut count: usize = 0;
+    let mut csv = String::with_capacity(content.len());
+    for line in content.split('\n') {
+        if line.is_empty() {
+            continue;
+        }
+
+        if line.starts_with(char::is_alphabetic) {
+            csv.push_str(line);
+        } else {
+            let adj_entry = adj_iter.next().expect("History entry count mismatch");
+            let adj_close = format!("{:.4}", adj_entry.adj_close);
+
+            let mut fields: Vec<&str> = line.split(',').collect();
+            if fields[5].trim().parse::<Price>()? != adj_close.parse::<Price>()? {
+                fields[5] = &adj_close;
+                count += 1;
+            }
+            csv.push_str(&fields.join(","));
+        }
+        csv.push('\n');
+    }
+
+    if count > 0 {
+        ds.replace_symbol(history::tag(), symbol, &csv)?;
+    }
+    Ok(count)
+}
+
+// --------------------------------------------------------------------------------
+// Unit Tests
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::datastore::dividends::DividendEntry;
+    use crate::datastore::splits::SplitEntry;
+    use crate::util::datetime;
+    use crate::util::price_type::price_eql;
+    use crate::util::temp_file;
+    use std::env;
+
+    fn test_history() -> History {
+        let csv = "2021-02-22,100.0,104.0,98.0,102.0,102.0,1000\n\
+                   2021-02-23,102.0,106.0,100.0,100.0,100.0,1200\n\
+                   2021-02-24,51.0,53.0,49.0,50.0,50.0,2600\n\
+                   2021-02-25,50.0,52.0,48.0,49.0,49.0,2400\n\
+                   2021-02-26,49.0,51.0,47.0,48.0,48.0,2000";
+        History::parse_csv("AAPL", csv).unwrap()
+    }
+
+    fn test_dividends() -> Dividends {
+        let mut div = Dividends::new("AAPL");
+        div.add_entry(DividendEntry::new(datetime::make_date(2021, 2, 25), 0.5));
+        div
+    }
+
+    fn test_splits() -> Splits {
+        let mut splt = Splits::new("AAPL");
+        splt.add_entry(SplitEntry::new(datetime::make_date(2021, 2, 24), String::from("2:1")));
+        splt
+    }
+
+    #[test]
+    fn test_adjust_factors() {
+        let factors = adjust_factors(&test_history(), &test_dividends(), &test_splits()).unwrap();
+        assert_eq!(factors.len(), 5);
+
+        let splits: Vec<Price> = factors.iter().map(|f| f.split).collect();
+        assert_eq!(splits, vec![0.5, 0.5, 1.0, 1.0, 1.0]);
+
+        let totals: Vec<Price> = factors.iter().map(|f| f.total).collect();
+        assert!(price_eql(totals[0], 0.495));
+        assert!(price_eql(totals[1], 0.495));
+        assert!(price_eql(totals[2], 0.99));
+        assert!(price_eql(totals[3], 1.0));
+        assert!(price_eql(totals[4], 1.0));
+
+        let factors = adjust_factors(&test_history(), &Dividends::new("AAPL"), &Splits::new("AAPL")).unwrap();
+        assert!(factors.iter().all(|f| f.split == 1.0 && f.total == 1.0));
+    }
+
+    #[test]
+    fn test_adjust_factors_error() {
+        let mut div = Dividends::new("AAPL");
+        div.add_entry(DividendEntry::new(datetime::make_date(2021, 2, 25), 50.0));
+        assert_eq!(format!("{}", adjust_factors(&test_history(), &div, &Splits::new("AAPL")).err().unwrap()),
+                   "Dividend 50 on 2021-02-25 is not less than close 50 on 2021-02-24");
+
+        let mut splt = Splits::new("AAPL");
+        splt.add_entry(SplitEntry::new(datetime::make_date(2021, 2, 24), String::from("2")));
+        assert_eq!(format!("{}", adjust_factors(&test_history(), &Dividends::new("AAPL"), &splt).err().unwrap()),
+                   "SplitEntry: Invalid split '2'");
+    }
+
+    #[test]
+    fn test_adjust_close() {
+        let hist = adjust_close(&test_history(), &test_dividends(), &test_splits()).unwrap();
+        assert_eq!(hist.symbol(), "AAPL");
+        assert_eq!(hist.count(), 5);
+
+        let entries = hist.entries();
+        assert_eq!(entries[0].close, 102.0);
+        assert!(price_eql(entries[0].adj_close, 50.49));
+        assert_eq!(entries[0].volume, 1000);
+        assert!(price_eql(entries[1].adj_close, 49.5));
+        assert!(price_eql(entries[2].adj_close, 49.5));
+        assert!(price_eql(entries[3].adj_close, 49.0));
+        assert!(price_eql(entries[4].adj_close, 48.0));
+    }
+
+    #[test]
+    fn test_adjust_ohlc() {
+        let hist = adjust_ohlc(&test_history(), &test_dividends(), &test_splits()).unwrap();
+        assert_eq!(hist.count(), 5);
+
+        let entries = hist.entries();
+        assert!(price_eql(entries[1].open, 50.49));
+        assert!(price_eql(entries[1].high, 52.47));
+        assert!(price_eql(entries[1].low, 49.5));
+        assert!(price_eql(entries[1].close, 49.5));
+        assert!(price_eql(entries[1].adj_close, 49.5));
+        assert_eq!(entries[1].volume, 2400);
+        assert!(price_eql(entries[2].open, 50.49));
+        assert_eq!(entries[2].volume, 2600);
+        assert!(price_eql(entries[4].close, 48.0));
+    }
+
+    #[test]
+    fn test_ds_adjust_symbol() {
+        let root = env::temp_dir();
+        let base_path = temp_file::make_path("test_adjust_symbol");
+        let ds = DataStore::new(root.to_str().unwrap(), "test_adjust_symbol");
+        ds.create().unwrap();
+
+        let csv = "Date,Open,High,Low,Close,Adj Close,Volume\n\
+                   2021-02-22,100.0,104.0,98.0,102.0,102.0,1000\n\
+                   2021-02-23,102.0,106.0,100.0,100.0,100.0,1200\n\
+                   2021-02-24,51.0,53.0,49.0,50.0,50.0,2600\n";
+        ds.insert_symbol(history::tag(), "AAPL", csv).unwrap();
+        ds.insert_symbol(splits::tag(), "AAPL", "2021-02-24,2:1\n").unwrap();
+
+        assert_eq!(ds_adjust_symbol(&ds, "AAPL").unwrap(), 2);
+        assert_eq!(ds.select_symbol(history::tag(), "AAPL").unwrap(),
+                   "2021-02-22,100.0,104.0,98.0,102.0,51.0000,1000\n\
+                    2021-02-23,102.0,106.0,100.0,100.0,50.0000,1200\n\
+                    2021-02-24,51.0,53.0,49.0,50.0,50.0,2600\n");
+        assert_eq!(ds_adjust_symbol(&ds, "AAPL").unwrap(), 0);
+
+        ds.delete().unwrap();
+        assert!(!base_path.exists());
+    }
+}
@@ -170,6 +170,15 @@ impl DataStore {
         Ok(count)
     }
 
+    // Replace symbol content, written to a temporary file first and renamed over the old file.
+    pub fn replace_symbol(&self, tag: &str, symbol: &str, csv: &str) -> Result<(), Error> {
+        let sym_file = DataStore::make_symbol_file(&self.base_path, tag, symbol);
+        let tmp_file = sym_file.with_extension("csv.tmp");
+        fs::write(&tmp_file, csv)?;
+        fs::rename(&tmp_file, &sym_file)?;
+        Ok(())
+    }
+
     pub fn drop_symbol(&self, tag: &str, symbol: &str) -> Result<(), Error> {
         let sym_file = DataStore::make_symbol_file(&self.base_path, tag, symbol);
         fs::remove_file(sym_file.as_path())?;
@@ -356,6 +365,32 @@ mod tests {
         assert!(!base_path.exists());
     }
 
+    #[test]
+    fn test_datastore_replace_select() {
+        let root = env::temp_dir();
+        let base_path = temp_file::make_path("test_replace_select");
+        let ds = DataStore::new(root.to_str().unwrap(), "test_replace_select");
+
+        let tag = "tst";
+        let symbol = "TEST";
+        let csv = "1,2,3,4,5\n\
+                   6,7,8,9,10\n";
+
+        ds.create().unwrap();
+        assert_eq!(ds.insert_symbol(tag, symbol, csv).unwrap(), 2);
+
+        ds.replace_symbol(tag, symbol, "1,2,3,4,6\n6,7,8,9,11\n").unwrap();
+        let data = ds.select_symbol(tag, symbol).unwrap();
+        let dvec: Vec<&str> = data.split('\n').collect();
+        assert_eq!(dvec.len(), 3);
+        assert_eq!(dvec[0], "1,2,3,4,6");
+        assert_eq!(dvec[1], "6,7,8,9,11");
+        assert!(!temp_file::make_path("test_replace_select/tst_TEST.csv.tmp").exists());
+
+        ds.delete().unwrap();
+        assert!(!base_path.exists());
+    }
+
     #[test]
     fn test_datastore_insert_select_last() {
         let root = env::temp_dir();
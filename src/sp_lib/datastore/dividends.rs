@@ -138,6 +138,11 @@ impl Dividends {
     pub fn count(&self) -> usize {
         self.entries.len()
     }
+
+    #[inline(always)]
+    pub fn add_entry(&mut self, entry: DividendEntry) {
+        self.entries.push(entry);
+    }
 }
 
 
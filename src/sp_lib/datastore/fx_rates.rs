@@ -0,0 +1,220 @@
+use crate::util::error::Error;
+use crate::util::datetime;
+use crate::util::datetime::SPDate;
+use crate::util::price_type::PriceType;
+use crate::datastore::datastore::DataStore;
+use crate::datastore::history::History;
+
+pub type Price = PriceType;
+
+// --------------------------------------------------------------------------------
+// FX Rates Tag
+
+#[inline(always)]
+pub fn tag() -> &'static str {
+    "fxrates"
+}
+
+// Datastore symbol of the rate series converting currency into base, e.g. EURUSD.
+#[inline(always)]
+pub fn pair_symbol(currency: &str, base: &str) -> String {
+    format!("{}{}", currency, base)
+}
+
+// Market data provider symbol of a currency pair history, e.g. EURUSD=X.
+#[inline(always)]
+pub fn provider_symbol(pair: &str) -> String {
+    format!("{}=X", pair)
+}
+
+// Rates csv made of history dates and closes.
+pub fn csv_from_history(hist: &History) -> String {
+    let mut csv = String::new();
+    for entry in hist.entries().iter().filter(|entry| entry.close > 0.0) {
+        csv.push_str(&format!("{},{}\n", entry.date.format("%Y-%m-%d"), entry.close));
+    }
+    csv
+}
+
+// --------------------------------------------------------------------------------
+// FX Rate Entry
+
+pub struct FxRateEntry {
+    pub date: SPDate,
+    pub rate: Price
+}
+
+impl FxRateEntry {
+    pub fn new(date: SPDate, rate: Price) -> Self {
+        FxRateEntry {
+            date,
+            rate
+        }
+    }
+
+    pub fn parse_csv(csv: &str) -> Result<Self, Error> {
+        let values: Vec<&str> = csv.split(',').map(|field| field.trim()).collect();
+        if values.len() == FxRateEntry::number_of_fields() {
+            Ok(FxRateEntry {
+                date: datetime::parse_date(values[0])?,
+                rate: values[1].parse::<Price>()?
+            })
+        }
+        else {
+            Err(format!("FxRateEntry: Invalid csv data length={} expected=2", values.len()).into())
+        }
+    }
+
+    #[inline(always)]
+    pub fn number_of_fields() -> usize {
+        2
+    }
+}
+
+// --------------------------------------------------------------------------------
+// FX Rates
+
+pub struct FxRates {
+    symbol: String,
+    entries: Vec<FxRateEntry>
+}
+
+impl FxRates {
+    pub fn new(symbol: &str) -> Self {
+        FxRates {
+            symbol: String::from(symbol),
+            entries: Vec::new()
+        }
+    }
+
+    pub fn parse_csv(symbol: &str, csv: &str) -> Result<Self, Error> {
+        let mut fx = FxRates::new(symbol);
+        for line in csv.split('\n') {
+            if line.is_empty() || line.starts_with(char::is_alphabetic) {
+                continue;
+            }
+            fx.entries.push(FxRateEntry::parse_csv(line)?);
+        }
+        Ok(fx)
+    }
+
+    pub fn ds_select_all(ds: &DataStore, symbol: &str) -> Result<Self, Error> {
+        let content = ds.select_symbol(tag(), symbol)?;
+        FxRates::parse_csv(symbol, &content)
+    }
+
+    pub fn ds_select_last(ds: &DataStore, symbol: &str) -> Result<Self, Error> {
+        let content = ds.select_last(tag(), symbol)?;
+        FxRates::parse_csv(symbol, &content)
+    }
+
+    pub fn check_csv(csv: &str) -> Result<(), Error> {
+        let fx = FxRates::parse_csv("fxrates_check", csv)?;
+        let entries = fx.entries;
+        if !entries.is_empty() {
+            let mut last_date = entries[0].date;
+            for entry in entries.iter().skip(1) {
+                let curr_date = entry.date;
+                datetime::check_dup_or_back_gap(&last_date, &curr_date)?;
+                last_date = curr_date;
+            }
+        }
+        Ok(())
+    }
+
+    // Latest rate on or before the given date.
+    pub fn rate_on(&self, date: &SPDate) -> Option<Price> {
+        let pos = self.entries.partition_point(|entry| entry.date <= *date);
+        if pos > 0 {
+            Some(self.entries[pos - 1].rate)
+        } else {
+            None
+        }
+    }
+
+    #[inline(always)]
+    pub fn symbol(&self) -> &str {
+        &self.symbol
+    }
+
+    #[inline(always)]
+    pub fn entries(&self) -> &Vec<FxRateEntry> {
+        &self.entries
+    }
+
+    #[inline(always)]
+    pub fn count(&self) -> usize {
+        self.entries.len()
+    }
+
+    #[inline(always)]
+    pub fn add_entry(&mut self, entry: FxRateEntry) {
+        self.entries.push(entry);
+    }
+}
+
+// --------------------------------------------------------------------------------
+// Unit Tests
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fx_rate_entry() {
+        let entry = FxRateEntry::parse_csv("2021-02-25, 1.2175 \n").unwrap();
+        check_rate(&entry, datetime::make_date(2021, 2, 25), 1.2175);
+
+        assert!(FxRateEntry::parse_csv("2021-02-25").is_err());
+        assert!(FxRateEntry::parse_csv("2021-02-25,1.2,1.3").is_err());
+    }
+
+    #[test]
+    fn test_fx_rates_parse_csv() {
+        let csv = "Date,Rate\n\
+                   2021-02-24,1.2150\n\
+                   \n\
+                   2021-02-26,1.2075";
+        let fx = FxRates::parse_csv("EURUSD", csv).unwrap();
+        assert_eq!(fx.symbol(), "EURUSD");
+        assert_eq!(fx.count(), 2);
+        check_rate(&fx.entries()[0], datetime::make_date(2021, 2, 24), 1.2150);
+        check_rate(&fx.entries()[1], datetime::make_date(2021, 2, 26), 1.2075);
+    }
+
+    #[test]
+    fn test_fx_rates_rate_on() {
+        let fx = FxRates::parse_csv("EURUSD", "2021-02-24,1.2150\n2021-02-26,1.2075").unwrap();
+        assert_eq!(fx.rate_on(&datetime::make_date(2021, 2, 23)), None);
+        assert_eq!(fx.rate_on(&datetime::make_date(2021, 2, 24)), Some(1.2150));
+        assert_eq!(fx.rate_on(&datetime::make_date(2021, 2, 25)), Some(1.2150));
+        assert_eq!(fx.rate_on(&datetime::make_date(2021, 2, 26)), Some(1.2075));
+        assert_eq!(fx.rate_on(&datetime::make_date(2021, 3, 1)), Some(1.2075));
+    }
+
+    #[test]
+    fn test_fx_symbols() {
+        assert_eq!(pair_symbol("EUR", "USD"), "EURUSD");
+        assert_eq!(provider_symbol("EURUSD"), "EURUSD=X");
+    }
+
+    #[test]
+    fn test_csv_from_history() {
+        let hist = History::parse_csv("EURUSD=X", "2021-02-24,1.21,1.22,1.20,1.215,1.215,0\n\
+                                                   2021-02-25,1.22,1.22,1.21,0.0,0.0,0\n\
+                                                   2021-02-26,1.21,1.21,1.20,1.2075,1.2075,0").unwrap();
+        assert_eq!(csv_from_history(&hist), "2021-02-24,1.215\n2021-02-26,1.2075\n");
+    }
+
+    #[test]
+    fn test_check_csv() {
+        assert!(FxRates::check_csv("2021-02-24,1.2150\n2021-02-26,1.2075").is_ok());
+        assert_eq!(format!("{}", FxRates::check_csv("2021-02-24,1.2150\n2021-02-24,1.2075").err().unwrap()),
+                   "Duplicate date 2021-02-24");
+    }
+
+    fn check_rate(entry: &FxRateEntry, date: SPDate, rate: Price) {
+        assert_eq!(entry.date, date);
+        assert_eq!(entry.rate, rate);
+    }
+}
@@ -1,6 +1,6 @@
 use crate::util::error::Error;
 use crate::util::datetime;
-use crate::util::datetime::SPDate;
+use crate::util::datetime::{Period, SPDate};
 use crate::util::price_type::PriceType;
 use crate::datastore::datastore::DataStore;
 
@@ -159,6 +159,41 @@ impl History {
     pub fn count(&self) -> usize {
         self.entries.len()
     }
+
+    #[inline(always)]
+    pub fn add_entry(&mut self, entry: HistoryEntry) {
+        self.entries.push(entry);
+    }
+
+    // Resample entries in date order into one bar per period, dated at the first entry of the period,
+    // with first open, max high, min low, last close and adj close, and summed volume.
+    pub fn resample(&self, period: Period) -> History {
+        let mut hist = History::new(&self.symbol);
+        let mut bar_start = datetime::earliest_date();
+        for entry in self.entries.iter() {
+            let start = datetime::period_start(&entry.date, period);
+            match hist.entries.last_mut() {
+                Some(bar) if start == bar_start => {
+                    bar.high = bar.high.max(entry.high);
+                    bar.low = bar.low.min(entry.low);
+                    bar.close = entry.close;
+                    bar.adj_close = entry.adj_close;
+                    bar.volume += entry.volume;
+                },
+                _ => {
+                    bar_start = start;
+                    hist.add_entry(HistoryEntry::new(entry.date,
+                                                     entry.open,
+                                                     entry.high,
+                                                     entry.low,
+                                                     entry.close,
+                                                     entry.adj_close,
+                                                     entry.volume));
+                }
+            }
+        }
+        hist
+    }
 }
 
 // --------------------------------------------------------------------------------
@@ -294,6 +329,38 @@ mod tests {
         };
     }
 
+    #[test]
+    fn test_history_resample() {
+        let csv = "2021-03-29,25.0,30.0,20.0,26.0,25.0,10000\n\
+                   2021-03-31,26.1,31.0,22.0,24.0,23.0,9000\n\
+                   2021-04-01,24.9,32.0,24.0,28.0,27.0,11000\n\
+                   2021-04-05,28.0,29.0,21.0,22.0,21.0,8000\n\
+                   2021-07-01,22.0,23.0,19.0,20.0,19.0,7000";
+        let hist = History::parse_csv("AAPL", csv).unwrap();
+
+        let daily = hist.resample(Period::Daily);
+        assert_eq!(daily.symbol(), "AAPL");
+        assert_eq!(daily.count(), 5);
+        check_entry(&daily.entries()[3], datetime::make_date(2021, 4, 5), 28.0, 29.0, 21.0, 22.0, 21.0, 8000);
+
+        let weekly = hist.resample(Period::Weekly);
+        assert_eq!(weekly.count(), 3);
+        check_entry(&weekly.entries()[0], datetime::make_date(2021, 3, 29), 25.0, 32.0, 20.0, 28.0, 27.0, 30000);
+        check_entry(&weekly.entries()[1], datetime::make_date(2021, 4, 5), 28.0, 29.0, 21.0, 22.0, 21.0, 8000);
+
+        let monthly = hist.resample(Period::Monthly);
+        assert_eq!(monthly.count(), 3);
+        check_entry(&monthly.entries()[0], datetime::make_date(2021, 3, 29), 25.0, 31.0, 20.0, 24.0, 23.0, 19000);
+        check_entry(&monthly.entries()[1], datetime::make_date(2021, 4, 1), 24.9, 32.0, 21.0, 22.0, 21.0, 19000);
+
+        let quarterly = hist.resample(Period::Quarterly);
+        assert_eq!(quarterly.count(), 3);
+        check_entry(&quarterly.entries()[1], datetime::make_date(2021, 4, 1), 24.9, 32.0, 21.0, 22.0, 21.0, 19000);
+        check_entry(&quarterly.entries()[2], datetime::make_date(2021, 7, 1), 22.0, 23.0, 19.0, 20.0, 19.0, 7000);
+
+        assert_eq!(History::new("AAPL").resample(Period::Weekly).count(), 0);
+    }
+
     fn check_entry(entry: &HistoryEntry,
                    date: SPDate,
                    open: Price,
@@ -40,6 +40,18 @@ impl SplitEntry {
         }
     }
 
+    // Shares after the split for each share before, e.g. 4.0 for "4:1" and 0.1 for "1:10".
+    pub fn ratio(&self) -> Result<f64, Error> {
+        if let Some((num, den)) = self.split.split_once(':') {
+            if let (Ok(num), Ok(den)) = (num.trim().parse::<f64>(), den.trim().parse::<f64>()) {
+                if num > 0.0 && den > 0.0 {
+                    return Ok(num / den);
+                }
+            }
+        }
+        Err(format!("SplitEntry: Invalid split '{}'", self.split).into())
+    }
+
     #[inline(always)]
     pub fn number_of_fields() -> usize {
         2
@@ -135,6 +147,20 @@ impl Splits {
     pub fn count(&self) -> usize {
         self.entries.len()
     }
+
+    #[inline(always)]
+    pub fn add_entry(&mut self, entry: SplitEntry) {
+        self.entries.push(entry);
+    }
+
+    // Product of the ratios of the splits after the given date.
+    pub fn ratio_after(&self, date: &SPDate) -> Result<f64, Error> {
+        let mut ratio = 1.0;
+        for entry in self.entries.iter().filter(|entry| entry.date > *date) {
+            ratio *= entry.ratio()?;
+        }
+        Ok(ratio)
+    }
 }
 
 // --------------------------------------------------------------------------------
@@ -167,6 +193,28 @@ mod tests {
         assert!(SplitEntry::parse_csv(&csv).is_err());
     }
 
+    #[test]
+    fn test_split_entry_ratio() {
+        let ratio = |split: &str| SplitEntry::new(datetime::make_date(2021, 2, 25), String::from(split)).ratio();
+        assert_eq!(ratio("4:1").unwrap(), 4.0);
+        assert_eq!(ratio("3:2").unwrap(), 1.5);
+        assert_eq!(ratio("1:10").unwrap(), 0.1);
+        assert_eq!(format!("{}", ratio("4").err().unwrap()), "SplitEntry: Invalid split '4'");
+        assert_eq!(format!("{}", ratio("4:0").err().unwrap()), "SplitEntry: Invalid split '4:0'");
+        assert_eq!(format!("{}", ratio("a:b").err().unwrap()), "SplitEntry: Invalid split 'a:b'");
+    }
+
+    #[test]
+    fn test_splits_ratio_after() {
+        let splt = Splits::parse_csv("SYMB", "2019-02-24,2:1\n2020-02-21,3:2").unwrap();
+        assert_eq!(splt.ratio_after(&datetime::make_date(2019, 1, 2)).unwrap(), 3.0);
+        assert_eq!(splt.ratio_after(&datetime::make_date(2019, 2, 24)).unwrap(), 1.5);
+        assert_eq!(splt.ratio_after(&datetime::make_date(2020, 2, 21)).unwrap(), 1.0);
+
+        let splt = Splits::parse_csv("SYMB", "2019-02-24,bad").unwrap();
+        assert!(splt.ratio_after(&datetime::make_date(2019, 1, 2)).is_err());
+    }
+
     #[test]
     fn test_splits_parse_csv() {
         let csv = "2019-02-24,2:1\n\
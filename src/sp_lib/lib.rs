@@ -1,5 +1,6 @@
 pub mod datastore;
 pub mod portfolio;
+pub mod provider;
 pub mod report;
 pub mod stats;
 pub mod util;
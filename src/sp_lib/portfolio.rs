@@ -9,3 +9,18 @@ pub mod stocks_reader;
 pub mod stocks_update;
 pub mod closed_position;
 pub mod closed_positions_reader;
+pub mod lot_method;
+pub mod transaction;
+pub mod transactions_reader;
+pub mod ledger;
+pub mod cash_flow;
+pub mod cash_flows_reader;
+pub mod performance;
+pub mod benchmark;
+pub mod currency;
+pub mod tax_lots;
+pub mod wash_sales;
+pub mod harvest;
+pub mod target;
+pub mod targets_reader;
+pub mod rebalance;
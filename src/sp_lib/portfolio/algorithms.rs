@@ -2,7 +2,7 @@ use std::cmp::Ordering;
 use std::collections::HashMap;
 use crate::util::error::Error;
 use crate::portfolio::symbol_trait::GetSymbol;
-use crate::portfolio::stock::{Price, Stock, StockList};
+use crate::portfolio::stock::{Price, Quantity, Stock, StockList};
 use crate::util::{price_type, datetime};
 use crate::portfolio::stocks_filter;
 
@@ -52,10 +52,10 @@ pub fn stock_groupby<T>(stocks: &StockList,
 }
 
 // Group by stock symbol, and calcuate aggregate quantity, base value and current value.
-pub fn stock_aggregate(stocks: &StockList) -> HashMap<String, (u32, Price, Price)> {
+pub fn stock_aggregate(stocks: &StockList) -> HashMap<String, (Quantity, Price, Price)> {
     stock_groupby(
         stocks,
-        |_| (0, 0.0, 0.0),
+        |_| (0.0, 0.0, 0.0),
         |stock, size_prices| {
             let sp = *size_prices;
             (sp.0 + stock.quantity, sp.1 + stock.base_notional(), sp.2 + stock.latest_notional())
@@ -63,16 +63,26 @@ pub fn stock_aggregate(stocks: &StockList) -> HashMap<String, (u32, Price, Price
 }
 
 // Group by stock symbol, and calculate aggregate quantity, cumulative dividend and base notional
-pub fn dividend_aggregate(stocks: &StockList) -> HashMap<String, (u32, Price, Price)> {
+pub fn dividend_aggregate(stocks: &StockList) -> HashMap<String, (Quantity, Price, Price)> {
     stock_groupby(
         stocks,
-        |_| (0, 0.0, 0.0),
+        |_| (0.0, 0.0, 0.0),
         |stock, size_prices| {
             let sp = *size_prices;
             (sp.0 + stock.quantity, sp.1 + stock.cum_dividend, sp.2 + stock.base_notional())
         })
 }
 
+// Group by account, and calculate lot count, base value, current value and cumulative dividend.
+pub fn account_aggregate(stocks: &StockList) -> HashMap<String, (usize, Price, Price, Price)> {
+    let mut groupby: HashMap<String, (usize, Price, Price, Price)> = HashMap::new();
+    for stock in stocks.iter() {
+        let entry = groupby.entry(stock.account.clone()).or_insert((0, 0.0, 0.0, 0.0));
+        *entry = (entry.0 + 1, entry.1 + stock.base_notional(), entry.2 + stock.latest_notional(), entry.3 + stock.cum_dividend);
+    }
+    groupby
+}
+
 pub fn sort_stocks(stocks: &mut StockList, order_by: &str, desc: bool) -> Result<(), Error> {
     match (order_by, desc) {
         ("symbol", false) => stocks.sort_by(|lhs, rhs| lhs.symbol.cmp(&rhs.symbol)),
@@ -90,8 +100,11 @@ pub fn sort_stocks(stocks: &mut StockList, order_by: &str, desc: bool) -> Result
         ("net", false) => stocks.sort_by(|lhs, rhs| price_type::price_cmp(lhs.net_price(), rhs.net_price())),
         ("net", true)  => stocks.sort_by(|lhs, rhs| price_type::price_cmp(rhs.net_price(), lhs.net_price())),
 
-        ("size", false) => stocks.sort_by(|lhs, rhs| lhs.quantity.cmp(&rhs.quantity)),
-        ("size", true)  => stocks.sort_by(|lhs, rhs| rhs.quantity.cmp(&lhs.quantity)),
+        ("size", false) => stocks.sort_by(|lhs, rhs| price_type::price_cmp(lhs.quantity, rhs.quantity)),
+        ("size", true)  => stocks.sort_by(|lhs, rhs| price_type::price_cmp(rhs.quantity, lhs.quantity)),
+
+        ("account", false) => stocks.sort_by(|lhs, rhs| lhs.account.cmp(&rhs.account)),
+        ("account", true)  => stocks.sort_by(|lhs, rhs| rhs.account.cmp(&lhs.account)),
 
         ("type", false) => stocks.sort_by(|lhs, rhs| lhs.stype.cmp(&rhs.stype)),
         ("type", true)  => stocks.sort_by(|lhs, rhs| rhs.stype.cmp(&lhs.stype)),
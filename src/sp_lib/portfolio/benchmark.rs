@@ -0,0 +1,196 @@
+use std::collections::BTreeMap;
+
+use crate::portfolio::performance::MarketHistory;
+use crate::portfolio::stock::{Price, StockList};
+use crate::portfolio::stock_type::StockType;
+use crate::util::datetime::SPDate;
+use crate::util::error::Error;
+
+// --------------------------------------------------------------------------------
+// Benchmark Comparison
+//
+// Value of a holding against the value of the same cash invested in a benchmark
+// on the same date. Benchmark values use closes only, without dividends.
+
+pub struct Comparison {
+    pub symbol: String,
+    pub date: SPDate,         // Buy date, earliest buy date when aggregated
+    pub cost: Price,          // Base notional
+    pub value: Price,         // Latest notional
+    pub bench_value: Price    // Latest notional of the cost invested in the benchmark
+}
+
+pub type ComparisonList = Vec<Comparison>;
+
+impl Comparison {
+    #[inline(always)]
+    pub fn pct_change(&self) -> Price {
+        pct_change(self.cost, self.value)
+    }
+
+    #[inline(always)]
+    pub fn bench_pct_change(&self) -> Price {
+        pct_change(self.cost, self.bench_value)
+    }
+
+    // Percent return in excess of the benchmark return
+    #[inline(always)]
+    pub fn excess_pct(&self) -> Price {
+        self.pct_change() - self.bench_pct_change()
+    }
+
+    fn add(&mut self, other: &Comparison) {
+        self.date = self.date.min(other.date);
+        self.cost += other.cost;
+        self.value += other.value;
+        self.bench_value += other.bench_value;
+    }
+}
+
+// Compare each lot to the benchmark from its buy date through its latest price date. Index stocks are skipped.
+pub fn compare_lots(stocks: &StockList, mkt: &MarketHistory, bench: &str) -> Result<ComparisonList, Error> {
+    let bench_close = |date: &SPDate| match mkt.close(bench, date) {
+        Some(close) if close > 0.0 => Ok(close),
+        _ => Err(format!("Benchmark {} has no close on or before {}", bench, date.format("%Y-%m-%d")))
+    };
+
+    let mut lots = ComparisonList::new();
+    for stock in stocks.iter().filter(|s| s.stype != StockType::Index) {
+        let cost = stock.base_notional();
+        let bench_qty = cost / bench_close(&stock.date)?;
+        lots.push(Comparison {
+            symbol: stock.symbol.clone(),
+            date: stock.date,
+            cost,
+            value: stock.latest_notional(),
+            bench_value: bench_qty * bench_close(&stock.latest_date)?
+        });
+    }
+    Ok(lots)
+}
+
+// Aggregate lot comparisons by symbol, ordered by symbol.
+pub fn compare_by_symbol(lots: &[Comparison]) -> ComparisonList {
+    let mut groups: BTreeMap<&str, Comparison> = BTreeMap::new();
+    for lot in lots {
+        match groups.get_mut(lot.symbol.as_str()) {
+            Some(group) => group.add(lot),
+            None => { groups.insert(&lot.symbol, copy_comparison(lot, &lot.symbol)); }
+        }
+    }
+    groups.into_values().collect()
+}
+
+// Aggregate all lot comparisons under the given name.
+pub fn compare_total(lots: &[Comparison], name: &str) -> Option<Comparison> {
+    let (first, rest) = lots.split_first()?;
+    let mut total = copy_comparison(first, name);
+    for lot in rest {
+        total.add(lot);
+    }
+    Some(total)
+}
+
+// --------------------------------------------------------------------------------
+// Private
+
+fn copy_comparison(cmp: &Comparison, symbol: &str) -> Comparison {
+    Comparison {
+        symbol: String::from(symbol),
+        date: cmp.date,
+        cost: cmp.cost,
+        value: cmp.value,
+        bench_value: cmp.bench_value
+    }
+}
+
+#[inline(always)]
+fn pct_change(base: Price, value: Price) -> Price {
+    if base > 0.0 { 100.0 * (value - base) / base } else { 0.0 }
+}
+
+// --------------------------------------------------------------------------------
+// Unit Tests
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::portfolio::stock::Stock;
+    use crate::util::datetime::make_date;
+    use crate::util::price_type::price_eql;
+
+    #[test]
+    fn test_compare_lots() {
+        let lots = compare_lots(&make_stocks(), &make_market(), "SPY").unwrap();
+        assert_eq!(lots.len(), 3);
+
+        assert_eq!(lots[0].symbol, "AAPL");
+        assert_eq!(lots[0].cost, 1000.0);
+        assert_eq!(lots[0].value, 1200.0);
+        assert_eq!(lots[0].bench_value, 1100.0);
+        assert!(price_eql(lots[0].pct_change(), 20.0));
+        assert!(price_eql(lots[0].bench_pct_change(), 10.0));
+        assert!(price_eql(lots[0].excess_pct(), 10.0));
+
+        assert_eq!(lots[1].symbol, "AAPL");
+        assert_eq!(lots[1].bench_value, 352.0);
+        assert!(price_eql(lots[1].excess_pct(), 50.0 + 12.0));
+
+        assert_eq!(lots[2].symbol, "DELL");
+        assert!(price_eql(lots[2].excess_pct(), -10.0 - 10.0));
+    }
+
+    #[test]
+    fn test_compare_lots_missing_close() {
+        let mut stocks = make_stocks();
+        stocks.push(Stock::new(String::from("DELL"), StockType::Cash, make_date(2020, 12, 31), 1.0, 10.0));
+        assert_eq!(format!("{}", compare_lots(&stocks, &make_market(), "SPY").err().unwrap()),
+                   "Benchmark SPY has no close on or before 2020-12-31");
+        assert_eq!(format!("{}", compare_lots(&stocks, &make_market(), "QQQ").err().unwrap()),
+                   "Benchmark QQQ has no close on or before 2021-01-04");
+    }
+
+    #[test]
+    fn test_compare_by_symbol() {
+        let lots = compare_lots(&make_stocks(), &make_market(), "SPY").unwrap();
+
+        let groups = compare_by_symbol(&lots);
+        assert_eq!(groups.len(), 2);
+        assert_eq!(groups[0].symbol, "AAPL");
+        assert_eq!(groups[0].date, make_date(2021, 1, 4));
+        assert_eq!(groups[0].cost, 1400.0);
+        assert_eq!(groups[0].value, 1800.0);
+        assert_eq!(groups[0].bench_value, 1452.0);
+        assert_eq!(groups[1].symbol, "DELL");
+        assert_eq!(groups[1].cost, 500.0);
+
+        let total = compare_total(&lots, "Total").unwrap();
+        assert_eq!(total.symbol, "Total");
+        assert_eq!(total.cost, 1900.0);
+        assert_eq!(total.value, 2250.0);
+        assert_eq!(total.bench_value, 2002.0);
+        assert!(compare_total(&[], "Total").is_none());
+    }
+
+    fn make_stocks() -> StockList {
+        let make_stock = |sym: &str, stype, date, qty, base, latest| {
+            let mut stock = Stock::new(String::from(sym), stype, date, qty, base);
+            stock.set_latest_price(latest, make_date(2021, 1, 8));
+            stock
+        };
+        vec![
+            make_stock("AAPL", StockType::Cash, make_date(2021, 1, 4), 10.0, 100.0, 120.0),
+            make_stock("AAPL", StockType::Cash, make_date(2021, 1, 6), 5.0, 80.0, 120.0),
+            make_stock("DELL", StockType::Cash, make_date(2021, 1, 4), 10.0, 50.0, 45.0),
+            make_stock("SPY", StockType::Index, make_date(2021, 1, 4), 1.0, 200.0, 220.0)
+        ]
+    }
+
+    fn make_market() -> MarketHistory {
+        let mut mkt = MarketHistory::new();
+        mkt.add_close("SPY", make_date(2021, 1, 4), 200.0);
+        mkt.add_close("SPY", make_date(2021, 1, 6), 250.0);
+        mkt.add_close("SPY", make_date(2021, 1, 8), 220.0);
+        mkt
+    }
+}
@@ -0,0 +1,312 @@
+use std::fmt;
+
+use crate::datastore::datastore::DataStore;
+use crate::datastore::dividends::{self, Dividends};
+use crate::util::datetime::SPDate;
+use crate::util::error::Error;
+use crate::util::fixed_price::{FixedPrice, FP_0};
+use crate::portfolio::closed_position::ClosedPositionList;
+use crate::portfolio::stock::StockList;
+use crate::portfolio::stock_type::StockType;
+use crate::portfolio::stocks_update;
+use crate::portfolio::transaction::{TransactionList, TransactionType};
+
+pub type Price = FixedPrice;
+
+// --------------------------------------------------------------------------------
+// Cash Flow Type
+
+#[derive(Debug, Copy, Clone)]
+#[derive(PartialOrd, Ord, PartialEq, Eq)]
+pub enum CashFlowType {
+    Deposit,
+    Withdrawal,
+    Interest,
+    Fee,
+    Dividend,
+    Purchase, // Cash paid for a buy, including fee
+    Proceeds  // Cash received from a sell, net of fee
+}
+
+pub fn cashflowtype2str(cft: CashFlowType) -> &'static str {
+    match cft {
+        CashFlowType::Deposit => "deposit",
+        CashFlowType::Withdrawal => "withdrawal",
+        CashFlowType::Interest => "interest",
+        CashFlowType::Fee => "fee",
+        CashFlowType::Dividend => "dividend",
+        CashFlowType::Purchase => "purchase",
+        CashFlowType::Proceeds => "proceeds"
+    }
+}
+
+pub fn str2cashflowtype(cftstr: &str) -> Result<CashFlowType, Error> {
+    match cftstr.to_lowercase().as_str() {
+        "deposit" => Ok(CashFlowType::Deposit),
+        "withdrawal" => Ok(CashFlowType::Withdrawal),
+        "interest" => Ok(CashFlowType::Interest),
+        "fee" => Ok(CashFlowType::Fee),
+        "dividend" => Ok(CashFlowType::Dividend),
+        "purchase" => Ok(CashFlowType::Purchase),
+        "proceeds" => Ok(CashFlowType::Proceeds),
+        _ => Err(format!("Unknown cash flow type '{}'", cftstr).into())
+    }
+}
+
+impl fmt::Display for CashFlowType {
+    fn fmt(self: &CashFlowType, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", cashflowtype2str(*self))
+    }
+}
+
+// --------------------------------------------------------------------------------
+// Cash Flow
+
+#[derive(Clone)]
+pub struct CashFlow {
+    pub date: SPDate,
+    pub ftype: CashFlowType,
+    pub amount: Price,    // Unsigned, direction is given by the flow type
+    pub symbol: String,   // Related symbol, if any
+    pub currency: String  // Currency of the amount, empty for base currency
+}
+
+pub type CashFlowList = Vec<CashFlow>;
+
+impl CashFlow {
+    pub fn new(date: SPDate, ftype: CashFlowType, amount: Price, symbol: String) -> Self {
+        CashFlow {
+            date,
+            ftype,
+            amount,
+            symbol,
+            currency: String::new()
+        }
+    }
+
+    #[inline(always)]
+    pub fn is_inflow(&self) -> bool {
+        !matches!(self.ftype, CashFlowType::Withdrawal | CashFlowType::Fee | CashFlowType::Purchase)
+    }
+
+    // Amount added to the cash balance, negative for outflows
+    #[inline(always)]
+    pub fn signed_amount(&self) -> Price {
+        if self.is_inflow() { self.amount } else { -self.amount }
+    }
+
+    // External flows move money into or out of the portfolio, as opposed to flows between cash and holdings.
+    #[inline(always)]
+    pub fn is_external(&self) -> bool {
+        matches!(self.ftype, CashFlowType::Deposit | CashFlowType::Withdrawal)
+    }
+
+    // Income flows are returns earned on cash, as opposed to transfers into or out of cash.
+    #[inline(always)]
+    pub fn is_income(&self) -> bool {
+        matches!(self.ftype, CashFlowType::Interest | CashFlowType::Fee)
+    }
+}
+
+impl fmt::Display for CashFlow {
+    fn fmt(self: &CashFlow, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "CashFlow({} {} {})", self.date.format("%Y-%m-%d"), self.ftype, self.signed_amount())
+    }
+}
+
+// --------------------------------------------------------------------------------
+// Cash Flow Functions
+
+// Purchase and proceeds flows for buys and sells in a transaction ledger.
+pub fn transaction_cash_flows(transactions: &TransactionList) -> CashFlowList {
+    transactions
+        .iter()
+        .map(|tx| match tx.ttype {
+            TransactionType::Buy => CashFlow::new(tx.date, CashFlowType::Purchase, tx.notional() + tx.fee, tx.symbol.clone()),
+            TransactionType::Sell => CashFlow::new(tx.date, CashFlowType::Proceeds, tx.notional() - tx.fee, tx.symbol.clone())
+        })
+        .collect()
+}
+
+// Proceeds and dividend flows of closed positions on their exit dates, in the positions currency.
+pub fn position_cash_flows(positions: &ClosedPositionList) -> CashFlowList {
+    let mut flows = CashFlowList::new();
+    for pos in positions.iter() {
+        let mut proceeds = CashFlow::new(pos.exit_date, CashFlowType::Proceeds, pos.exit_notional() - pos.exit_fee, pos.symbol.clone());
+        proceeds.currency = pos.currency.clone();
+        flows.push(proceeds);
+
+        if pos.dividend != FP_0 {
+            let mut dividend = CashFlow::new(pos.exit_date, CashFlowType::Dividend, pos.dividend, pos.symbol.clone());
+            dividend.currency = pos.currency.clone();
+            flows.push(dividend);
+        }
+    }
+    flows
+}
+
+// Dividend flows of stocks paid after their buy dates, in the stocks currency.
+// Dividends of DRIP lots are reinvested and index stocks are not holdings, so both are skipped.
+pub fn dividend_cash_flows_from_ds(stocks: &StockList, ds: &DataStore) -> Result<CashFlowList, Error> {
+    let mut flows = CashFlowList::new();
+    for stock in stocks.iter().filter(|stock| !stock.drip && stock.stype != StockType::Index) {
+        if !ds.symbol_exists(dividends::tag(), &stock.symbol) {
+            continue;
+        }
+
+        let div = Dividends::ds_select_if(ds, &stock.symbol, |entry| entry.date > stock.date)?;
+        let splt = stocks_update::select_splits(stock, ds)?;
+        for entry in div.entries().iter() {
+            let amount = Price::from_float(stocks_update::shares_on(stock, &entry.date, &splt)? * entry.price);
+            let mut flow = CashFlow::new(entry.date, CashFlowType::Dividend, amount, stock.symbol.clone());
+            flow.currency = stock.currency.clone();
+            flows.push(flow);
+        }
+    }
+    Ok(flows)
+}
+
+// Sort flows by date, keeping the given order for flows on the same date.
+#[inline(always)]
+pub fn sort_cash_flows(flows: &mut CashFlowList) {
+    flows.sort_by_key(|flow| flow.date);
+}
+
+// Net cash flow on or before the given date.
+pub fn cash_balance(flows: &CashFlowList, date: &SPDate) -> Price {
+    flows
+        .iter()
+        .filter(|flow| flow.date <= *date)
+        .fold(FP_0, |balance, flow| balance + flow.signed_amount())
+}
+
+// Running cash balance at the end of each date with flows; flows must be sorted by date.
+pub fn cash_balances(flows: &CashFlowList) -> Vec<(SPDate, Price)> {
+    let mut balances: Vec<(SPDate, Price)> = Vec::new();
+    let mut balance = FP_0;
+    for flow in flows.iter() {
+        balance += flow.signed_amount();
+        match balances.last_mut() {
+            Some(last) if last.0 == flow.date => last.1 = balance,
+            _ => balances.push((flow.date, balance))
+        }
+    }
+    balances
+}
+
+// --------------------------------------------------------------------------------
+// Unit Tests
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::datetime::make_date;
+    use crate::portfolio::closed_position::ClosedPosition;
+    use crate::portfolio::transaction::{Quantity, Transaction};
+
+    #[test]
+    fn test_cash_flow_type() {
+        let types = [
+            CashFlowType::Deposit,
+            CashFlowType::Withdrawal,
+            CashFlowType::Interest,
+            CashFlowType::Fee,
+            CashFlowType::Dividend,
+            CashFlowType::Purchase,
+            CashFlowType::Proceeds
+        ];
+        for cft in types {
+            assert!(str2cashflowtype(cashflowtype2str(cft)).unwrap() == cft);
+        }
+        assert!(str2cashflowtype("Deposit").unwrap() == CashFlowType::Deposit);
+        assert_eq!(format!("{}", CashFlowType::Withdrawal), "withdrawal");
+        assert_eq!(format!("{}", str2cashflowtype("loan").unwrap_err()), "Unknown cash flow type 'loan'");
+    }
+
+    #[test]
+    fn test_cash_flow_signed_amount() {
+        let flow = |ftype| CashFlow::new(make_date(2021, 3, 1), ftype, Price::from_string("10.00"), String::new());
+
+        assert_eq!(flow(CashFlowType::Deposit).signed_amount(), Price::from_string("10.00"));
+        assert_eq!(flow(CashFlowType::Interest).signed_amount(), Price::from_string("10.00"));
+        assert_eq!(flow(CashFlowType::Dividend).signed_amount(), Price::from_string("10.00"));
+        assert_eq!(flow(CashFlowType::Proceeds).signed_amount(), Price::from_string("10.00"));
+        assert_eq!(flow(CashFlowType::Withdrawal).signed_amount(), Price::from_string("-10.00"));
+        assert_eq!(flow(CashFlowType::Fee).signed_amount(), Price::from_string("-10.00"));
+        assert_eq!(flow(CashFlowType::Purchase).signed_amount(), Price::from_string("-10.00"));
+
+        assert!(flow(CashFlowType::Deposit).is_external());
+        assert!(flow(CashFlowType::Withdrawal).is_external());
+        assert!(!flow(CashFlowType::Dividend).is_external());
+
+        assert_eq!(format!("{}", flow(CashFlowType::Fee)), "CashFlow(2021-03-01 fee -10.0000)");
+    }
+
+    #[test]
+    fn test_transaction_cash_flows() {
+        let tx = |ttype, qty: &str, price: &str, fee: &str| Transaction::new(
+            make_date(2021, 3, 1), ttype, String::from("AAPL"), StockType::Cash,
+            Quantity::from_string(qty), Price::from_string(price), Price::from_string(fee), String::new());
+        let txs = vec![
+            tx(TransactionType::Buy, "10", "100.00", "1.00"),
+            tx(TransactionType::Sell, "4", "110.00", "0.50")
+        ];
+
+        let flows = transaction_cash_flows(&txs);
+        assert_eq!(flows.len(), 2);
+        assert!(flows[0].ftype == CashFlowType::Purchase);
+        assert_eq!(flows[0].amount, Price::from_string("1001.00"));
+        assert_eq!(flows[0].symbol, "AAPL");
+        assert!(flows[1].ftype == CashFlowType::Proceeds);
+        assert_eq!(flows[1].amount, Price::from_string("439.50"));
+    }
+
+    #[test]
+    fn test_position_cash_flows() {
+        let mut positions = vec![
+            ClosedPosition::new(String::from("AAPL"), StockType::Cash, make_date(2021, 1, 4), make_date(2021, 3, 1),
+                                Quantity::from_string("10"), Price::from_string("100.00"), Price::from_string("110.00"),
+                                Price::from_string("1.00"), Price::from_string("0.50"), Price::from_string("2.05")),
+            ClosedPosition::new(String::from("SAP"), StockType::Cash, make_date(2021, 1, 4), make_date(2021, 4, 1),
+                                Quantity::from_string("5"), Price::from_string("100.00"), Price::from_string("90.00"),
+                                FP_0, Price::from_string("1.00"), FP_0)
+        ];
+        positions[1].currency = String::from("EUR");
+
+        let flows = position_cash_flows(&positions);
+        assert_eq!(flows.len(), 3);
+        assert!(flows[0].ftype == CashFlowType::Proceeds);
+        assert_eq!(flows[0].date, make_date(2021, 3, 1));
+        assert_eq!(flows[0].amount, Price::from_string("1099.50"));
+        assert!(flows[1].ftype == CashFlowType::Dividend);
+        assert_eq!(flows[1].amount, Price::from_string("2.05"));
+        assert_eq!(flows[1].symbol, "AAPL");
+        assert!(flows[2].ftype == CashFlowType::Proceeds);
+        assert_eq!(flows[2].amount, Price::from_string("449.00"));
+        assert_eq!(flows[2].currency, "EUR");
+    }
+
+    #[test]
+    fn test_cash_balance() {
+        let mut flows = vec![
+            CashFlow::new(make_date(2021, 3, 1), CashFlowType::Purchase, Price::from_string("1001.00"), String::from("AAPL")),
+            CashFlow::new(make_date(2021, 1, 4), CashFlowType::Deposit, Price::from_string("5000.00"), String::new()),
+            CashFlow::new(make_date(2021, 3, 1), CashFlowType::Interest, Price::from_string("1.25"), String::new()),
+            CashFlow::new(make_date(2021, 6, 1), CashFlowType::Withdrawal, Price::from_string("500.00"), String::new()),
+        ];
+        sort_cash_flows(&mut flows);
+        assert_eq!(flows[0].date, make_date(2021, 1, 4));
+
+        assert_eq!(cash_balance(&flows, &make_date(2020, 12, 31)), FP_0);
+        assert_eq!(cash_balance(&flows, &make_date(2021, 1, 4)), Price::from_string("5000.00"));
+        assert_eq!(cash_balance(&flows, &make_date(2021, 3, 1)), Price::from_string("4000.25"));
+        assert_eq!(cash_balance(&flows, &make_date(2021, 12, 31)), Price::from_string("3500.25"));
+
+        let balances = cash_balances(&flows);
+        assert_eq!(balances, vec![
+            (make_date(2021, 1, 4), Price::from_string("5000.00")),
+            (make_date(2021, 3, 1), Price::from_string("4000.25")),
+            (make_date(2021, 6, 1), Price::from_string("3500.25"))
+        ]);
+    }
+}
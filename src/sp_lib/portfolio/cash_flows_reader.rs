@@ -0,0 +1,112 @@
+use std::io::prelude::*;
+use std::fs::File;
+use std::io::BufReader;
+
+use crate::util::error::Error;
+use crate::util::datetime;
+use crate::util::fixed_price::FP_0;
+use crate::portfolio::cash_flow::{Price, CashFlow, CashFlowList, str2cashflowtype};
+
+pub struct CashFlowsReader {
+    cash_flows_file: String
+}
+
+impl CashFlowsReader {
+    pub fn new(cash_flows_file: String) -> Self {
+        CashFlowsReader {
+            cash_flows_file
+        }
+    }
+
+    pub fn read(&self) -> Result<CashFlowList, Error> {
+        match File::open(&self.cash_flows_file) {
+            Ok(file) => {
+                let mut reader = BufReader::new(file);
+                let mut content = String::new();
+                match reader.read_to_string(&mut content) {
+                    Ok(_) => Self::parse_content(&content),
+                    Err(e) => Err(format!("CashFlowsReader::read - {}", e).into())
+                }
+            },
+            Err(e) => Err(format!("CashFlowsReader::read - {}", e).into())
+        }
+    }
+
+    // Columns: date,type,amount,symbol
+    // The symbol column can be left empty.
+    pub fn parse_content(content: &str) -> Result<CashFlowList, Error> {
+        let mut flows = CashFlowList::new();
+
+        let mut skip_header: bool = true;
+        for flow_line in content.lines() {
+            // Assume first line is a header and skip it.
+            if skip_header {
+                skip_header = false;
+                continue;
+            }
+
+            if flow_line.is_empty() {
+                continue;
+            }
+
+            let flow_tokens: Vec<&str> = flow_line.split(',').map(|t| t.trim()).collect();
+            if flow_tokens.len() != 4 {
+                return Err(format!("CashFlowsReader::parse_content - Invalid cash flow line '{}'", flow_line).into())
+            }
+
+            let date = datetime::parse_date(flow_tokens[0])?;
+            let ftype = str2cashflowtype(flow_tokens[1])?;
+
+            let amount = match Price::parse(flow_tokens[2]) {
+                Ok(amt) if amt >= FP_0 => amt,
+                Ok(_) => return Err(format!("CashFlowsReader::parse_content - Invalid amount '{}'", flow_tokens[2]).into()),
+                Err(e) => return Err(format!("CashFlowsReader::parse_content - Invalid amount '{}'", e).into())
+            };
+
+            flows.push(CashFlow::new(date, ftype, amount, String::from(flow_tokens[3])));
+        }
+
+        Ok(flows)
+    }
+}
+
+// --------------------------------------------------------------------------------
+// Unit Tests
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::portfolio::cash_flow::CashFlowType;
+
+    #[test]
+    fn test_parse_content() {
+        let content = "date,type,amount,symbol\n\
+                       2021-01-04,deposit,5000.00,\n\
+                       2021-02-18,dividend,56.00,MSFT\n";
+        let flows = CashFlowsReader::parse_content(content).unwrap();
+        assert_eq!(flows.len(), 2);
+
+        assert_eq!(flows[0].date, datetime::make_date(2021, 1, 4));
+        assert!(flows[0].ftype == CashFlowType::Deposit);
+        assert_eq!(flows[0].amount, Price::from_string("5000.00"));
+        assert_eq!(flows[0].symbol, "");
+
+        assert_eq!(flows[1].date, datetime::make_date(2021, 2, 18));
+        assert!(flows[1].ftype == CashFlowType::Dividend);
+        assert_eq!(flows[1].amount, Price::from_string("56.00"));
+        assert_eq!(flows[1].symbol, "MSFT");
+    }
+
+    #[test]
+    fn test_parse_content_errors() {
+        fn check(line: &str, err: &str) {
+            let content = format!("date,type,amount,symbol\n{}\n", line);
+            assert_eq!(format!("{}", CashFlowsReader::parse_content(&content).err().unwrap()), err);
+        }
+
+        check("2021-01-04,deposit,5000.00",
+              "CashFlowsReader::parse_content - Invalid cash flow line '2021-01-04,deposit,5000.00'");
+        check("2021-01-04,loan,5000.00,", "Unknown cash flow type 'loan'");
+        check("2021-01-04,deposit,-5.00,", "CashFlowsReader::parse_content - Invalid amount '-5.00'");
+    }
+}
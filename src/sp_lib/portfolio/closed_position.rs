@@ -6,18 +6,22 @@ use crate::portfolio::stock_type::StockType;
 use crate::portfolio::symbol_trait::GetSymbol;
 
 pub type Price = FixedPrice;
+pub type Quantity = FixedPrice;
 
+#[derive(Clone)]
 pub struct ClosedPosition {
     pub symbol: String,
     pub stype: StockType,
     pub base_date: SPDate,
     pub exit_date: SPDate,
-    pub quantity: u32,
+    pub quantity: Quantity,
     pub base_price: Price,
     pub exit_price: Price,
     pub base_fee: Price,
     pub exit_fee: Price,
-    pub dividend: Price
+    pub dividend: Price,
+    pub currency: String,
+    pub account: String
 }
 
 pub type ClosedPositionList = Vec<ClosedPosition>;
@@ -27,7 +31,7 @@ impl ClosedPosition {
                stype: StockType,
                base_date: SPDate,
                exit_date: SPDate,
-               quantity: u32,
+               quantity: Quantity,
                base_price: Price,
                exit_price: Price,
                base_fee: Price,
@@ -43,10 +47,23 @@ impl ClosedPosition {
             exit_price,
             base_fee,
             exit_fee,
-            dividend
+            dividend,
+            currency: String::new(),
+            account: String::new()
         }
     }
 
+    // Same buy and sale of the same symbol in the same account.
+    pub fn same_lot(&self, other: &ClosedPosition) -> bool {
+        self.symbol == other.symbol &&
+            self.account == other.account &&
+            self.base_date == other.base_date &&
+            self.exit_date == other.exit_date &&
+            self.quantity == other.quantity &&
+            self.base_price == other.base_price &&
+            self.exit_price == other.exit_price
+    }
+
     #[inline(always)]
     pub fn net_price(&self) -> Price {
         self.exit_price - self.base_price
@@ -54,17 +71,17 @@ impl ClosedPosition {
 
     #[inline(always)]
     pub fn net_notional(&self) -> Price {
-        Price::from_unsigned(self.quantity) * self.net_price()
+        self.quantity * self.net_price()
     }
 
     #[inline(always)]
     pub fn base_notional(&self) -> Price {
-        Price::from_unsigned(self.quantity) * self.base_price
+        self.quantity * self.base_price
     }
 
     #[inline(always)]
     pub fn exit_notional(&self) -> Price {
-        Price::from_unsigned(self.quantity) * self.exit_price
+        self.quantity * self.exit_price
     }
 }
 
@@ -96,12 +113,14 @@ mod tests {
         assert!(cp.stype == StockType::Cash);
         assert_eq!(cp.base_date, datetime::today_plus_days(-10));
         assert_eq!(cp.exit_date, datetime::today());
-        assert_eq!(cp.quantity, 100);
+        assert_eq!(cp.quantity, Quantity::from_unsigned(100));
         assert_eq!(cp.base_price, Price::from_string("115.00"));
         assert_eq!(cp.exit_price, Price::from_string("120.00"));
         assert_eq!(cp.base_fee, FP_0);
         assert_eq!(cp.exit_fee, Price::from_string("0.05"));
         assert_eq!(cp.dividend, Price::from_string("5.00"));
+        assert_eq!(cp.currency, "");
+        assert_eq!(cp.account, "");
     }
 
     #[test]
@@ -117,7 +136,7 @@ mod tests {
             StockType::Cash,
             datetime::today_plus_days(-10),
             datetime::today(),
-            100,
+            Quantity::from_unsigned(100),
             Price::from_string("115.00"),
             Price::from_string("120.00"),
             FP_0,
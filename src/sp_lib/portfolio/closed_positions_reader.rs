@@ -4,8 +4,10 @@ use std::io::BufReader;
 
 use crate::util::error::Error;
 use crate::util::datetime;
+use crate::util::fixed_price::FP_0;
+use crate::portfolio::currency;
 use crate::portfolio::stock_type;
-use crate::portfolio::closed_position::{Price, ClosedPosition, ClosedPositionList};
+use crate::portfolio::closed_position::{Price, Quantity, ClosedPosition, ClosedPositionList};
 
 pub struct ClosedPositionsReader {
     closed_positions_file: String
@@ -48,7 +50,7 @@ impl ClosedPositionsReader {
             }
 
             let position_tokens: Vec<&str> = position_line.split(',').collect();
-            if position_tokens.len() != 10 {
+            if position_tokens.len() < 10 || position_tokens.len() > 12 {
                 return Err(format!("ClosedPositionsReader::parse_content - Invalid position line '{}'", position_line).into())
             }
 
@@ -57,8 +59,9 @@ impl ClosedPositionsReader {
             let base_date = datetime::parse_date(position_tokens[2])?;
             let exit_date = datetime::parse_date(position_tokens[3])?;
 
-            let quantity = match position_tokens[4].parse::<u32>() {
-                Ok(qty) => qty,
+            let quantity = match Quantity::parse(position_tokens[4]) {
+                Ok(qty) if qty >= FP_0 => qty,
+                Ok(_) => return Err(format!("ClosedPositionsReader::parse_content - Invalid quantity '{}'", position_tokens[4]).into()),
                 Err(e) => return Err(format!("ClosedPositionsReader::parse_content - Invalid quantity '{}'", e).into())
             };
 
@@ -68,7 +71,7 @@ impl ClosedPositionsReader {
             let exit_fee = Self::parse_price(position_tokens[8], "exit_fee")?;
             let dividend = Self::parse_price(position_tokens[9], "dividend")?;
 
-            positions.push(ClosedPosition::new(
+            let mut position = ClosedPosition::new(
                 symbol,
                 stype,
                 base_date,
@@ -78,7 +81,15 @@ impl ClosedPositionsReader {
                 exit_price,
                 base_fee,
                 exit_fee,
-                dividend));
+                dividend);
+            if position_tokens.len() >= 11 && !position_tokens[10].is_empty() {
+                position.currency = currency::parse_currency(position_tokens[10])?;
+            }
+            if position_tokens.len() == 12 {
+                position.account = String::from(position_tokens[11].trim());
+            }
+
+            positions.push(position);
         }
 
         Ok(positions)
@@ -99,7 +110,6 @@ impl ClosedPositionsReader {
 mod tests {
     use super::*;
     use crate::portfolio::stock_type::StockType;
-    use crate::util::fixed_price::FP_0;
 
     #[test]
     fn test_parse_content() {
@@ -118,11 +128,23 @@ mod tests {
         };
     }
 
+    #[test]
+    fn test_parse_content_account() {
+        let content = "symbol,type,base_date,exit_date,quantity,base_price,exit_price,base_fee,exit_fee,dividend,currency,account\n\
+                       MYSYM,cash,2016-04-15,2023-03-28,100,44.10,131.56,0.00,0.12,1009.00,,ira\n\
+                       MYOTH,cash,2021-10-18,2023-09-06,44.5,85.60,165.45,0.00,0.07,1205.60,eur\n";
+        let positions = ClosedPositionsReader::parse_content(content).unwrap();
+        assert_eq!(positions[0].account, "ira");
+        assert_eq!(positions[0].currency, "");
+        assert_eq!(positions[1].account, "");
+        assert_eq!(positions[1].currency, "EUR");
+    }
+
     fn make_content() -> &'static str {
         "symbol,type,base_date,exit_date,quantity,base_price,exit_price,base_fee,exit_fee,dividend\n\
          MYSYM,cash,2016-04-15,2023-03-28,100,44.10,131.56,0.00,0.12,1009.00\n\
          MYSYM,cash,2016-04-15,2023-03-28,100,44.10,131.55,0.00,0.12,1009.00\n\
-         MYOTH,cash,2021-10-18,2023-09-06,44,85.60,165.45,0.00,0.07,1205.60\n"
+         MYOTH,cash,2021-10-18,2023-09-06,44.5,85.60,165.45,0.00,0.07,1205.60\n"
     }
 
     fn position_0() -> ClosedPosition {
@@ -130,7 +152,7 @@ mod tests {
                             StockType::Cash,
                             datetime::make_date(2016, 4, 15),
                             datetime::make_date(2023, 3, 28),
-                            100,
+                            Quantity::from_unsigned(100),
                             Price::from_string("44.10"),
                             Price::from_string("131.56"),
                             FP_0,
@@ -143,7 +165,7 @@ mod tests {
                             StockType::Cash,
                             datetime::make_date(2016, 4, 15),
                             datetime::make_date(2023, 3, 28),
-                            100,
+                            Quantity::from_unsigned(100),
                             Price::from_string("44.10"),
                             Price::from_string("131.55"),
                             FP_0,
@@ -156,7 +178,7 @@ mod tests {
                             StockType::Cash,
                             datetime::make_date(2021, 10, 18),
                             datetime::make_date(2023, 9, 6),
-                            44,
+                            Quantity::from_string("44.5"),
                             Price::from_string("85.60"),
                             Price::from_string("165.45"),
                             FP_0,
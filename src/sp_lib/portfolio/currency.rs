@@ -0,0 +1,174 @@
+use std::collections::HashMap;
+
+use crate::datastore::datastore::DataStore;
+use crate::datastore::dividends;
+use crate::datastore::dividends::Dividends;
+use crate::datastore::fx_rates;
+use crate::datastore::fx_rates::FxRates;
+use crate::datastore::splits::Splits;
+use crate::portfolio::cash_flow::{self, CashFlow, CashFlowList};
+use crate::portfolio::closed_position::{self, ClosedPosition, ClosedPositionList};
+use crate::portfolio::performance::MarketHistory;
+use crate::portfolio::stock::{Price, Stock, StockList};
+use crate::portfolio::stocks_update;
+use crate::util::datetime;
+use crate::util::datetime::SPDate;
+use crate::util::error::Error;
+
+pub const DEFAULT_BASE_CURRENCY: &str = "USD";
+
+// Three letter currency code, in upper case.
+pub fn parse_currency(value: &str) -> Result<String, Error> {
+    let value = value.trim();
+    if value.len() == 3 && value.chars().all(|c| c.is_ascii_alphabetic()) {
+        Ok(value.to_uppercase())
+    } else {
+        Err(format!("Invalid currency '{}'", value).into())
+    }
+}
+
+// Whether prices in currency need converting to base. An empty currency is the base currency.
+#[inline(always)]
+pub fn is_foreign(currency: &str, base: &str) -> bool {
+    !currency.is_empty() && currency != base
+}
+
+// Earliest date of the stocks and closed positions in each foreign currency.
+pub fn currency_base_dates(stocks: &StockList, positions: &ClosedPositionList, base: &str) -> HashMap<String, SPDate> {
+    let mut dates: HashMap<String, SPDate> = HashMap::new();
+    let lots = stocks.iter().map(|s| (&s.currency, s.date))
+        .chain(positions.iter().map(|p| (&p.currency, p.base_date)));
+    for (currency, date) in lots.filter(|(currency, _)| is_foreign(currency, base)) {
+        let entry = dates.entry(currency.clone()).or_insert(date);
+        *entry = (*entry).min(date);
+    }
+    dates
+}
+
+// Listing currency of each symbol of the stocks and closed positions in a foreign currency.
+pub fn symbol_currencies(stocks: &StockList, positions: &ClosedPositionList, base: &str) -> HashMap<String, String> {
+    stocks.iter().map(|s| (&s.symbol, &s.currency))
+        .chain(positions.iter().map(|p| (&p.symbol, &p.currency)))
+        .filter(|(_, currency)| is_foreign(currency, base))
+        .map(|(symbol, currency)| (symbol.clone(), currency.clone()))
+        .collect()
+}
+
+// Convert stock prices to base on the buy, latest and dividend dates.
+// The cumulative dividend is recomputed from the dividends paid after the buy date on the shares held
+// on their dates, and stays 0 for DRIP lots.
+pub fn convert_stock(stock: &mut Stock, base: &str, fx: &FxRates, div: &Dividends, splt: &Splits) -> Result<(), Error> {
+    stock.base_price *= fx_rate(fx, &stock.date)?;
+    stock.latest_price *= fx_rate(fx, &stock.latest_date)?;
+    if stock.latest_div_date != datetime::earliest_date() {
+        stock.latest_div_price *= fx_rate(fx, &stock.latest_div_date)?;
+    }
+
+    let mut cum_dividend: Price = 0.0;
+    for entry in div.entries().iter().filter(|entry| entry.date > stock.date) {
+        cum_dividend += stocks_update::shares_on(stock, &entry.date, splt)? * entry.price * fx_rate(fx, &entry.date)?;
+    }
+    stock.cum_dividend = if stock.drip { 0.0 } else { cum_dividend };
+
+    stock.currency = String::from(base);
+    Ok(())
+}
+
+// Convert closed position prices and fees to base on the base and exit dates.
+// The dividend is converted on the exit date.
+pub fn convert_position(pos: &mut ClosedPosition, base: &str, fx: &FxRates) -> Result<(), Error> {
+    let convert = |price: closed_position::Price, rate: Price| closed_position::Price::from_float(price.to_float() * rate);
+
+    let base_rate = fx_rate(fx, &pos.base_date)?;
+    pos.base_price = convert(pos.base_price, base_rate);
+    pos.base_fee = convert(pos.base_fee, base_rate);
+
+    let exit_rate = fx_rate(fx, &pos.exit_date)?;
+    pos.exit_price = convert(pos.exit_price, exit_rate);
+    pos.exit_fee = convert(pos.exit_fee, exit_rate);
+    pos.dividend = convert(pos.dividend, exit_rate);
+
+    pos.currency = String::from(base);
+    Ok(())
+}
+
+// Convert a cash flow amount to base on the flow date.
+pub fn convert_cash_flow(flow: &mut CashFlow, base: &str, fx: &FxRates) -> Result<(), Error> {
+    flow.amount = cash_flow::Price::from_float(flow.amount.to_float() * fx_rate(fx, &flow.date)?);
+    flow.currency = String::from(base);
+    Ok(())
+}
+
+pub fn convert_stocks_from_ds(stocks: &mut StockList, ds: &DataStore, base: &str) -> Result<usize, Error> {
+    let mut rates: HashMap<String, FxRates> = HashMap::new();
+    let mut count: usize = 0;
+    for stock in stocks.iter_mut().filter(|stock| is_foreign(&stock.currency, base)) {
+        let div = if ds.symbol_exists(dividends::tag(), &stock.symbol) {
+            Dividends::ds_select_if(ds, &stock.symbol, |entry| entry.date > stock.date)?
+        } else {
+            Dividends::new(&stock.symbol)
+        };
+
+        let splt = stocks_update::select_splits(stock, ds)?;
+        let fx = select_rates(&mut rates, ds, &stock.currency, base)?;
+        convert_stock(stock, base, fx, &div, &splt)?;
+        count += 1;
+    }
+    Ok(count)
+}
+
+pub fn convert_positions_from_ds(positions: &mut ClosedPositionList, ds: &DataStore, base: &str) -> Result<usize, Error> {
+    let mut rates: HashMap<String, FxRates> = HashMap::new();
+    let mut count: usize = 0;
+    for pos in positions.iter_mut().filter(|pos| is_foreign(&pos.currency, base)) {
+        let fx = select_rates(&mut rates, ds, &pos.currency, base)?;
+        convert_position(pos, base, fx)?;
+        count += 1;
+    }
+    Ok(count)
+}
+
+pub fn convert_cash_flows_from_ds(flows: &mut CashFlowList, ds: &DataStore, base: &str) -> Result<usize, Error> {
+    let mut rates: HashMap<String, FxRates> = HashMap::new();
+    let mut count: usize = 0;
+    for flow in flows.iter_mut().filter(|flow| is_foreign(&flow.currency, base)) {
+        let fx = select_rates(&mut rates, ds, &flow.currency, base)?;
+        convert_cash_flow(flow, base, fx)?;
+        count += 1;
+    }
+    Ok(count)
+}
+
+// Convert closes and dividends of the symbols listed in a foreign currency to base on their dates.
+// Closes and dividends before the first fx rate are dropped.
+pub fn convert_market_from_ds(mkt: &mut MarketHistory, currencies: &HashMap<String, String>, ds: &DataStore, base: &str) -> Result<usize, Error> {
+    let mut rates: HashMap<String, FxRates> = HashMap::new();
+    let mut count: usize = 0;
+    for (symbol, currency) in currencies.iter().filter(|(_, currency)| is_foreign(currency, base)) {
+        let fx = select_rates(&mut rates, ds, currency, base)?;
+        mkt.convert_symbol(symbol, |date| fx.rate_on(date));
+        count += 1;
+    }
+    Ok(count)
+}
+
+// --------------------------------------------------------------------------------
+// Private
+
+fn fx_rate(fx: &FxRates, date: &SPDate) -> Result<Price, Error> {
+    match fx.rate_on(date) {
+        Some(rate) => Ok(rate),
+        None => Err(format!("No {} fx rate on or before {}", fx.symbol(), date.format("%Y-%m-%d")).into())
+    }
+}
+
+fn select_rates<'a>(rates: &'a mut HashMap<String, FxRates>, ds: &DataStore, currency: &str, base: &str) -> Result<&'a FxRates, Error> {
+    let pair = fx_rates::pair_symbol(currency, base);
+    if !rates.contains_key(&pair) {
+        if !ds.symbol_exists(fx_rates::tag(), &pair) {
+            return Err(format!("Missing {} fx rates in datastore {}", pair, ds).into());
+        }
+        rates.insert(pair.clone(), FxRates::ds_select_all(ds, &pair)?);
+    }
+    Ok(&rates[&pair])
+}
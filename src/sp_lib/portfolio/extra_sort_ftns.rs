@@ -94,7 +94,7 @@ pub fn extra_sort_by_valchg(ds: &DataStore, stocks: &mut StockList, desc: bool)
                     let entries = hist.entries();
                     if entries.len() == 2 {
                         let delta = entries[1].adj_close - entries[0].adj_close;
-                        return stock.quantity as f64 * delta;
+                        return stock.quantity * delta;
                     }
                 }
             }
@@ -0,0 +1,95 @@
+use crate::util::datetime::SPDate;
+use crate::portfolio::closed_position::ClosedPositionList;
+use crate::portfolio::stock::{Price, StockList};
+use crate::portfolio::stock_type::StockType;
+use crate::portfolio::tax_lots::{self, HoldingTerm};
+use crate::portfolio::wash_sales;
+
+// --------------------------------------------------------------------------------
+// Harvest Candidate
+
+// Open lot at a loss that could be sold to realize the loss on its latest date.
+pub struct HarvestCandidate {
+    pub index: usize,              // Index of the lot in the stocks
+    pub loss: Price,               // Realizable loss, positive
+    pub pct_loss: f64,             // Loss percent of base notional, positive
+    pub term: HoldingTerm,
+    pub wash_buy: Option<SPDate>   // Latest buy that would make the sale a wash sale
+}
+
+pub type HarvestCandidateList = Vec<HarvestCandidate>;
+
+// Open lots with a loss of at least min_loss and min_pct_loss percent, excluding index stocks.
+// The loss is the negative of the lot net notional. Wash sale buys are looked up in all stocks
+// and positions, which include lots filtered out of stocks.
+pub fn harvest_candidates(stocks: &StockList,
+                          all_stocks: &StockList,
+                          all_positions: &ClosedPositionList,
+                          min_loss: Price,
+                          min_pct_loss: f64) -> HarvestCandidateList {
+    stocks.iter()
+        .enumerate()
+        .filter(|(_, stock)| stock.stype != StockType::Index && stock.net_notional() < 0.0)
+        .map(|(index, stock)| HarvestCandidate {
+            index,
+            loss: -stock.net_notional(),
+            pct_loss: -stock.pct_change(),
+            term: tax_lots::holding_term(&stock.date, &stock.latest_date),
+            wash_buy: wash_sales::recent_buy(all_stocks, all_positions, &stock.symbol, &stock.date, &stock.latest_date)
+        })
+        .filter(|candidate| candidate.loss >= min_loss && candidate.pct_loss >= min_pct_loss)
+        .collect()
+}
+
+// --------------------------------------------------------------------------------
+// Unit Tests
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::portfolio::stock::Stock;
+    use crate::util::datetime;
+    use crate::util::price_type::price_eql;
+
+    #[test]
+    fn test_harvest_candidates() {
+        let stocks: StockList = vec![make_stock("AAPL", StockType::Cash, datetime::make_date(2020, 1, 6), 10.0, 120.0, 100.0),
+                                     make_stock("AAPL", StockType::Cash, datetime::make_date(2021, 12, 10), 2.0, 98.0, 100.0),
+                                     make_stock("DELL", StockType::Cash, datetime::make_date(2021, 6, 1), 100.0, 80.0, 78.0),
+                                     make_stock("MSFT", StockType::Cash, datetime::make_date(2021, 6, 1), 10.0, 200.0, 220.0),
+                                     make_stock("SPY", StockType::Index, datetime::make_date(2021, 6, 1), 1.0, 480.0, 470.0)];
+        let positions = ClosedPositionList::new();
+
+        let candidates = harvest_candidates(&stocks, &stocks, &positions, 0.0, 0.0);
+        assert_eq!(candidates.iter().map(|c| c.index).collect::<Vec<usize>>(), vec![0, 2]);
+
+        let aapl = &candidates[0];
+        assert!(price_eql(aapl.loss, 200.0));
+        assert!(price_eql(aapl.pct_loss, 100.0 / 6.0));
+        assert_eq!(aapl.term, HoldingTerm::Long);
+        assert_eq!(aapl.wash_buy, Some(datetime::make_date(2021, 12, 10)));
+
+        let dell = &candidates[1];
+        assert!(price_eql(dell.loss, 200.0));
+        assert!(price_eql(dell.pct_loss, 2.5));
+        assert_eq!(dell.term, HoldingTerm::Short);
+        assert_eq!(dell.wash_buy, None);
+
+        let candidates = harvest_candidates(&stocks, &stocks, &positions, 0.0, 5.0);
+        assert_eq!(candidates.iter().map(|c| c.index).collect::<Vec<usize>>(), vec![0]);
+
+        let candidates = harvest_candidates(&stocks, &stocks, &positions, 250.0, 0.0);
+        assert!(candidates.is_empty());
+
+        // Buys filtered out of the stocks are still wash sale buys
+        let candidates = harvest_candidates(&stocks[..1].to_vec(), &stocks, &positions, 0.0, 0.0);
+        assert_eq!(candidates.len(), 1);
+        assert_eq!(candidates[0].wash_buy, Some(datetime::make_date(2021, 12, 10)));
+    }
+
+    fn make_stock(symbol: &str, stype: StockType, date: SPDate, quantity: f64, base: Price, latest: Price) -> Stock {
+        let mut stock = Stock::new(String::from(symbol), stype, date, quantity, base);
+        stock.set_latest_price(latest, datetime::make_date(2021, 12, 20));
+        stock
+    }
+}
@@ -0,0 +1,344 @@
+use std::collections::HashMap;
+
+use crate::util::error::Error;
+use crate::util::fixed_price::{FixedPrice, FP_0};
+use crate::portfolio::closed_position::{ClosedPosition, ClosedPositionList};
+use crate::portfolio::lot_method::LotMethod;
+use crate::portfolio::stock::{Stock, StockList};
+use crate::portfolio::stock_type::StockType;
+use crate::portfolio::transaction::{Quantity, Transaction, TransactionList, TransactionType};
+use crate::util::datetime::SPDate;
+use crate::util::price_type::quantity_format;
+
+type Price = FixedPrice;
+
+struct OpenLot {
+    seq: usize,
+    date: SPDate,
+    stype: StockType,
+    quantity: Quantity,
+    price: Price,
+    fee: Price,
+    lot: String
+}
+
+// Derive open stocks and closed positions from a transaction ledger.
+// Transactions are applied in date order, keeping ledger order for transactions on the same date.
+// Sells are matched against open lots of the same symbol using the given lot method, which applies to all
+// sells of the ledger; there is no per-sell override, and the lot column of a sell is only used by the specific method.
+// The buy fee not taken by sells is added to the base price of the open stock lot.
+pub fn apply_transactions(transactions: &TransactionList, method: LotMethod) -> Result<(StockList, ClosedPositionList), Error> {
+    let mut ordered: Vec<&Transaction> = transactions.iter().collect();
+    ordered.sort_by_key(|tx| tx.date);
+
+    let mut open_lots: HashMap<&str, Vec<OpenLot>> = HashMap::new();
+    let mut closed_positions = ClosedPositionList::new();
+
+    for (seq, tx) in ordered.iter().enumerate() {
+        match tx.ttype {
+            TransactionType::Buy => {
+                open_lots.entry(&tx.symbol).or_default().push(OpenLot {
+                    seq,
+                    date: tx.date,
+                    stype: tx.stype,
+                    quantity: tx.quantity,
+                    price: tx.price,
+                    fee: tx.fee,
+                    lot: tx.lot.clone()
+                });
+            },
+            TransactionType::Sell => {
+                let lots = open_lots.entry(&tx.symbol).or_default();
+                apply_sell(tx, method, lots, &mut closed_positions)?;
+            }
+        }
+    }
+
+    let mut remaining: Vec<(&str, OpenLot)> = open_lots
+        .into_iter()
+        .flat_map(|(symbol, lots)| lots.into_iter().map(move |lot| (symbol, lot)))
+        .collect();
+    remaining.sort_by_key(|(_, lot)| lot.seq);
+
+    let stocks: StockList = remaining
+        .into_iter()
+        .map(|(symbol, lot)| {
+            let quantity = lot.quantity.to_float();
+            let base_price = lot.price.to_float() + lot.fee.to_float() / quantity;
+            Stock::new(String::from(symbol), lot.stype, lot.date, quantity, base_price)
+        })
+        .collect();
+
+    Ok((stocks, closed_positions))
+}
+
+// --------------------------------------------------------------------------------
+// Private Helpers
+
+fn apply_sell(tx: &Transaction,
+              method: LotMethod,
+              lots: &mut Vec<OpenLot>,
+              closed_positions: &mut ClosedPositionList) -> Result<(), Error> {
+    let held = lots.iter().fold(FP_0, |held, lot| held + lot.quantity);
+    if tx.quantity > held {
+        return Err(format!("Ledger: Sell quantity {} exceeds open quantity {} for {} on {}",
+                           fmt_quantity(tx.quantity), fmt_quantity(held), tx.symbol, tx.date.format("%Y-%m-%d")).into());
+    }
+
+    if method == LotMethod::Specific {
+        check_specific_lot(tx, lots)?;
+    }
+
+    if method == LotMethod::Average {
+        let cost = lots.iter().fold(FP_0, |cost, lot| cost + lot.quantity * lot.price);
+        let average = cost / held;
+        for lot in lots.iter_mut() {
+            lot.price = average;
+        }
+    }
+
+    let mut remaining = tx.quantity;
+    let mut remaining_fee = tx.fee;
+    while remaining > FP_0 {
+        let index = match method {
+            LotMethod::Fifo | LotMethod::Average => 0,
+            LotMethod::Lifo => lots.len() - 1,
+            LotMethod::Specific => find_specific_lot(tx, lots)?
+        };
+
+        let lot = &mut lots[index];
+        let quantity = remaining.min(lot.quantity);
+        let base_fee = prorate(lot.fee, quantity, lot.quantity);
+        let exit_fee = prorate(remaining_fee, quantity, remaining);
+
+        closed_positions.push(ClosedPosition::new(
+            tx.symbol.clone(),
+            lot.stype,
+            lot.date,
+            tx.date,
+            quantity,
+            lot.price,
+            tx.price,
+            base_fee,
+            exit_fee,
+            FP_0));
+
+        lot.quantity -= quantity;
+        lot.fee -= base_fee;
+        remaining -= quantity;
+        remaining_fee -= exit_fee;
+
+        if lot.quantity == FP_0 {
+            lots.remove(index);
+        }
+    }
+
+    Ok(())
+}
+
+fn find_specific_lot(tx: &Transaction, lots: &[OpenLot]) -> Result<usize, Error> {
+    if tx.lot.is_empty() {
+        return Err(format!("Ledger: Missing lot for {} sell on {}", tx.symbol, tx.date.format("%Y-%m-%d")).into());
+    }
+
+    match lots.iter().position(|lot| lot.lot == tx.lot) {
+        Some(index) => Ok(index),
+        None => Err(format!("Ledger: No open lot '{}' for {} sell on {}", tx.lot, tx.symbol, tx.date.format("%Y-%m-%d")).into())
+    }
+}
+
+fn check_specific_lot(tx: &Transaction, lots: &[OpenLot]) -> Result<(), Error> {
+    let lot_quantity = lots.iter().filter(|lot| lot.lot == tx.lot).fold(FP_0, |held, lot| held + lot.quantity);
+    if lot_quantity > FP_0 && tx.quantity > lot_quantity {
+        return Err(format!("Ledger: Sell quantity {} exceeds open lot '{}' quantity {} for {} on {}",
+                           fmt_quantity(tx.quantity), tx.lot, fmt_quantity(lot_quantity), tx.symbol, tx.date.format("%Y-%m-%d")).into());
+    }
+    Ok(())
+}
+
+// Share of amount for part out of total; the last part takes whatever is left to avoid rounding residue.
+#[inline(always)]
+fn prorate(amount: Price, part: Quantity, total: Quantity) -> Price {
+    if part == total {
+        amount
+    } else {
+        amount * part / total
+    }
+}
+
+#[inline(always)]
+fn fmt_quantity(quantity: Quantity) -> String {
+    quantity_format(quantity.to_float())
+}
+
+// --------------------------------------------------------------------------------
+// Unit Tests
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::datetime::make_date;
+
+    #[test]
+    fn test_apply_buys_only() {
+        let txs = vec![
+            buy(make_date(2021, 3, 1), "AAPL", "100", "120.00", "1.00", ""),
+            buy(make_date(2021, 2, 1), "DELL", "50", "80.00", "0.00", ""),
+        ];
+
+        let (stocks, closed) = apply_transactions(&txs, LotMethod::Fifo).unwrap();
+        assert_eq!(closed.len(), 0);
+        assert_eq!(stocks.len(), 2);
+        check_stock(&stocks[0], "DELL", make_date(2021, 2, 1), 50.0, 80.0);
+        check_stock(&stocks[1], "AAPL", make_date(2021, 3, 1), 100.0, 120.01);
+    }
+
+    #[test]
+    fn test_apply_fifo() {
+        let (stocks, closed) = apply_transactions(&make_transactions(""), LotMethod::Fifo).unwrap();
+
+        // The 1.00 of the buy fee not taken by the sell is in the base price
+        assert_eq!(stocks.len(), 1);
+        check_stock(&stocks[0], "AAPL", make_date(2021, 2, 1), 50.0, 110.02);
+
+        assert_eq!(closed.len(), 2);
+        check_closed(&closed[0], make_date(2021, 1, 4), "100", "100.00", "1.00", "1.00");
+        check_closed(&closed[1], make_date(2021, 2, 1), "150", "110.00", "3.00", "1.50");
+    }
+
+    #[test]
+    fn test_apply_lifo() {
+        let (stocks, closed) = apply_transactions(&make_transactions(""), LotMethod::Lifo).unwrap();
+
+        assert_eq!(stocks.len(), 1);
+        check_stock(&stocks[0], "AAPL", make_date(2021, 1, 4), 50.0, 100.01);
+
+        assert_eq!(closed.len(), 2);
+        check_closed(&closed[0], make_date(2021, 2, 1), "200", "110.00", "4.00", "2.00");
+        check_closed(&closed[1], make_date(2021, 1, 4), "50", "100.00", "0.50", "0.50");
+    }
+
+    #[test]
+    fn test_apply_specific() {
+        let mut txs = make_transactions("L2");
+        txs[1].quantity = Quantity::from_unsigned(150);
+
+        let (stocks, closed) = apply_transactions(&txs, LotMethod::Specific).unwrap();
+
+        assert_eq!(stocks.len(), 2);
+        check_stock(&stocks[0], "AAPL", make_date(2021, 1, 4), 100.0, 100.01);
+        check_stock(&stocks[1], "AAPL", make_date(2021, 2, 1), 50.0, 110.02);
+
+        assert_eq!(closed.len(), 1);
+        check_closed(&closed[0], make_date(2021, 2, 1), "150", "110.00", "3.00", "2.50");
+
+        let err = apply_transactions(&make_transactions(""), LotMethod::Specific).err().unwrap();
+        assert_eq!(format!("{}", err), "Ledger: Missing lot for AAPL sell on 2021-03-01");
+
+        let err = apply_transactions(&make_transactions("L9"), LotMethod::Specific).err().unwrap();
+        assert_eq!(format!("{}", err), "Ledger: No open lot 'L9' for AAPL sell on 2021-03-01");
+
+        let err = apply_transactions(&make_transactions("L1"), LotMethod::Specific).err().unwrap();
+        assert_eq!(format!("{}", err), "Ledger: Sell quantity 250 exceeds open lot 'L1' quantity 100 for AAPL on 2021-03-01");
+    }
+
+    #[test]
+    fn test_apply_average() {
+        let (stocks, closed) = apply_transactions(&make_transactions(""), LotMethod::Average).unwrap();
+
+        // Average cost (100 * 100.00 + 200 * 110.00) / 300 = 106.6666, plus the 1.00 fee left over 50 shares
+        assert_eq!(stocks.len(), 1);
+        check_stock(&stocks[0], "AAPL", make_date(2021, 2, 1), 50.0, 106.6866);
+
+        assert_eq!(closed.len(), 2);
+        check_closed(&closed[0], make_date(2021, 1, 4), "100", "106.6666", "1.00", "1.00");
+        check_closed(&closed[1], make_date(2021, 2, 1), "150", "106.6666", "3.00", "1.50");
+    }
+
+    #[test]
+    fn test_apply_oversell() {
+        let txs = vec![
+            buy(make_date(2021, 1, 4), "AAPL", "100", "100.00", "0.00", ""),
+            sell(make_date(2021, 3, 1), "AAPL", "101", "120.00", "0.00", ""),
+        ];
+
+        let err = apply_transactions(&txs, LotMethod::Fifo).err().unwrap();
+        assert_eq!(format!("{}", err), "Ledger: Sell quantity 101 exceeds open quantity 100 for AAPL on 2021-03-01");
+
+        let txs = vec![sell(make_date(2021, 3, 1), "DELL", "1", "120.00", "0.00", "")];
+        let err = apply_transactions(&txs, LotMethod::Fifo).err().unwrap();
+        assert_eq!(format!("{}", err), "Ledger: Sell quantity 1 exceeds open quantity 0 for DELL on 2021-03-01");
+    }
+
+    #[test]
+    fn test_apply_fractional() {
+        let txs = vec![
+            buy(make_date(2021, 1, 4), "AAPL", "10.5", "100.00", "1.00", ""),
+            buy(make_date(2021, 2, 1), "AAPL", "0.25", "110.00", "0.00", ""),
+            sell(make_date(2021, 3, 1), "AAPL", "5.25", "120.00", "1.00", ""),
+        ];
+
+        let (stocks, closed) = apply_transactions(&txs, LotMethod::Fifo).unwrap();
+
+        assert_eq!(stocks.len(), 2);
+        check_stock(&stocks[0], "AAPL", make_date(2021, 1, 4), 5.25, 100.095238);
+        check_stock(&stocks[1], "AAPL", make_date(2021, 2, 1), 0.25, 110.0);
+
+        assert_eq!(closed.len(), 1);
+        check_closed(&closed[0], make_date(2021, 1, 4), "5.25", "100.00", "0.50", "1.00");
+
+        let txs = vec![
+            buy(make_date(2021, 1, 4), "AAPL", "10.5", "100.00", "0.00", ""),
+            sell(make_date(2021, 3, 1), "AAPL", "10.75", "120.00", "0.00", ""),
+        ];
+        let err = apply_transactions(&txs, LotMethod::Fifo).err().unwrap();
+        assert_eq!(format!("{}", err), "Ledger: Sell quantity 10.75 exceeds open quantity 10.5 for AAPL on 2021-03-01");
+    }
+
+    fn make_transactions(sell_lot: &str) -> TransactionList {
+        // Ledger order is not date order
+        vec![
+            buy(make_date(2021, 2, 1), "AAPL", "200", "110.00", "4.00", "L2"),
+            sell(make_date(2021, 3, 1), "AAPL", "250", "120.00", "2.50", sell_lot),
+            buy(make_date(2021, 1, 4), "AAPL", "100", "100.00", "1.00", "L1"),
+        ]
+    }
+
+    fn buy(date: SPDate, symbol: &str, qty: &str, price: &str, fee: &str, lot: &str) -> Transaction {
+        make_transaction(date, TransactionType::Buy, symbol, qty, price, fee, lot)
+    }
+
+    fn sell(date: SPDate, symbol: &str, qty: &str, price: &str, fee: &str, lot: &str) -> Transaction {
+        make_transaction(date, TransactionType::Sell, symbol, qty, price, fee, lot)
+    }
+
+    fn make_transaction(date: SPDate, ttype: TransactionType, symbol: &str, qty: &str, price: &str, fee: &str, lot: &str) -> Transaction {
+        Transaction::new(date,
+                         ttype,
+                         String::from(symbol),
+                         StockType::Cash,
+                         Quantity::from_string(qty),
+                         Price::from_string(price),
+                         Price::from_string(fee),
+                         String::from(lot))
+    }
+
+    fn check_stock(stock: &Stock, symbol: &str, date: SPDate, qty: f64, price: f64) {
+        assert_eq!(stock.symbol, symbol);
+        assert_eq!(stock.date, date);
+        assert_eq!(stock.quantity, qty);
+        assert!((stock.base_price - price).abs() < 0.0001);
+    }
+
+    fn check_closed(cp: &ClosedPosition, base_date: SPDate, qty: &str, base_price: &str, base_fee: &str, exit_fee: &str) {
+        assert_eq!(cp.symbol, "AAPL");
+        assert_eq!(cp.base_date, base_date);
+        assert_eq!(cp.exit_date, make_date(2021, 3, 1));
+        assert_eq!(cp.quantity, Quantity::from_string(qty));
+        assert_eq!(cp.base_price, Price::from_string(base_price));
+        assert_eq!(cp.exit_price, Price::from_string("120.00"));
+        assert_eq!(cp.base_fee, Price::from_string(base_fee));
+        assert_eq!(cp.exit_fee, Price::from_string(exit_fee));
+        assert_eq!(cp.dividend, FP_0);
+    }
+}
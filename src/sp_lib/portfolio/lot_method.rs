@@ -0,0 +1,69 @@
+use std::fmt;
+use crate::util::error::Error;
+
+#[derive(Debug, Copy, Clone)]
+#[derive(PartialOrd, Ord, PartialEq, Eq)]
+pub enum LotMethod {
+    Fifo,     // First In First Out
+    Lifo,     // Last In First Out
+    Specific, // Specific Lot
+    Average   // Average Cost
+}
+
+pub fn lotmethod2str(lm: LotMethod) -> &'static str {
+    match lm {
+        LotMethod::Fifo => "fifo",
+        LotMethod::Lifo => "lifo",
+        LotMethod::Specific => "specific",
+        LotMethod::Average => "average"
+    }
+}
+
+pub fn str2lotmethod(lmstr: &str) -> Result<LotMethod, Error> {
+    match lmstr.to_lowercase().as_str() {
+        "fifo" => Ok(LotMethod::Fifo),
+        "lifo" => Ok(LotMethod::Lifo),
+        "specific" => Ok(LotMethod::Specific),
+        "average" => Ok(LotMethod::Average),
+        _ => Err(format!("Unknown lot method '{}'", lmstr).into())
+    }
+}
+
+impl fmt::Display for LotMethod {
+    fn fmt(self: &LotMethod, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", lotmethod2str(*self))
+    }
+}
+
+// --------------------------------------------------------------------------------
+// Unit Tests
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lot_method() {
+        let fifo = LotMethod::Fifo;
+        let lifo = LotMethod::Lifo;
+        let specific = LotMethod::Specific;
+        let average = LotMethod::Average;
+        let fifo_str = "fifo";
+        let lifo_str = "lifo";
+        let specific_str = "specific";
+        let average_str = "average";
+
+        assert_eq!(lotmethod2str(fifo), fifo_str);
+        assert_eq!(lotmethod2str(lifo), lifo_str);
+        assert_eq!(lotmethod2str(specific), specific_str);
+        assert_eq!(lotmethod2str(average), average_str);
+        assert!(str2lotmethod(fifo_str).unwrap() == fifo);
+        assert!(str2lotmethod(lifo_str).unwrap() == lifo);
+        assert!(str2lotmethod(specific_str).unwrap() == specific);
+        assert!(str2lotmethod(average_str).unwrap() == average);
+        assert!(str2lotmethod("FIFO").unwrap() == fifo);
+
+        assert_eq!(format!("{}", fifo), fifo_str);
+        assert_eq!(format!("{}", str2lotmethod("foobar").unwrap_err()), "Unknown lot method 'foobar'");
+    }
+}
@@ -0,0 +1,674 @@
+use std::collections::{BTreeSet, HashMap};
+
+use crate::datastore::datastore::DataStore;
+use crate::datastore::dividends::{self, Dividends};
+use crate::datastore::history::History;
+use crate::datastore::splits::{self, Splits};
+use crate::portfolio::cash_flow::{CashFlowList, CashFlowType};
+use crate::portfolio::closed_position::ClosedPositionList;
+use crate::portfolio::stock::{Price, StockList};
+use crate::portfolio::stock_type::StockType;
+use crate::util::datetime;
+use crate::util::datetime::{Period, SPDate};
+use crate::util::error::Error;
+
+// --------------------------------------------------------------------------------
+// Market History
+//
+// Closes, dividends and split ratios per symbol used to value positions over time.
+
+pub struct MarketHistory {
+    closes: HashMap<String, Vec<(SPDate, Price)>>,
+    dividends: HashMap<String, Vec<(SPDate, Price)>>,
+    splits: HashMap<String, Vec<(SPDate, Price)>>
+}
+
+impl MarketHistory {
+    pub fn new() -> Self {
+        MarketHistory {
+            closes: HashMap::new(),
+            dividends: HashMap::new(),
+            splits: HashMap::new()
+        }
+    }
+
+    // Load closes and dividends up to the given date for the symbols of stocks and closed positions.
+    pub fn from_ds(ds: &DataStore, stocks: &StockList, positions: &ClosedPositionList, to: &SPDate) -> Result<Self, Error> {
+        let mut symbols: Vec<&str> = stocks.iter().map(|s| s.symbol.as_str())
+            .chain(positions.iter().map(|p| p.symbol.as_str()))
+            .collect();
+        symbols.sort_unstable();
+        symbols.dedup();
+
+        let mut mkt = MarketHistory::new();
+        for symbol in symbols {
+            mkt.add_symbol_from_ds(ds, symbol, to)?;
+        }
+        Ok(mkt)
+    }
+
+    // Load closes and dividends of a symbol up to the given date, replacing any already loaded.
+    // All splits are loaded, as stock lots are split adjusted for all of them.
+    pub fn add_symbol_from_ds(&mut self, ds: &DataStore, symbol: &str, to: &SPDate) -> Result<(), Error> {
+        self.closes.remove(symbol);
+        self.dividends.remove(symbol);
+        self.splits.remove(symbol);
+
+        let hist = History::ds_select_if(ds, symbol, |entry| entry.date <= *to)?;
+        for entry in hist.entries() {
+            self.add_close(symbol, entry.date, entry.close);
+        }
+
+        if ds.symbol_exists(dividends::tag(), symbol) {
+            let div = Dividends::ds_select_if(ds, symbol, |entry| entry.date <= *to)?;
+            for entry in div.entries() {
+                self.add_dividend(symbol, entry.date, entry.price);
+            }
+        }
+
+        if ds.symbol_exists(splits::tag(), symbol) {
+            let splt = Splits::ds_select_all(ds, symbol)?;
+            for entry in splt.entries() {
+                self.add_split(symbol, entry.date, entry.ratio()?);
+            }
+        }
+        Ok(())
+    }
+
+    // Convert the closes and dividends of a symbol with the rate on their dates, dropping those without a rate.
+    pub fn convert_symbol<F>(&mut self, symbol: &str, rate_on: F)
+        where F: Fn(&SPDate) -> Option<Price> {
+        let convert = |entries: &mut Vec<(SPDate, Price)>| entries.retain_mut(|(date, price)| match rate_on(date) {
+            Some(rate) => {
+                *price *= rate;
+                true
+            },
+            None => false
+        });
+
+        if let Some(closes) = self.closes.get_mut(symbol) {
+            convert(closes);
+        }
+        if let Some(divs) = self.dividends.get_mut(symbol) {
+            convert(divs);
+        }
+    }
+
+    // Closes should be added in date order.
+    pub fn add_close(&mut self, symbol: &str, date: SPDate, close: Price) {
+        self.closes.entry(String::from(symbol)).or_default().push((date, close));
+    }
+
+    // Dividends should be added in date order.
+    pub fn add_dividend(&mut self, symbol: &str, date: SPDate, dividend: Price) {
+        self.dividends.entry(String::from(symbol)).or_default().push((date, dividend));
+    }
+
+    // Splits should be added in date order.
+    pub fn add_split(&mut self, symbol: &str, date: SPDate, ratio: Price) {
+        self.splits.entry(String::from(symbol)).or_default().push((date, ratio));
+    }
+
+    // Product of the ratios of the splits after date
+    pub fn split_ratio_after(&self, symbol: &str, date: &SPDate) -> Price {
+        match self.splits.get(symbol) {
+            Some(splits) => splits.iter().filter(|(dt, _)| dt > date).map(|(_, ratio)| ratio).product(),
+            None => 1.0
+        }
+    }
+
+    // Last close on or before date
+    pub fn close(&self, symbol: &str, date: &SPDate) -> Option<Price> {
+        let closes = self.closes.get(symbol)?;
+        let pos = closes.partition_point(|(dt, _)| dt <= date);
+        if pos > 0 { Some(closes[pos - 1].1) } else { None }
+    }
+
+    // Closes from date through to date
+    pub fn closes_between(&self, symbol: &str, from: &SPDate, to: &SPDate) -> Vec<(SPDate, Price)> {
+        match self.closes.get(symbol) {
+            Some(closes) => closes.iter().filter(|(dt, _)| dt >= from && dt <= to).cloned().collect(),
+            None => Vec::new()
+        }
+    }
+
+    // Dividend per share paid on date
+    pub fn dividend(&self, symbol: &str, date: &SPDate) -> Price {
+        match self.dividends.get(symbol) {
+            Some(divs) => divs.iter().filter(|(dt, _)| dt == date).map(|(_, px)| px).sum(),
+            None => 0.0
+        }
+    }
+
+    fn dates(&self) -> impl Iterator<Item = &SPDate> {
+        self.closes.values().chain(self.dividends.values()).flat_map(|entries| entries.iter().map(|(dt, _)| dt))
+    }
+}
+
+impl Default for MarketHistory {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// --------------------------------------------------------------------------------
+// Value Series
+
+// Portfolio value, cash and cost basis at the close of a date, with money put into and taken out of the portfolio on that date.
+// Without cash, inflows are buys at cost including fees and outflows are sell proceeds net of fees and dividends paid.
+// With cash, money moved between cash and holdings stays in the portfolio, and inflows and outflows are netted.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ValuePoint {
+    pub date: SPDate,
+    pub value: Price,   // Holdings value plus cash
+    pub cash: Price,
+    pub cost: Price,
+    pub inflow: Price,
+    pub outflow: Price
+}
+
+impl ValuePoint {
+    #[inline(always)]
+    pub fn unrealized(&self) -> Price {
+        self.value - self.cash - self.cost
+    }
+
+    #[inline(always)]
+    pub fn unrealized_pct(&self) -> Price {
+        if self.cost > 0.0 { 100.0 * self.unrealized() / self.cost } else { 0.0 }
+    }
+}
+
+// Daily value series of the holdings and cash through to, see value_series_if and add_cash.
+pub fn value_series(stocks: &StockList,
+                    positions: &ClosedPositionList,
+                    cash: Price,
+                    flows: &CashFlowList,
+                    mkt: &MarketHistory,
+                    to: &SPDate) -> Vec<ValuePoint> {
+    add_cash(&value_series_if(stocks, positions, mkt, to, |_| true), cash, flows, to)
+}
+
+// Daily value series of the stocks and closed positions with symbols matching the predicate, without cash,
+// from the first buy date through to, over dates with closes, dividends or flows.
+// Positions are valued at the latest close, or at cost when there is no close yet. Index stocks are skipped.
+// Closes and dividends are not split adjusted, so split adjusted stock quantities are valued in the shares held
+// on each date, and closed position quantities, held through to the exit date, likewise.
+pub fn value_series_if(stocks: &StockList,
+                       positions: &ClosedPositionList,
+                       mkt: &MarketHistory,
+                       to: &SPDate,
+                       pred: impl Fn(&str) -> bool) -> Vec<ValuePoint> {
+    let stocks: Vec<_> = stocks.iter().filter(|s| s.stype != StockType::Index && pred(&s.symbol)).collect();
+    let positions: Vec<_> = positions.iter().filter(|p| p.stype != StockType::Index && pred(&p.symbol)).collect();
+
+    let first = stocks.iter().map(|s| s.date).chain(positions.iter().map(|p| p.base_date)).min();
+    let first = match first {
+        Some(date) => date,
+        None => return Vec::new()
+    };
+
+    let dates: BTreeSet<SPDate> = mkt.dates().cloned()
+        .chain(stocks.iter().map(|s| s.date))
+        .chain(positions.iter().flat_map(|p| [p.base_date, p.exit_date]))
+        .filter(|date| *date >= first && date <= to)
+        .collect();
+
+    let mut series: Vec<ValuePoint> = Vec::new();
+    for date in dates {
+        let mut point = ValuePoint { date, value: 0.0, cash: 0.0, cost: 0.0, inflow: 0.0, outflow: 0.0 };
+
+        for stock in stocks.iter().filter(|s| s.date <= date) {
+            let ratio = mkt.split_ratio_after(&stock.symbol, &date);
+            point.value += stock.quantity * mkt.close(&stock.symbol, &date).map_or(stock.base_price, |close| close / ratio);
+            point.cost += stock.base_notional();
+            if stock.date == date {
+                point.inflow += stock.base_notional();
+            } else {
+                point.outflow += stock.quantity / ratio * mkt.dividend(&stock.symbol, &date);
+            }
+        }
+
+        for pos in positions.iter().filter(|p| p.base_date <= date && date <= p.exit_date) {
+            if date < pos.exit_date {
+                let quantity = pos.quantity.to_float();
+                let ratio = mkt.split_ratio_after(&pos.symbol, &date) / mkt.split_ratio_after(&pos.symbol, &pos.exit_date);
+                point.value += quantity * mkt.close(&pos.symbol, &date).map_or_else(|| pos.base_price.to_float(), |close| close / ratio);
+                point.cost += (pos.base_notional() + pos.base_fee).to_float();
+            } else {
+                point.outflow += (pos.exit_notional() - pos.exit_fee + pos.dividend).to_float();
+            }
+            if pos.base_date == date {
+                point.inflow += (pos.base_notional() + pos.base_fee).to_float();
+            }
+        }
+
+        series.push(point);
+    }
+    series
+}
+
+// Add the opening cash and the cash balance of flows sorted by date to a holdings value series.
+// Transfers between cash and holdings, such as purchases, proceeds and dividends, cancel out the holdings
+// inflows and outflows, while deposits and withdrawals are external flows. Interest and fees are returns on cash.
+// The opening cash is put in on the first date. When cash would go negative, the shortfall is put in.
+pub fn add_cash(holdings: &[ValuePoint], cash: Price, flows: &CashFlowList, to: &SPDate) -> Vec<ValuePoint> {
+    let flows: Vec<_> = flows.iter().filter(|flow| flow.date <= *to).collect();
+    let dates: BTreeSet<SPDate> = holdings.iter().map(|pt| pt.date)
+        .chain(flows.iter().map(|flow| flow.date))
+        .collect();
+
+    let mut series: Vec<ValuePoint> = Vec::with_capacity(dates.len());
+    let mut balance = cash;
+    let mut net_in = cash;
+    let (mut value, mut cost) = (0.0, 0.0);
+    let (mut next_pt, mut next_flow) = (0, 0);
+    for date in dates {
+        if next_pt < holdings.len() && holdings[next_pt].date == date {
+            let pt = &holdings[next_pt];
+            value = pt.value;
+            cost = pt.cost;
+            net_in += pt.inflow - pt.outflow;
+            next_pt += 1;
+        }
+
+        while next_flow < flows.len() && flows[next_flow].date == date {
+            let flow = flows[next_flow];
+            let amount = flow.signed_amount().to_float();
+            balance += amount;
+            if !flow.is_income() {
+                net_in += amount;
+            }
+            next_flow += 1;
+        }
+
+        if balance < 0.0 {
+            net_in -= balance;
+            balance = 0.0;
+        }
+
+        let (inflow, outflow) = if net_in >= 0.0 { (net_in, 0.0) } else { (0.0, -net_in) };
+        series.push(ValuePoint { date, value: value + balance, cash: balance, cost, inflow, outflow });
+        net_in = 0.0;
+    }
+    series
+}
+
+// Resample a value series to one point per period, at the last date of the period.
+// Inflows and outflows are summed over the period.
+pub fn resample_series(series: &[ValuePoint], period: Period) -> Vec<ValuePoint> {
+    let mut sampled: Vec<ValuePoint> = Vec::new();
+    for pt in series {
+        match sampled.last_mut() {
+            Some(last) if datetime::period_start(&last.date, period) == datetime::period_start(&pt.date, period) => {
+                last.date = pt.date;
+                last.value = pt.value;
+                last.cash = pt.cash;
+                last.cost = pt.cost;
+                last.inflow += pt.inflow;
+                last.outflow += pt.outflow;
+            },
+            _ => sampled.push(pt.clone())
+        }
+    }
+    sampled
+}
+
+// Growth of one unit invested at the first point, chaining returns net of flows as with TWR.
+pub fn growth_series(series: &[ValuePoint]) -> Vec<(SPDate, Price)> {
+    let mut growth: Vec<(SPDate, Price)> = Vec::with_capacity(series.len());
+    let mut level = 1.0;
+    for (i, pt) in series.iter().enumerate() {
+        if i > 0 {
+            let base = series[i - 1].value + pt.inflow;
+            if base > 0.0 {
+                level *= (pt.value + pt.outflow) / base;
+            }
+        }
+        growth.push((pt.date, level));
+    }
+    growth
+}
+
+// --------------------------------------------------------------------------------
+// Returns
+
+pub struct Performance {
+    pub from: SPDate,
+    pub to: SPDate,
+    pub start_value: Price,
+    pub end_value: Price,
+    pub inflow: Price,
+    pub outflow: Price,
+    pub deposits: Price,    // Deposits in the cash flows
+    pub withdrawals: Price, // Withdrawals in the cash flows
+    pub twr: Price,         // Cumulative time-weighted return
+    pub mwr: Option<Price>  // Annualized money-weighted return (XIRR)
+}
+
+impl Performance {
+    // Time-weighted return annualized over the period
+    #[inline(always)]
+    pub fn twr_annualized(&self) -> Price {
+        annualize(self.twr, datetime::count_days(&self.from, &self.to))
+    }
+}
+
+// Time-weighted and money-weighted returns over [from, to] of a value series, with the deposits and withdrawals
+// of the cash flows over [from, to]. The value at the close of the last date before from is the start value.
+pub fn calc_performance(series: &[ValuePoint], cash_flows: &CashFlowList, from: &SPDate, to: &SPDate) -> Performance {
+    let start = series.partition_point(|pt| pt.date < *from);
+    let end = series.partition_point(|pt| pt.date <= *to);
+    let start_value = if start > 0 { series[start - 1].value } else { 0.0 };
+    let points = if start < end { &series[start..end] } else { &series[0..0] };
+    let end_value = points.last().map(|pt| pt.value).unwrap_or(start_value);
+
+    // Investor cash flows; money put in is negative
+    let mut flows: Vec<(SPDate, Price)> = vec![(*from, -start_value)];
+    flows.extend(points.iter().map(|pt| (pt.date, pt.outflow - pt.inflow)));
+    flows.push((*to, end_value));
+
+    let external = |ftype| cash_flows.iter()
+        .filter(|flow| flow.ftype == ftype && flow.date >= *from && flow.date <= *to)
+        .fold(0.0, |total, flow| total + flow.amount.to_float());
+
+    Performance {
+        from: *from,
+        to: *to,
+        start_value,
+        end_value,
+        inflow: points.iter().map(|pt| pt.inflow).sum(),
+        outflow: points.iter().map(|pt| pt.outflow).sum(),
+        deposits: external(CashFlowType::Deposit),
+        withdrawals: external(CashFlowType::Withdrawal),
+        twr: twr(start_value, points),
+        mwr: xirr(&flows).ok()
+    }
+}
+
+// Cumulative time-weighted return, chaining daily returns (value + outflow) / (previous value + inflow).
+pub fn twr(start_value: Price, points: &[ValuePoint]) -> Price {
+    let mut prev = start_value;
+    let mut growth = 1.0;
+    for pt in points {
+        let base = prev + pt.inflow;
+        if base > 0.0 {
+            growth *= (pt.value + pt.outflow) / base;
+        }
+        prev = pt.value;
+    }
+    growth - 1.0
+}
+
+// Annualized internal rate of return of dated cash flows, with an actual/365 day count.
+pub fn xirr(flows: &[(SPDate, Price)]) -> Result<Price, Error> {
+    let flows: Vec<(Price, Price)> = match flows.first() {
+        Some((first, _)) => flows
+            .iter()
+            .filter(|(_, cf)| *cf != 0.0)
+            .map(|(date, cf)| (datetime::count_days(first, date) as Price / 365.0, *cf))
+            .collect(),
+        None => Vec::new()
+    };
+
+    if !flows.iter().any(|(_, cf)| *cf > 0.0) || !flows.iter().any(|(_, cf)| *cf < 0.0) {
+        return Err("XIRR: Cash flows must include positive and negative values".into());
+    }
+
+    let npv = |rate: Price| flows.iter().fold(0.0, |sum, (years, cf)| sum + cf / (1.0 + rate).powf(*years));
+
+    // Bracket a root, then bisect
+    let mut lo: Price = -0.999999;
+    let mut hi: Price = 1.0;
+    while npv(lo).signum() == npv(hi).signum() {
+        hi *= 2.0;
+        if hi > 1.0e6 {
+            return Err("XIRR: Failed to find a solution".into());
+        }
+    }
+
+    let lo_sign = npv(lo).signum();
+    for _ in 0..200 {
+        let mid = (lo + hi) / 2.0;
+        if npv(mid).signum() == lo_sign {
+            lo = mid;
+        } else {
+            hi = mid;
+        }
+        if hi - lo < 1.0e-10 {
+            break;
+        }
+    }
+    Ok((lo + hi) / 2.0)
+}
+
+// Compound a return over days into a yearly return
+pub fn annualize(ret: Price, days: i64) -> Price {
+    if days > 0 && ret > -1.0 {
+        (1.0 + ret).powf(365.0 / days as Price) - 1.0
+    } else {
+        0.0
+    }
+}
+
+// --------------------------------------------------------------------------------
+// Unit Tests
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::portfolio::cash_flow::CashFlow;
+    use crate::portfolio::closed_position::{ClosedPosition, Quantity};
+    use crate::portfolio::stock::Stock;
+    use crate::util::datetime::make_date;
+    use crate::util::fixed_price::{FixedPrice, FP_0};
+    use crate::util::price_type::price_eql;
+
+    #[test]
+    fn test_market_history() {
+        let mkt = make_market();
+        assert_eq!(mkt.close("AAPL", &make_date(2021, 1, 3)), None);
+        assert_eq!(mkt.close("AAPL", &make_date(2021, 1, 4)), Some(100.0));
+        assert_eq!(mkt.close("AAPL", &make_date(2021, 1, 9)), Some(120.0));
+        assert_eq!(mkt.close("DELL", &make_date(2021, 1, 9)), None);
+        assert_eq!(mkt.dividend("AAPL", &make_date(2021, 1, 6)), 1.0);
+        assert_eq!(mkt.dividend("AAPL", &make_date(2021, 1, 5)), 0.0);
+        assert_eq!(mkt.closes_between("AAPL", &make_date(2021, 1, 5), &make_date(2021, 1, 6)),
+                   vec![(make_date(2021, 1, 5), 110.0), (make_date(2021, 1, 6), 100.0)]);
+        assert!(mkt.closes_between("DELL", &make_date(2021, 1, 5), &make_date(2021, 1, 6)).is_empty());
+        assert_eq!(mkt.split_ratio_after("AAPL", &make_date(2021, 1, 4)), 1.0);
+    }
+
+    #[test]
+    fn test_value_series() {
+        let stocks = vec![Stock::new(String::from("AAPL"), StockType::Cash, make_date(2021, 1, 4), 10.0, 100.0)];
+        let positions = vec![ClosedPosition::new(String::from("AAPL"), StockType::Cash,
+                                                 make_date(2021, 1, 5), make_date(2021, 1, 7),
+                                                 Quantity::from_unsigned(5), FixedPrice::from_string("110.00"),
+                                                 FixedPrice::from_string("130.00"), FixedPrice::from_string("1.00"),
+                                                 FixedPrice::from_string("1.00"), FP_0)];
+
+        let series = value_series(&stocks, &positions, 0.0, &CashFlowList::new(), &make_market(), &make_date(2021, 1, 7));
+        assert_eq!(series, vec![
+            ValuePoint { date: make_date(2021, 1, 4), value: 1000.0, cash: 0.0, cost: 1000.0, inflow: 1000.0, outflow: 0.0 },
+            ValuePoint { date: make_date(2021, 1, 5), value: 1650.0, cash: 0.0, cost: 1551.0, inflow: 551.0, outflow: 0.0 },
+            ValuePoint { date: make_date(2021, 1, 6), value: 1500.0, cash: 0.0, cost: 1551.0, inflow: 0.0, outflow: 10.0 },
+            ValuePoint { date: make_date(2021, 1, 7), value: 1300.0, cash: 0.0, cost: 1000.0, inflow: 0.0, outflow: 649.0 }
+        ]);
+
+        assert_eq!(series[1].unrealized(), 99.0);
+        assert!(price_eql(series[3].unrealized_pct(), 30.0));
+
+        let series = value_series_if(&stocks, &positions, &make_market(), &make_date(2021, 1, 7), |sym| sym == "DELL");
+        assert!(series.is_empty());
+    }
+
+    #[test]
+    fn test_value_series_split() {
+        // 2:1 split on 2021-01-11, with the close and dividend after it per post-split share
+        let mut mkt = make_market();
+        mkt.add_close("AAPL", make_date(2021, 1, 11), 60.0);
+        mkt.add_dividend("AAPL", make_date(2021, 1, 11), 0.5);
+        mkt.add_split("AAPL", make_date(2021, 1, 11), 2.0);
+        assert_eq!(mkt.split_ratio_after("AAPL", &make_date(2021, 1, 8)), 2.0);
+        assert_eq!(mkt.split_ratio_after("AAPL", &make_date(2021, 1, 11)), 1.0);
+
+        // 10 shares at 100.00 split adjusted to 20 shares at 50.00
+        let stocks = vec![Stock::new(String::from("AAPL"), StockType::Cash, make_date(2021, 1, 4), 20.0, 50.0)];
+        let positions = vec![ClosedPosition::new(String::from("AAPL"), StockType::Cash,
+                                                 make_date(2021, 1, 8), make_date(2021, 1, 11),
+                                                 Quantity::from_unsigned(10), FixedPrice::from_string("60.00"),
+                                                 FixedPrice::from_string("60.00"), FP_0, FP_0, FP_0)];
+
+        let series = value_series(&stocks, &positions, 0.0, &CashFlowList::new(), &mkt, &make_date(2021, 1, 11));
+        assert_eq!(series.iter().map(|pt| pt.value).collect::<Vec<Price>>(), vec![1000.0, 1100.0, 1000.0, 1300.0, 1800.0, 1200.0]);
+        assert_eq!(series.iter().map(|pt| pt.outflow).collect::<Vec<Price>>(), vec![0.0, 0.0, 10.0, 0.0, 0.0, 610.0]);
+        assert_eq!(series.iter().map(|pt| pt.cost).collect::<Vec<Price>>(), vec![1000.0, 1000.0, 1000.0, 1000.0, 1600.0, 1000.0]);
+    }
+
+    #[test]
+    fn test_value_series_with_cash() {
+        let stocks = vec![Stock::new(String::from("AAPL"), StockType::Cash, make_date(2021, 1, 4), 10.0, 100.0)];
+        let flow = |date, ftype, amount: &str| CashFlow::new(date, ftype, FixedPrice::from_string(amount), String::new());
+        let flows = vec![
+            flow(make_date(2021, 1, 5), CashFlowType::Deposit, "200.00"),
+            flow(make_date(2021, 1, 6), CashFlowType::Dividend, "10.00"),
+            flow(make_date(2021, 1, 6), CashFlowType::Interest, "5.00"),
+            flow(make_date(2021, 1, 8), CashFlowType::Withdrawal, "100.00")
+        ];
+
+        let series = value_series(&stocks, &Vec::new(), 500.0, &flows, &make_market(), &make_date(2021, 1, 8));
+        assert_eq!(series, vec![
+            ValuePoint { date: make_date(2021, 1, 4), value: 1500.0, cash: 500.0, cost: 1000.0, inflow: 1500.0, outflow: 0.0 },
+            ValuePoint { date: make_date(2021, 1, 5), value: 1800.0, cash: 700.0, cost: 1000.0, inflow: 200.0, outflow: 0.0 },
+            ValuePoint { date: make_date(2021, 1, 6), value: 1715.0, cash: 715.0, cost: 1000.0, inflow: 0.0, outflow: 0.0 },
+            ValuePoint { date: make_date(2021, 1, 7), value: 2015.0, cash: 715.0, cost: 1000.0, inflow: 0.0, outflow: 0.0 },
+            ValuePoint { date: make_date(2021, 1, 8), value: 1815.0, cash: 615.0, cost: 1000.0, inflow: 0.0, outflow: 100.0 }
+        ]);
+        assert_eq!(series[1].unrealized(), 100.0);
+
+        let perf = calc_performance(&series, &flows, &make_date(2021, 1, 5), &make_date(2021, 1, 8));
+        assert_eq!(perf.deposits, 200.0);
+        assert_eq!(perf.withdrawals, 100.0);
+
+        // Shortfall of cash is put in
+        let series = add_cash(&[], 0.0, &vec![flow(make_date(2021, 1, 5), CashFlowType::Fee, "10.00")], &make_date(2021, 1, 8));
+        assert_eq!(series, vec![
+            ValuePoint { date: make_date(2021, 1, 5), value: 0.0, cash: 0.0, cost: 0.0, inflow: 10.0, outflow: 0.0 }
+        ]);
+    }
+
+    #[test]
+    fn test_resample_series() {
+        let point = |date, value, inflow, outflow| ValuePoint { date, value, cash: 0.0, cost: 1000.0, inflow, outflow };
+        let series = vec![
+            point(make_date(2021, 3, 30), 1000.0, 1000.0, 0.0),
+            point(make_date(2021, 3, 31), 1010.0, 0.0, 5.0),
+            point(make_date(2021, 4, 1), 1020.0, 0.0, 0.0),
+            point(make_date(2021, 4, 5), 990.0, 0.0, 5.0)
+        ];
+
+        assert_eq!(resample_series(&series, Period::Daily), series);
+        assert_eq!(resample_series(&series, Period::Weekly), vec![
+            point(make_date(2021, 4, 1), 1020.0, 1000.0, 5.0),
+            point(make_date(2021, 4, 5), 990.0, 0.0, 5.0)
+        ]);
+        assert_eq!(resample_series(&series, Period::Monthly), vec![
+            point(make_date(2021, 3, 31), 1010.0, 1000.0, 5.0),
+            point(make_date(2021, 4, 5), 990.0, 0.0, 5.0)
+        ]);
+        assert!(resample_series(&[], Period::Weekly).is_empty());
+    }
+
+    #[test]
+    fn test_growth_series() {
+        let points = vec![
+            ValuePoint { date: make_date(2021, 1, 4), value: 1000.0, cash: 0.0, cost: 1000.0, inflow: 1000.0, outflow: 0.0 },
+            ValuePoint { date: make_date(2021, 1, 5), value: 1100.0, cash: 0.0, cost: 1000.0, inflow: 0.0, outflow: 0.0 },
+            ValuePoint { date: make_date(2021, 1, 6), value: 3300.0, cash: 0.0, cost: 3200.0, inflow: 2200.0, outflow: 0.0 },
+            ValuePoint { date: make_date(2021, 1, 7), value: 2970.0, cash: 0.0, cost: 3200.0, inflow: 0.0, outflow: 0.0 }
+        ];
+        let growth = growth_series(&points);
+        assert_eq!(growth.len(), 4);
+        assert_eq!(growth[0], (make_date(2021, 1, 4), 1.0));
+        assert!(price_eql(growth[1].1, 1.1));
+        assert!(price_eql(growth[2].1, 1.1));
+        assert!(price_eql(growth[3].1, 0.99));
+        assert!(growth_series(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_twr() {
+        let points = vec![
+            ValuePoint { date: make_date(2021, 1, 4), value: 1000.0, cash: 0.0, cost: 1000.0, inflow: 1000.0, outflow: 0.0 },
+            ValuePoint { date: make_date(2021, 1, 5), value: 1100.0, cash: 0.0, cost: 1000.0, inflow: 0.0, outflow: 0.0 },
+            ValuePoint { date: make_date(2021, 1, 6), value: 3300.0, cash: 0.0, cost: 3200.0, inflow: 2200.0, outflow: 0.0 },
+            ValuePoint { date: make_date(2021, 1, 7), value: 2970.0, cash: 0.0, cost: 3200.0, inflow: 0.0, outflow: 0.0 }
+        ];
+        // Flows do not change the return: 1.1 * 1.0 * 0.9
+        assert!(price_eql(twr(0.0, &points), -0.01));
+        assert!(price_eql(twr(1000.0, &points[1..]), -0.01));
+        assert_eq!(twr(0.0, &[]), 0.0);
+    }
+
+    #[test]
+    fn test_xirr() {
+        let flows = vec![(make_date(2020, 1, 1), -1000.0), (make_date(2020, 12, 31), 1100.0)];
+        assert!((xirr(&flows).unwrap() - 0.1).abs() < 1.0e-6);
+
+        let flows = vec![(make_date(2019, 1, 1), -1000.0), (make_date(2020, 1, 1), -1000.0), (make_date(2021, 1, 1), 2310.0)];
+        assert!((xirr(&flows).unwrap() - 0.1).abs() < 1.0e-3);
+
+        let flows = vec![(make_date(2020, 1, 1), -1000.0), (make_date(2020, 12, 31), 500.0)];
+        assert!((xirr(&flows).unwrap() + 0.5).abs() < 1.0e-6);
+
+        let flows = vec![(make_date(2020, 1, 1), -1000.0)];
+        assert_eq!(format!("{}", xirr(&flows).unwrap_err()), "XIRR: Cash flows must include positive and negative values");
+    }
+
+    #[test]
+    fn test_calc_performance() {
+        let points = vec![
+            ValuePoint { date: make_date(2020, 1, 1), value: 1000.0, cash: 0.0, cost: 1000.0, inflow: 1000.0, outflow: 0.0 },
+            ValuePoint { date: make_date(2020, 7, 1), value: 1050.0, cash: 0.0, cost: 1000.0, inflow: 0.0, outflow: 0.0 },
+            ValuePoint { date: make_date(2020, 12, 31), value: 1100.0, cash: 0.0, cost: 1000.0, inflow: 0.0, outflow: 0.0 }
+        ];
+
+        let perf = calc_performance(&points, &CashFlowList::new(), &make_date(2020, 1, 1), &make_date(2020, 12, 31));
+        assert_eq!(perf.start_value, 0.0);
+        assert_eq!(perf.end_value, 1100.0);
+        assert_eq!(perf.inflow, 1000.0);
+        assert_eq!(perf.outflow, 0.0);
+        assert!(price_eql(perf.twr, 0.1));
+        assert!(price_eql(perf.twr_annualized(), 0.1));
+        assert!((perf.mwr.unwrap() - 0.1).abs() < 1.0e-6);
+
+        let perf = calc_performance(&points, &CashFlowList::new(), &make_date(2020, 7, 2), &make_date(2020, 12, 31));
+        assert_eq!(perf.start_value, 1050.0);
+        assert_eq!(perf.inflow, 0.0);
+        assert!(price_eql(perf.twr, 1100.0 / 1050.0 - 1.0));
+
+        let perf = calc_performance(&points, &CashFlowList::new(), &make_date(2021, 1, 1), &make_date(2021, 6, 30));
+        assert_eq!(perf.start_value, 1100.0);
+        assert_eq!(perf.end_value, 1100.0);
+        assert_eq!(perf.twr, 0.0);
+    }
+
+    #[test]
+    fn test_annualize() {
+        assert!(price_eql(annualize(0.21, 730), 0.1));
+        assert!(price_eql(annualize(0.1, 365), 0.1));
+        assert_eq!(annualize(0.1, 0), 0.0);
+    }
+
+    fn make_market() -> MarketHistory {
+        let mut mkt = MarketHistory::new();
+        mkt.add_close("AAPL", make_date(2021, 1, 4), 100.0);
+        mkt.add_close("AAPL", make_date(2021, 1, 5), 110.0);
+        mkt.add_close("AAPL", make_date(2021, 1, 6), 100.0);
+        mkt.add_close("AAPL", make_date(2021, 1, 7), 130.0);
+        mkt.add_close("AAPL", make_date(2021, 1, 8), 120.0);
+        mkt.add_dividend("AAPL", make_date(2021, 1, 6), 1.0);
+        mkt
+    }
+}
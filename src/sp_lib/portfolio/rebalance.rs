@@ -0,0 +1,254 @@
+use std::collections::{BTreeMap, HashMap};
+
+use crate::portfolio::algorithms;
+use crate::portfolio::stock::{Price, Quantity, StockList};
+use crate::portfolio::target::{self, Target, TargetKey};
+
+// --------------------------------------------------------------------------------
+// Rebalance
+
+// Current value and weight of a target, and the value to buy (positive) or sell (negative).
+pub struct TargetDrift {
+    pub name: String,
+    pub weight: f64,
+    pub tolerance: f64,
+    pub value: Price,
+    pub cur_weight: f64,
+    pub trade: Price
+}
+
+impl TargetDrift {
+    // Current weight less target weight, in percent points.
+    #[inline(always)]
+    pub fn drift(&self) -> f64 {
+        self.cur_weight - self.weight
+    }
+}
+
+// Whole shares to buy (positive) or sell (negative) of a symbol.
+pub struct Trade {
+    pub symbol: String,
+    pub price: Price,
+    pub shares: Quantity
+}
+
+impl Trade {
+    #[inline(always)]
+    pub fn value(&self) -> Price {
+        self.shares * self.price
+    }
+}
+
+pub struct Rebalance {
+    pub total: Price,       // Stocks latest notional plus cash
+    pub cash: Price,
+    pub other: Price,       // Latest notional of stocks without a target
+    pub drifts: Vec<TargetDrift>,
+    pub trades: Vec<Trade>,
+    pub unpriced: Vec<String>,  // Symbols to trade without a price
+    pub untradable: Vec<String> // Stock type targets to trade without held symbols
+}
+
+impl Rebalance {
+    // Cash left after the trades.
+    pub fn cash_after(&self) -> Price {
+        self.trades.iter().fold(self.cash, |cash, trade| cash - trade.value())
+    }
+}
+
+// Rebalance stocks and cash to target weights. Targets drifting more than their tolerance are
+// traded back to their target weight, others are left as they are. Stocks without a target are
+// not traded. A stock type target trades the symbols held of that type in proportion to their
+// value. With cash only, sales are dropped and buys are scaled down to the cash available.
+// Trades are rounded toward zero to whole shares at the given symbol prices, symbols without a
+// price are not traded and are listed as unpriced. Stock type targets without held symbols have no
+// symbol to trade and are listed as untradable.
+pub fn rebalance(stocks: &StockList, cash: Price, targets: &[Target], prices: &HashMap<String, Price>, cash_only: bool) -> Rebalance {
+    let total = algorithms::latest_notional(stocks) + cash;
+
+    let mut values: Vec<Price> = vec![0.0; targets.len()];
+    let mut symbol_values: Vec<BTreeMap<&str, Price>> = vec![BTreeMap::new(); targets.len()];
+    let mut other: Price = 0.0;
+    for stock in stocks.iter() {
+        match target::target_index(targets, stock) {
+            Some(index) => {
+                values[index] += stock.latest_notional();
+                *symbol_values[index].entry(stock.symbol.as_str()).or_insert(0.0) += stock.latest_notional();
+            },
+            None => other += stock.latest_notional()
+        }
+    }
+
+    let mut drifts: Vec<TargetDrift> = targets.iter().zip(values.iter()).map(|(target, value)| {
+        let cur_weight = if total > 0.0 { 100.0 * value / total } else { 0.0 };
+        let trade = if (cur_weight - target.weight).abs() > target.tolerance { target.weight * total / 100.0 - value } else { 0.0 };
+        TargetDrift {
+            name: target.name(),
+            weight: target.weight,
+            tolerance: target.tolerance,
+            value: *value,
+            cur_weight,
+            trade
+        }
+    }).collect();
+
+    if cash_only {
+        let buys = drifts.iter().fold(0.0, |total, drift| total + drift.trade.max(0.0));
+        let scale = if buys > cash.max(0.0) { cash.max(0.0) / buys } else { 1.0 };
+        for drift in drifts.iter_mut() {
+            drift.trade = drift.trade.max(0.0) * scale;
+        }
+    }
+
+    let mut trades: Vec<Trade> = Vec::new();
+    let mut unpriced: Vec<String> = Vec::new();
+    let mut untradable: Vec<String> = Vec::new();
+    for (index, drift) in drifts.iter().enumerate().filter(|(_, drift)| drift.trade != 0.0) {
+        if matches!(targets[index].key, TargetKey::Type(_)) && symbol_values[index].is_empty() {
+            untradable.push(drift.name.clone());
+            continue;
+        }
+
+        let symbol_trades: Vec<(&str, Price)> = match &targets[index].key {
+            TargetKey::Symbol(symbol) => vec![(symbol.as_str(), drift.trade)],
+            TargetKey::Type(_) => symbol_values[index].iter()
+                .map(|(symbol, value)| (*symbol, drift.trade * value / values[index]))
+                .collect()
+        };
+
+        for (symbol, value) in symbol_trades {
+            match prices.get(symbol).filter(|price| **price > 0.0) {
+                Some(price) => {
+                    let shares = (value / price).trunc();
+                    if shares != 0.0 {
+                        trades.push(Trade { symbol: String::from(symbol), price: *price, shares });
+                    }
+                },
+                None => unpriced.push(String::from(symbol))
+            }
+        }
+    }
+
+    Rebalance {
+        total,
+        cash,
+        other,
+        drifts,
+        trades,
+        unpriced,
+        untradable
+    }
+}
+
+// --------------------------------------------------------------------------------
+// Unit Tests
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::portfolio::stock::Stock;
+    use crate::portfolio::stock_type::StockType;
+    use crate::util::datetime;
+    use crate::util::price_type::price_eql;
+
+    fn test_stocks() -> StockList {
+        vec![make_stock("AAPL", StockType::Cash, 50.0, 100.0),
+             make_stock("ICLN", StockType::ETF, 100.0, 20.0),
+             make_stock("SPYD", StockType::ETF, 50.0, 40.0),
+             make_stock("DELL", StockType::Cash, 10.0, 80.0)]
+    }
+
+    fn test_targets() -> Vec<Target> {
+        vec![Target::new(TargetKey::Symbol(String::from("AAPL")), 40.0, 2.0),
+             Target::new(TargetKey::Type(StockType::ETF), 30.0, 5.0),
+             Target::new(TargetKey::Symbol(String::from("MSFT")), 10.0, 0.0)]
+    }
+
+    fn test_prices() -> HashMap<String, Price> {
+        let mut prices = HashMap::new();
+        prices.insert(String::from("AAPL"), 100.0);
+        prices.insert(String::from("ICLN"), 20.0);
+        prices.insert(String::from("SPYD"), 40.0);
+        prices.insert(String::from("DELL"), 80.0);
+        prices.insert(String::from("MSFT"), 250.0);
+        prices
+    }
+
+    #[test]
+    fn test_rebalance() {
+        // Total 5000 + 2000 + 2000 + 800 + 1200 cash = 11000
+        let rebal = rebalance(&test_stocks(), 1200.0, &test_targets(), &test_prices(), false);
+        assert!(price_eql(rebal.total, 11000.0));
+        assert!(price_eql(rebal.other, 800.0));
+        assert_eq!(rebal.drifts.len(), 3);
+
+        let aapl = &rebal.drifts[0];
+        assert_eq!(aapl.name, "AAPL");
+        assert!(price_eql(aapl.value, 5000.0));
+        assert!(price_eql(aapl.cur_weight, 500.0 / 11.0));
+        assert!(price_eql(aapl.drift(), 500.0 / 11.0 - 40.0));
+        assert!(price_eql(aapl.trade, -600.0));
+
+        let etf = &rebal.drifts[1];
+        assert_eq!(etf.name, "type:etf");
+        assert!(price_eql(etf.value, 4000.0));
+        assert!(price_eql(etf.trade, -700.0));
+
+        let msft = &rebal.drifts[2];
+        assert!(price_eql(msft.value, 0.0));
+        assert!(price_eql(msft.trade, 1100.0));
+
+        let trades: Vec<(&str, Quantity)> = rebal.trades.iter().map(|t| (t.symbol.as_str(), t.shares)).collect();
+        assert_eq!(trades, vec![("AAPL", -6.0), ("ICLN", -17.0), ("SPYD", -8.0), ("MSFT", 4.0)]);
+        assert!(price_eql(rebal.cash_after(), 1200.0 + 600.0 + 340.0 + 320.0 - 1000.0));
+        assert!(rebal.unpriced.is_empty());
+
+        // A symbol target without a price is listed instead of traded
+        let mut prices = test_prices();
+        prices.remove("MSFT");
+        let rebal = rebalance(&test_stocks(), 1200.0, &test_targets(), &prices, false);
+        assert!(rebal.trades.iter().all(|t| t.symbol != "MSFT"));
+        assert_eq!(rebal.unpriced, vec!["MSFT"]);
+        assert!(rebal.untradable.is_empty());
+    }
+
+    #[test]
+    fn test_rebalance_untradable() {
+        // A stock type target without held symbols is listed instead of traded
+        let mut targets = test_targets();
+        targets.push(Target::new(TargetKey::Type(StockType::Index), 5.0, 0.0));
+        let rebal = rebalance(&test_stocks(), 1200.0, &targets, &test_prices(), false);
+        assert!(price_eql(rebal.drifts[3].trade, 550.0));
+        assert_eq!(rebal.untradable, vec!["type:index"]);
+        assert_eq!(rebal.trades.len(), 4);
+        assert!(rebal.unpriced.is_empty());
+    }
+
+    #[test]
+    fn test_rebalance_within_tolerance() {
+        let targets = vec![Target::new(TargetKey::Symbol(String::from("AAPL")), 45.0, 1.0)];
+        let rebal = rebalance(&test_stocks(), 1200.0, &targets, &test_prices(), false);
+        assert!(price_eql(rebal.drifts[0].trade, 0.0));
+        assert!(rebal.trades.is_empty());
+        assert!(price_eql(rebal.cash_after(), 1200.0));
+    }
+
+    #[test]
+    fn test_rebalance_cash_only() {
+        let rebal = rebalance(&test_stocks(), 550.0, &test_targets(), &test_prices(), true);
+        assert!(price_eql(rebal.drifts[0].trade, 0.0));
+        assert!(price_eql(rebal.drifts[1].trade, 0.0));
+        assert!(price_eql(rebal.drifts[2].trade, 550.0));
+
+        let trades: Vec<(&str, Quantity)> = rebal.trades.iter().map(|t| (t.symbol.as_str(), t.shares)).collect();
+        assert_eq!(trades, vec![("MSFT", 2.0)]);
+        assert!(price_eql(rebal.cash_after(), 50.0));
+    }
+
+    fn make_stock(symbol: &str, stype: StockType, quantity: Quantity, price: Price) -> Stock {
+        let date = datetime::make_date(2021, 2, 10);
+        let mut stock = Stock::new(String::from(symbol), stype, date, quantity, price);
+        stock.set_latest_price(price, date);
+        stock
+    }
+}
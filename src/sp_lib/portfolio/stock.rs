@@ -2,17 +2,19 @@ use std::fmt;
 
 use crate::util::datetime;
 use crate::util::datetime::SPDate;
-use crate::util::price_type::{PriceType, calc_daily};
+use crate::util::price_type::{PriceType, calc_daily, quantity_format};
 use crate::portfolio::stock_type::StockType;
 use crate::portfolio::symbol_trait::GetSymbol;
 
 pub type Price = PriceType;
+pub type Quantity = PriceType;
 
+#[derive(Clone)]
 pub struct Stock {
     pub symbol: String,          // Name
     pub stype: StockType,        // Stock Type
     pub date: SPDate,            // Buy Date
-    pub quantity: u32,           // Buy Quantity
+    pub quantity: Quantity,      // Buy Quantity
     pub base_price: Price,       // Buy Price
     pub cum_dividend: Price,     // Cumulative Dividend
     pub latest_price: Price,     // Latest Price
@@ -20,6 +22,9 @@ pub struct Stock {
     pub latest_div_price: Price, // Latest Dividend Price
     pub latest_div_date: SPDate, // Latest Dividend Date
     pub days_held: i64,          // Days Held
+    pub drip: bool,              // Dividend Reinvestment
+    pub currency: String,        // Currency, empty for base currency
+    pub account: String,         // Account, empty if none
 
     // For temporary use with extra sorting and other algorithms
     pub user_data: f64
@@ -31,7 +36,7 @@ impl Stock {
     pub fn new(symbol: String,
                stype: StockType,
                date: SPDate,
-               quantity: u32,
+               quantity: Quantity,
                base_price: Price) -> Stock {
         Stock {
             symbol,
@@ -45,11 +50,23 @@ impl Stock {
             latest_div_price: 0.0,
             latest_div_date: datetime::earliest_date(),
             days_held: 0,
+            drip: false,
+            currency: String::new(),
+            account: String::new(),
             user_data: 0.0
         }
     }
 
     #[inline(always)]
+    // Same buy of the same symbol in the same account, regardless of latest prices.
+    pub fn same_lot(self: &Stock, other: &Stock) -> bool {
+        self.symbol == other.symbol &&
+            self.account == other.account &&
+            self.date == other.date &&
+            self.quantity == other.quantity &&
+            self.base_price == other.base_price
+    }
+
     pub fn set_latest_price(self: &mut Stock, price: Price, date: SPDate) {
         self.latest_price = price;
         self.latest_date = date;
@@ -62,6 +79,13 @@ impl Stock {
         self.latest_div_date = date;
     }
 
+    // Apply a split with the given ratio of new shares per old share, keeping the base notional.
+    #[inline(always)]
+    pub fn apply_split(self: &mut Stock, ratio: f64) {
+        self.quantity *= ratio;
+        self.base_price /= ratio;
+    }
+
     #[inline(always)]
     pub fn net_price(self: &Stock) -> Price {
         self.latest_price - self.base_price
@@ -69,17 +93,17 @@ impl Stock {
 
     #[inline(always)]
     pub fn base_notional(self: &Stock) -> Price {
-        self.quantity as Price * self.base_price
+        self.quantity * self.base_price
     }
 
     #[inline(always)]
     pub fn latest_notional(self: &Stock) -> Price {
-        self.quantity as Price * self.latest_price
+        self.quantity * self.latest_price
     }
 
     #[inline(always)]
     pub fn net_notional(self: &Stock) -> Price {
-        self.quantity as Price * self.net_price()
+        self.quantity * self.net_price()
     }
 
     #[inline(always)]
@@ -89,7 +113,7 @@ impl Stock {
 
     #[inline(always)]
     pub fn latest_dividend(self: &Stock) -> Price {
-        self.quantity as Price * self.latest_div_price
+        self.quantity * self.latest_div_price
     }
 
     #[inline(always)]
@@ -99,8 +123,8 @@ impl Stock {
 
     #[inline(always)]
     pub fn daily_unit_dividend(self: &Stock) -> Price {
-        if self.quantity > 0 {
-            calc_daily(self.cum_dividend / self.quantity as Price, self.days_held)
+        if self.quantity > 0.0 {
+            calc_daily(self.cum_dividend / self.quantity, self.days_held)
         } else {
             0.0
         }
@@ -114,7 +138,7 @@ impl Stock {
 
 impl fmt::Display for Stock {
     fn fmt(self: &Stock, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "Stock({} {}@{:.2})", self.symbol, self.quantity, self.latest_price)
+        write!(f, "Stock({} {}@{:.2})", self.symbol, quantity_format(self.quantity), self.latest_price)
     }
 }
 
@@ -133,24 +157,40 @@ mod tests {
 
     #[test]
     fn test_stock_new() {
-        let stock = Stock::new(String::from("AAPL"), StockType::Cash, datetime::today(), 100, 120.25);
+        let stock = Stock::new(String::from("AAPL"), StockType::Cash, datetime::today(), 100.0, 120.25);
         assert_eq!(stock.symbol, "AAPL");
         assert!(stock.stype == StockType::Cash);
         assert_eq!(stock.date, datetime::today());
-        assert_eq!(stock.quantity, 100);
+        assert_eq!(stock.quantity, 100.0);
         assert_eq!(stock.base_price, 120.25);
         assert_eq!(stock.cum_dividend, 0.0);
         assert_eq!(stock.latest_price, 0.0);
         assert_eq!(stock.latest_date, datetime::earliest_date());
         assert_eq!(stock.days_held, 0);
+        assert!(!stock.drip);
+        assert_eq!(stock.currency, "");
+        assert_eq!(stock.account, "");
         assert_eq!(stock.yearly_dividend(), 0.0);
         assert_eq!(stock.daily_unit_dividend(), 0.0);
         assert_eq!(stock.cum_dividend_return(), 0.0);
     }
 
+    #[test]
+    fn test_stock_apply_split() {
+        let mut stock = Stock::new(String::from("AAPL"), StockType::Cash, datetime::today(), 100.0, 480.0);
+        stock.apply_split(4.0);
+        assert_eq!(stock.quantity, 400.0);
+        assert_eq!(stock.base_price, 120.0);
+        assert_eq!(stock.base_notional(), 48000.0);
+
+        stock.apply_split(0.5);
+        assert_eq!(stock.quantity, 200.0);
+        assert_eq!(stock.base_price, 240.0);
+    }
+
     #[test]
     fn test_stock_set_latest_price() {
-        let mut stock = Stock::new(String::from("AAPL"), StockType::Cash, datetime::today(), 100, 120.25);
+        let mut stock = Stock::new(String::from("AAPL"), StockType::Cash, datetime::today(), 100.0, 120.25);
         assert_eq!(stock.latest_price, 0.0);
         assert_eq!(stock.latest_date, datetime::earliest_date());
         assert_eq!(stock.days_held, 0);
@@ -170,7 +210,7 @@ mod tests {
 
     #[test]
     fn test_stock_latest_dividend() {
-        let mut stock = Stock::new(String::from("AAPL"), StockType::Cash, datetime::today(), 100, 120.25);
+        let mut stock = Stock::new(String::from("AAPL"), StockType::Cash, datetime::today(), 100.0, 120.25);
         assert_eq!(stock.latest_div_date, datetime::earliest_date());
         assert_eq!(stock.latest_div_price, 0.0);
         assert_eq!(stock.latest_dividend(), 0.0);
@@ -183,7 +223,7 @@ mod tests {
 
     #[test]
     fn test_stock_getters() {
-        let mut stock = Stock::new(String::from("AAPL"), StockType::Cash, datetime::today(), 100, 120.25);
+        let mut stock = Stock::new(String::from("AAPL"), StockType::Cash, datetime::today(), 100.0, 120.25);
         stock.set_latest_price(125.50, datetime::today());
 
         assert_eq!(stock.net_price(), 5.25);
@@ -195,7 +235,7 @@ mod tests {
 
     #[test]
     fn test_stock_display() {
-        let mut stock = Stock::new(String::from("AAPL"), StockType::Cash, datetime::today(), 100, 120.25);
+        let mut stock = Stock::new(String::from("AAPL"), StockType::Cash, datetime::today(), 100.0, 120.25);
         stock.set_latest_price(125.50, datetime::today());
 
         assert_eq!(format!("{}", stock), "Stock(AAPL 100@125.50)");
@@ -203,7 +243,7 @@ mod tests {
 
     #[test]
     fn test_stock_dividend_functions() {
-        let mut stock = Stock::new(String::from("AAPL"), StockType::Cash, datetime::today(), 200, 120.25);
+        let mut stock = Stock::new(String::from("AAPL"), StockType::Cash, datetime::today(), 200.0, 120.25);
         stock.set_latest_price(125.50, datetime::today_plus_days(40));
         stock.cum_dividend = 115.0;
 
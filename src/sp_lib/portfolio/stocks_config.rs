@@ -2,11 +2,25 @@ use std::io::prelude::*;
 use std::env;
 use std::fs::File;
 use std::io::BufReader;
+use std::path::{Path, PathBuf};
+use crate::util::datetime::SPDate;
 use crate::util::error::Error;
 use crate::portfolio::stock::{Price, StockList};
 use crate::portfolio::stocks_reader::StocksReader;
 use crate::portfolio::closed_position::ClosedPositionList;
 use crate::portfolio::closed_positions_reader::ClosedPositionsReader;
+use crate::portfolio::cash_flow;
+use crate::portfolio::cash_flow::CashFlowList;
+use crate::portfolio::cash_flows_reader::CashFlowsReader;
+use crate::portfolio::currency;
+use crate::portfolio::ledger;
+use crate::portfolio::lot_method::{LotMethod, str2lotmethod};
+use crate::portfolio::target::TargetList;
+use crate::portfolio::targets_reader::TargetsReader;
+use crate::portfolio::transaction::TransactionList;
+use crate::portfolio::transactions_reader::TransactionsReader;
+use crate::provider::provider_type::{ProviderType, str2providertype};
+use crate::yfinance::http::HttpConfig;
 
 #[derive(Copy, Clone, PartialEq)]
 #[allow(clippy::upper_case_acronyms)]
@@ -16,6 +30,13 @@ enum SContentType {
     CSVFile,
     CSVCP,
     CSVFileCP,
+    CSVTX,
+    CSVFileTX,
+    CSVCF,
+    CSVFileCF,
+    CSVAC,
+    CSVTG,
+    CSVFileTG,
 }
 
 pub struct StocksConfig {
@@ -23,7 +44,16 @@ pub struct StocksConfig {
     ds_name: String,
     stocks: StockList,
     closed_positions: ClosedPositionList,
-    cash: Price
+    transactions: TransactionList,
+    lot_method: LotMethod,
+    cash: Price,
+    cash_flows: CashFlowList,
+    holding_cash_flows: CashFlowList,
+    targets: TargetList,
+    base_currency: String,
+    provider: ProviderType,
+    provider_root: String,
+    http: HttpConfig
 }
 
 impl StocksConfig {
@@ -33,27 +63,26 @@ impl StocksConfig {
             ds_name: String::new(),
             stocks: StockList::new(),
             closed_positions: ClosedPositionList::new(),
-            cash: 0.0
+            transactions: TransactionList::new(),
+            lot_method: LotMethod::Fifo,
+            cash: 0.0,
+            cash_flows: CashFlowList::new(),
+            holding_cash_flows: CashFlowList::new(),
+            targets: TargetList::new(),
+            base_currency: String::from(currency::DEFAULT_BASE_CURRENCY),
+            provider: ProviderType::Yahoo,
+            provider_root: String::new(),
+            http: HttpConfig::new()
         }
     }
 
     pub fn from_file(config_file: &str) -> Result<Self, Error> {
-        match File::open(config_file) {
-            Ok(file) => {
-                let mut reader = BufReader::new(file);
-                let mut content = String::new();
-                match reader.read_to_string(&mut content) {
-                    Ok(_) => Self::parse(&content),
-                    Err(e) => Err(format!("config::read - {}", e).into())
-                }
-            },
-            Err(e) => Err(format!("StocksConfig::read - {}", e).into())
-        }
+        Self::read(Path::new(config_file), &mut Vec::new())
     }
 
     #[allow(clippy::should_implement_trait)]
     pub fn from_str(config_str: &str) -> Result<Self, Error> {
-        Self::parse(config_str)
+        Self::parse(config_str, Path::new(""), &mut Vec::new())
     }
 
     #[inline(always)] pub fn ds_root(&self) -> &str { &self.ds_root }
@@ -65,17 +94,88 @@ impl StocksConfig {
     #[inline(always)] pub fn closed_positions(&self) -> &ClosedPositionList { &self.closed_positions }
     #[inline(always)] pub fn closed_positions_mut(&mut self) -> &mut ClosedPositionList { &mut self.closed_positions }
 
+    #[inline(always)] pub fn transactions(&self) -> &TransactionList { &self.transactions }
+    #[inline(always)] pub fn lot_method(&self) -> LotMethod { self.lot_method }
+
     #[inline(always)] pub fn cash(&self) -> Price { self.cash }
+    #[inline(always)] pub fn cash_flows(&self) -> &CashFlowList { &self.cash_flows }
+    #[inline(always)] pub fn cash_flows_mut(&mut self) -> &mut CashFlowList { &mut self.cash_flows }
+
+    // Proceeds and dividends of holdings whose purchases are not in the cash flows. They are left out
+    // of the cash balance and only used by the value series, where the holdings purchases are modeled.
+    #[inline(always)] pub fn holding_cash_flows(&self) -> &CashFlowList { &self.holding_cash_flows }
+    #[inline(always)] pub fn holding_cash_flows_mut(&mut self) -> &mut CashFlowList { &mut self.holding_cash_flows }
+
+    // Cash flows and holding cash flows sorted by date
+    pub fn series_cash_flows(&self) -> CashFlowList {
+        let mut flows: CashFlowList = self.cash_flows.iter().chain(self.holding_cash_flows.iter()).cloned().collect();
+        cash_flow::sort_cash_flows(&mut flows);
+        flows
+    }
+
+    // Opening cash plus the net of cash flows on or before the given date
+    pub fn cash_balance(&self, date: &SPDate) -> Price {
+        self.cash + cash_flow::cash_balance(&self.cash_flows, date).to_float()
+    }
+
+    #[inline(always)] pub fn targets(&self) -> &TargetList { &self.targets }
+
+    #[inline(always)] pub fn base_currency(&self) -> &str { &self.base_currency }
+
+    #[inline(always)] pub fn provider(&self) -> ProviderType { self.provider }
+    #[inline(always)] pub fn provider_root(&self) -> &str { &self.provider_root }
+
+    #[inline(always)] pub fn http_config(&self) -> &HttpConfig { &self.http }
 
     // --------------------------------------------------------------------------------
     // Private Helpers
 
-    fn parse(content: &str) -> Result<Self, Error> {
+    // Path of the CSV file named in a csv_file block, relative paths are joined to dir.
+    fn csv_file_path(dir: &Path, scontent: &str) -> String {
+        dir.join(scontent.trim()).to_string_lossy().into_owned()
+    }
+
+    // Includes holds the canonical paths of the config files being read, from the top config down to this one.
+    fn read(path: &Path, includes: &mut Vec<PathBuf>) -> Result<Self, Error> {
+        let canonical = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+        if includes.contains(&canonical) {
+            return Err(format!("StocksConfig::parse - Circular account include '{}'", path.display()).into());
+        }
+
+        match File::open(path) {
+            Ok(file) => {
+                let mut reader = BufReader::new(file);
+                let mut content = String::new();
+                match reader.read_to_string(&mut content) {
+                    Ok(_) => {
+                        includes.push(canonical);
+                        let config = Self::parse(&content, path.parent().unwrap_or(Path::new("")), includes);
+                        includes.pop();
+                        config
+                    },
+                    Err(e) => Err(format!("config::read - {}", e).into())
+                }
+            },
+            Err(e) => Err(format!("StocksConfig::read - {}", e).into())
+        }
+    }
+
+    // Relative account config files and CSV files are read from dir, the directory of the including config.
+    fn parse(content: &str, dir: &Path, includes: &mut Vec<PathBuf>) -> Result<Self, Error> {
         let mut root: String = env::var("HOME")?;
         let mut name: String = String::from("sp_datastore");
         let mut stocks: Option<StockList> = None;
         let mut closed_positions: Option<ClosedPositionList> = None;
+        let mut transactions: Option<TransactionList> = None;
+        let mut cash_flows: Option<CashFlowList> = None;
+        let mut accounts: Vec<(String, String)> = Vec::new();
+        let mut targets: Option<TargetList> = None;
+        let mut lot_method = LotMethod::Fifo;
         let mut cash: Price = 0.0;
+        let mut base_currency = String::from(currency::DEFAULT_BASE_CURRENCY);
+        let mut provider = ProviderType::Yahoo;
+        let mut provider_root = String::new();
+        let mut http = HttpConfig::new();
 
         let mut collect_scontent = false;
         let mut scontent_type = SContentType::None;
@@ -87,9 +187,16 @@ impl StocksConfig {
             if line.trim() == "}" {
                 match scontent_type {
                     SContentType::CSV => stocks = Some(StocksReader::parse_content(&scontent)?),
-                    SContentType::CSVFile => stocks = Some(StocksReader::new(scontent.trim().to_string()).read()?),
+                    SContentType::CSVFile => stocks = Some(StocksReader::new(Self::csv_file_path(dir, &scontent)).read()?),
                     SContentType::CSVCP => closed_positions = Some(ClosedPositionsReader::parse_content(&scontent)?),
-                    SContentType::CSVFileCP => closed_positions = Some(ClosedPositionsReader::new(scontent.trim().to_string()).read()?),
+                    SContentType::CSVFileCP => closed_positions = Some(ClosedPositionsReader::new(Self::csv_file_path(dir, &scontent)).read()?),
+                    SContentType::CSVTX => transactions = Some(TransactionsReader::parse_content(&scontent)?),
+                    SContentType::CSVFileTX => transactions = Some(TransactionsReader::new(Self::csv_file_path(dir, &scontent)).read()?),
+                    SContentType::CSVCF => cash_flows = Some(CashFlowsReader::parse_content(&scontent)?),
+                    SContentType::CSVFileCF => cash_flows = Some(CashFlowsReader::new(Self::csv_file_path(dir, &scontent)).read()?),
+                    SContentType::CSVAC => accounts = Self::parse_accounts(&scontent)?,
+                    SContentType::CSVTG => targets = Some(TargetsReader::parse_content(&scontent)?),
+                    SContentType::CSVFileTG => targets = Some(TargetsReader::new(Self::csv_file_path(dir, &scontent)).read()?),
                     SContentType::None => return Err("StocksConfig::parse - Unexpected scontent type None".into())
                 };
                 collect_scontent = false;
@@ -104,10 +211,15 @@ impl StocksConfig {
                 continue;
             }
 
-            let tokens: Vec<&str> = line
+            let mut tokens: Vec<&str> = line
                 .split(':')
                 .map(|t| t.trim())
                 .collect();
+            if tokens.len() > 2 && Self::allows_colon(tokens[0]) {
+                // Values such as proxy URLs may contain ':'
+                let pos = line.find(':').unwrap_or(0);
+                tokens = vec![tokens[0], line[pos + 1..].trim()];
+            }
             if tokens.len() != 2 {
                 return Err(format!("StocksConfig::parse - Invalid line '{}'", line).into());
             }
@@ -122,6 +234,22 @@ impl StocksConfig {
                         Err(e) => return Err(format!("StocksConfig::parse - {}", e).into())
                     };
                 },
+                "base_currency" => base_currency = currency::parse_currency(value)?,
+                "lot_method" => lot_method = str2lotmethod(value)?,
+                "provider" => {
+                    match str2providertype(value) {
+                        Ok(v) => provider = v,
+                        Err(e) => return Err(format!("StocksConfig::parse - {}", e).into())
+                    };
+                },
+                "provider_root" => provider_root = String::from(value),
+                "http_connect_timeout" => http = http.with_connect_timeout(Self::parse_number(tokens[0], value)?),
+                "http_timeout" => http = http.with_timeout(Self::parse_number(tokens[0], value)?),
+                "http_retries" => http = http.with_retries(Self::parse_number(tokens[0], value)?),
+                "http_backoff" => http = http.with_backoff(Self::parse_number(tokens[0], value)?),
+                "http_rate_limit" => http = http.with_rate_limit(Self::parse_number(tokens[0], value)?),
+                "http_proxy" => http = http.with_proxy(value),
+                "http_user_agent" => http = http.with_user_agent(value),
                 "stocks" => {
                     collect_scontent = true;
                     match value {
@@ -138,20 +266,126 @@ impl StocksConfig {
                         _ => return Err(format!("StocksConfig::parse - Unsupported block type '{}'", value).into())
                     };
                 },
+                "transactions" => {
+                    collect_scontent = true;
+                    match value {
+                        "csv{" => scontent_type = SContentType::CSVTX,
+                        "csv_file{" => scontent_type = SContentType::CSVFileTX,
+                        _ => return Err(format!("StocksConfig::parse - Unsupported block type '{}'", value).into())
+                    };
+                },
+                "cash_flows" => {
+                    collect_scontent = true;
+                    match value {
+                        "csv{" => scontent_type = SContentType::CSVCF,
+                        "csv_file{" => scontent_type = SContentType::CSVFileCF,
+                        _ => return Err(format!("StocksConfig::parse - Unsupported block type '{}'", value).into())
+                    };
+                },
+                "targets" => {
+                    collect_scontent = true;
+                    match value {
+                        "csv{" => scontent_type = SContentType::CSVTG,
+                        "csv_file{" => scontent_type = SContentType::CSVFileTG,
+                        _ => return Err(format!("StocksConfig::parse - Unsupported block type '{}'", value).into())
+                    };
+                },
+                "accounts" => {
+                    collect_scontent = true;
+                    match value {
+                        "csv{" => scontent_type = SContentType::CSVAC,
+                        _ => return Err(format!("StocksConfig::parse - Unsupported block type '{}'", value).into())
+                    };
+                },
                 _ => {
                     return Err(format!("StocksConfig::parse - Unknown config name '{}'", tokens[0]).into());
                 }
             };
         }
 
+        // Proceeds and dividends of closed positions listed explicitly are holding cash flows
+        let mut stocks = stocks.unwrap_or_default();
+        let mut closed_positions = closed_positions.unwrap_or_default();
+        let mut cash_flows = cash_flows.unwrap_or_default();
+        let mut holding_cash_flows = cash_flow::position_cash_flows(&closed_positions);
+
+        // Open lots and closed positions derived from transactions are added to any listed explicitly
+        let mut transactions = transactions.unwrap_or_default();
+        if !transactions.is_empty() {
+            let (tx_stocks, tx_positions) = ledger::apply_transactions(&transactions, lot_method)?;
+            stocks.extend(tx_stocks);
+            closed_positions.extend(tx_positions);
+        }
+
+        // Purchases and proceeds of transactions are added to the cash flows
+        cash_flows.extend(cash_flow::transaction_cash_flows(&transactions));
+
+        // Lots, positions, cash and cash flows of included account configs are added to this one
+        for (account, config_file) in accounts.iter() {
+            let mut config = Self::read(&dir.join(config_file), includes)?;
+            for stock in config.stocks.iter_mut().filter(|stock| stock.account.is_empty()) {
+                stock.account = account.clone();
+            }
+            for position in config.closed_positions.iter_mut().filter(|position| position.account.is_empty()) {
+                position.account = account.clone();
+            }
+            stocks.append(&mut config.stocks);
+            closed_positions.append(&mut config.closed_positions);
+            transactions.append(&mut config.transactions);
+            cash_flows.append(&mut config.cash_flows);
+            holding_cash_flows.append(&mut config.holding_cash_flows);
+            cash += config.cash;
+        }
+        cash_flow::sort_cash_flows(&mut cash_flows);
+        cash_flow::sort_cash_flows(&mut holding_cash_flows);
+
         Ok(StocksConfig {
             ds_root: root,
             ds_name: name,
-            stocks: stocks.unwrap_or_default(),
-            closed_positions: closed_positions.unwrap_or_default(),
-            cash: cash
+            stocks,
+            closed_positions,
+            transactions,
+            lot_method,
+            cash: cash,
+            cash_flows,
+            holding_cash_flows,
+            targets: targets.unwrap_or_default(),
+            base_currency,
+            provider,
+            provider_root,
+            http
         })
     }
+
+    // Columns: account,config_file
+    fn parse_accounts(content: &str) -> Result<Vec<(String, String)>, Error> {
+        let mut accounts: Vec<(String, String)> = Vec::new();
+        // Assume first line is a header and skip it.
+        for account_line in content.lines().skip(1) {
+            if account_line.is_empty() {
+                continue;
+            }
+
+            let account_tokens: Vec<&str> = account_line.split(',').map(|t| t.trim()).collect();
+            if account_tokens.len() != 2 || account_tokens[0].is_empty() || account_tokens[1].is_empty() {
+                return Err(format!("StocksConfig::parse - Invalid account line '{}'", account_line).into());
+            }
+            accounts.push((String::from(account_tokens[0]), String::from(account_tokens[1])));
+        }
+        Ok(accounts)
+    }
+
+    #[inline(always)]
+    fn allows_colon(name: &str) -> bool {
+        name == "http_proxy" || name == "http_user_agent"
+    }
+
+    fn parse_number<T: std::str::FromStr>(name: &str, value: &str) -> Result<T, Error> {
+        match value.parse::<T>() {
+            Ok(v) => Ok(v),
+            Err(_) => Err(format!("StocksConfig::parse - Invalid {} value '{}'", name, value).into())
+        }
+    }
 }
 
 impl Default for StocksConfig {
@@ -1,7 +1,8 @@
 use std::collections::HashSet;
+use crate::util::datetime::{self, SPDate};
 use crate::util::error::Error;
 use crate::portfolio::stock::{Stock, StockList};
-use crate::portfolio::stock_type;
+use crate::portfolio::stock_type::{self, StockType};
 
 // --------------------------------------------------------------------------------
 // StocksFilter
@@ -27,16 +28,21 @@ impl StocksFilter {
         self.func.filter_stocks(stocks, keep)
     }
 
+    // A single type name, a list of symbols, or account: followed by a list of accounts are
+    // shorthands; anything else is parsed as an expression.
     fn make_filter_func(filter_str: &str) -> Result<FilterFtnPtr, Error> {
         let fstr = filter_str.trim();
-        if let Ok(stype) = stock_type::str2stocktype(fstr) {
-            Ok(Box::new(TypeFilter::make(stype)))
+        if let Some(accounts) = fstr.strip_prefix("account:") {
+            return Ok(Box::new(AccountFilter::make(accounts)))
         }
-        else if fstr.contains(',') || !fstr.contains(' ') {
-            Ok(Box::new(SymbolsFilter::make(filter_str)))
-        }
-        else {
-            Ok(Box::new(ExprFilter::make(filter_str)?))
+
+        let tokens = tokenize(fstr)?;
+        match name_list(&tokens) {
+            Some(names) => match stock_type::str2stocktype(&names[0]) {
+                Ok(stype) if names.len() == 1 => Ok(Box::new(TypeFilter::make(stype))),
+                _ => Ok(Box::new(SymbolsFilter::make(names)))
+            },
+            None => Ok(Box::new(ExprFilter::make(fstr, tokens)?))
         }
     }
 }
@@ -45,11 +51,11 @@ impl StocksFilter {
 // Type Filter
 
 struct TypeFilter {
-    stype: stock_type::StockType
+    stype: StockType
 }
 
 impl TypeFilter {
-    pub fn make(stype: stock_type::StockType) -> Self {
+    pub fn make(stype: StockType) -> Self {
         TypeFilter {
             stype
         }
@@ -70,9 +76,9 @@ struct SymbolsFilter {
 }
 
 impl SymbolsFilter {
-    pub fn make(filter_str: &str) -> Self {
+    pub fn make(symbols: Vec<String>) -> Self {
         SymbolsFilter {
-            symbols: filter_str.split(',').map(|name| String::from(name.trim())).collect()
+            symbols: symbols.into_iter().collect()
         }
     }
 }
@@ -83,77 +89,379 @@ impl FilterFtn for SymbolsFilter {
     }
 }
 
+// --------------------------------------------------------------------------------
+// Account Filter
+
+struct AccountFilter {
+    accounts: HashSet<String>
+}
+
+impl AccountFilter {
+    pub fn make(accounts_str: &str) -> Self {
+        AccountFilter {
+            accounts: accounts_str.split(',').map(|name| String::from(name.trim())).collect()
+        }
+    }
+}
+
+impl FilterFtn for AccountFilter {
+    fn filter_stocks(&self, stocks: &mut StockList, keep: bool) {
+        stocks.retain(|stock| self.accounts.contains(stock.account.as_str()) == keep);
+    }
+}
+
 // --------------------------------------------------------------------------------
 // Expression Filter
 
 struct ExprFilter {
-    field_ftn: fn(&Stock) -> f64,
-    op_ftn: fn(f64, f64) -> bool,
-    value: f64
+    expr: FilterExpr
 }
 
-pub type ExprFieldFtn = fn(&Stock) -> f64;
-pub type ExprOpFtn = fn(f64, f64) -> bool;
-
 impl ExprFilter {
-    pub fn make(filter_expr: &str) -> Result<Self, Error> {
-        let tokens: Vec<&str> = filter_expr.split_whitespace().collect();
-        if tokens.len() != 3 {
-            return Err(format!("Invalid by expression '{}'", filter_expr).into())
+    pub fn make(filter_expr: &str, tokens: Vec<Token>) -> Result<Self, Error> {
+        Ok(ExprFilter {
+            expr: ExprParser::parse(filter_expr, tokens)?
+        })
+    }
+}
+
+impl FilterFtn for ExprFilter {
+    fn filter_stocks(&self, stocks: &mut StockList, keep: bool) {
+        stocks.retain(|stock| self.expr.matches(stock) == keep);
+    }
+}
+
+enum FilterExpr {
+    Compare(Comparison),
+    Not(Box<FilterExpr>),
+    And(Box<FilterExpr>, Box<FilterExpr>),
+    Or(Box<FilterExpr>, Box<FilterExpr>)
+}
+
+impl FilterExpr {
+    fn matches(&self, stock: &Stock) -> bool {
+        match self {
+            FilterExpr::Compare(cmp) => cmp.matches(stock),
+            FilterExpr::Not(expr) => !expr.matches(stock),
+            FilterExpr::And(lhs, rhs) => lhs.matches(stock) && rhs.matches(stock),
+            FilterExpr::Or(lhs, rhs) => lhs.matches(stock) || rhs.matches(stock)
         }
+    }
+}
 
-        let field_ftn = Self::make_field_ftn(tokens[0])?;
-        let op_ftn = Self::make_op_ftn(tokens[1])?;
-        let value = tokens[2].parse::<f64>()?;
+#[derive(Debug, Copy, Clone, PartialEq)]
+enum CompareOp {
+    Eq,
+    Ne,
+    Lt,
+    Gt,
+    Le,
+    Ge
+}
 
-        Ok(ExprFilter {
-            field_ftn,
-            op_ftn,
-            value
-        })
+impl CompareOp {
+    fn compare<T: PartialOrd>(self, lhs: T, rhs: T) -> bool {
+        match self {
+            CompareOp::Eq => lhs == rhs,
+            CompareOp::Ne => lhs != rhs,
+            CompareOp::Lt => lhs < rhs,
+            CompareOp::Gt => lhs > rhs,
+            CompareOp::Le => lhs <= rhs,
+            CompareOp::Ge => lhs >= rhs
+        }
     }
+}
+
+enum Comparison {
+    Number(fn(&Stock) -> f64, CompareOp, f64),
+    Text(fn(&Stock) -> &str, CompareOp, String),
+    Type(CompareOp, StockType),
+    Date(fn(&Stock) -> SPDate, CompareOp, SPDate)
+}
 
-    fn make_field_ftn(field: &str) -> Result<ExprFieldFtn, Error> {
-        if field == "days" {
-            Ok(|stock| stock.days_held as f64)
-        } else if field == "price" {
-            Ok(|stock| stock.latest_price)
-        } else if field == "net" {
-            Ok(|stock| stock.net_price())
-        } else if field == "pct" {
-            Ok(|stock| stock.pct_change())
-        } else if field == "div" {
-            Ok(|stock| stock.cum_dividend)
-        } else if field == "size" {
-            Ok(|stock| stock.quantity as f64)
-        } else if field == "value" {
-            Ok(|stock| stock.latest_notional())
-        } else {
-            Err(format!("Unsupported filter expression field '{}'", field).into())
-        }
-    }
-
-    fn make_op_ftn(op: &str) -> Result<ExprOpFtn, Error> {
-        if op == "=" {
-            Ok(|l, r| l == r)
-        } else if op == "!=" {
-            Ok(|l, r| l != r)
-        } else if op == "<" {
-            Ok(|l, r| l < r)
-        } else if op == ">" {
-            Ok(|l, r| l > r)
-        } else if op == "<=" {
-            Ok(|l, r| l <= r)
-        } else if op == ">=" {
-            Ok(|l, r| l >= r)
-        } else {
-            Err(format!("Unsupported filter expression op '{}'", op).into())
+impl Comparison {
+    fn matches(&self, stock: &Stock) -> bool {
+        match self {
+            Comparison::Number(field, op, value) => op.compare(field(stock), *value),
+            Comparison::Text(field, op, value) => op.compare(field(stock), value.as_str()),
+            Comparison::Type(op, stype) => op.compare(stock.stype, *stype),
+            Comparison::Date(field, op, date) => op.compare(field(stock), *date)
         }
     }
 }
 
-impl FilterFtn for ExprFilter {
-    fn filter_stocks(&self, stocks: &mut StockList, keep: bool) {
-        stocks.retain(|stock| (self.op_ftn)((self.field_ftn)(stock), self.value) == keep);
+// Expression fields, the same as the sort stocks order by fields.
+enum Field {
+    Number(fn(&Stock) -> f64),
+    Text(fn(&Stock) -> &str),
+    Type,
+    Date(fn(&Stock) -> SPDate)
+}
+
+fn make_field(name: &str) -> Option<Field> {
+    match name {
+        "symbol" => Some(Field::Text(|stock| stock.symbol.as_str())),
+        "account" => Some(Field::Text(|stock| stock.account.as_str())),
+        "type" => Some(Field::Type),
+        "date" => Some(Field::Date(|stock| stock.date)),
+        "laddt" => Some(Field::Date(|stock| stock.latest_div_date)),
+        "days" => Some(Field::Number(|stock| stock.days_held as f64)),
+        "price" => Some(Field::Number(|stock| stock.latest_price)),
+        "net" => Some(Field::Number(|stock| stock.net_price())),
+        "pct" => Some(Field::Number(|stock| stock.pct_change())),
+        "size" => Some(Field::Number(|stock| stock.quantity)),
+        "value" => Some(Field::Number(|stock| stock.latest_notional())),
+        "div" => Some(Field::Number(|stock| stock.cum_dividend)),
+        "ladiv" => Some(Field::Number(|stock| stock.latest_dividend())),
+        "yrdiv" => Some(Field::Number(|stock| stock.yearly_dividend())),
+        "dudiv" => Some(Field::Number(|stock| stock.daily_unit_dividend())),
+        "divret" => Some(Field::Number(|stock| stock.cum_dividend_return())),
+        _ => None
+    }
+}
+
+// --------------------------------------------------------------------------------
+// Expression Tokens
+
+const KEYWORDS: [&str; 3] = ["and", "or", "not"];
+
+#[derive(Debug, Clone, PartialEq)]
+enum TokenKind {
+    Word,
+    Quoted,
+    Op(CompareOp),
+    LParen,
+    RParen,
+    Comma
+}
+
+#[derive(Debug, Clone)]
+struct Token {
+    kind: TokenKind,
+    text: String,   // Token text, without quotes if quoted
+    pos: usize      // One based character position in the expression
+}
+
+impl Token {
+    #[inline(always)]
+    fn is_keyword(&self, keyword: &str) -> bool {
+        self.kind == TokenKind::Word && self.text == keyword
+    }
+}
+
+#[inline(always)]
+fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '.' || c == '-' || c == '_' || c == '^'
+}
+
+fn tokenize(expr: &str) -> Result<Vec<Token>, Error> {
+    let chars: Vec<char> = expr.chars().collect();
+    let mut tokens: Vec<Token> = Vec::new();
+
+    let mut start = 0;
+    while start < chars.len() {
+        let c = chars[start];
+        let next = chars.get(start + 1).copied();
+        let pos = start + 1;
+
+        let (kind, end) = match c {
+            c if c.is_whitespace() => {
+                start += 1;
+                continue;
+            },
+            '(' => (TokenKind::LParen, start + 1),
+            ')' => (TokenKind::RParen, start + 1),
+            ',' => (TokenKind::Comma, start + 1),
+            '=' if next == Some('=') => (TokenKind::Op(CompareOp::Eq), start + 2),
+            '=' => (TokenKind::Op(CompareOp::Eq), start + 1),
+            '!' if next == Some('=') => (TokenKind::Op(CompareOp::Ne), start + 2),
+            '<' if next == Some('=') => (TokenKind::Op(CompareOp::Le), start + 2),
+            '<' => (TokenKind::Op(CompareOp::Lt), start + 1),
+            '>' if next == Some('=') => (TokenKind::Op(CompareOp::Ge), start + 2),
+            '>' => (TokenKind::Op(CompareOp::Gt), start + 1),
+            '\'' | '"' => match chars[start + 1..].iter().position(|qc| *qc == c) {
+                Some(len) => {
+                    tokens.push(Token { kind: TokenKind::Quoted, text: chars[start + 1..start + 1 + len].iter().collect(), pos });
+                    start += len + 2;
+                    continue;
+                },
+                None => return Err(expr_error(expr, Some(pos), "Unterminated string"))
+            },
+            c if is_word_char(c) => (TokenKind::Word, word_end(&chars, start)),
+            c => return Err(expr_error(expr, Some(pos), &format!("Unexpected character '{}'", c)))
+        };
+
+        tokens.push(Token { kind, text: chars[start..end].iter().collect(), pos });
+        start = end;
+    }
+
+    Ok(tokens)
+}
+
+// End of the word starting at start. An '=' between word characters is part of the word, as in the
+// symbol EURUSD=X, unless the word so far is a field name, as in days=3.
+fn word_end(chars: &[char], start: usize) -> usize {
+    let mut end = start;
+    loop {
+        end += chars[end..].iter().position(|wc| !is_word_char(*wc)).unwrap_or(chars.len() - end);
+        let in_word = chars.get(end) == Some(&'=') && chars.get(end + 1).is_some_and(|nc| is_word_char(*nc));
+        if !in_word || make_field(&chars[start..end].iter().collect::<String>()).is_some() {
+            return end
+        }
+        end += 1;
+    }
+}
+
+// Names of a comma separated list of words or quoted strings.
+fn name_list(tokens: &[Token]) -> Option<Vec<String>> {
+    if tokens.last().is_none_or(|token| token.kind == TokenKind::Comma) {
+        return None
+    }
+
+    let names_ok = tokens.iter().step_by(2).all(|token| token.kind == TokenKind::Word || token.kind == TokenKind::Quoted);
+    let commas_ok = tokens.iter().skip(1).step_by(2).all(|token| token.kind == TokenKind::Comma);
+    if names_ok && commas_ok {
+        Some(tokens.iter().step_by(2).map(|token| token.text.clone()).collect())
+    }
+    else {
+        None
+    }
+}
+
+fn expr_error(expr: &str, pos: Option<usize>, msg: &str) -> Error {
+    match pos {
+        Some(pos) => format!("{} at position {} in filter expression '{}'", msg, pos, expr).into(),
+        None => format!("{} at end of filter expression '{}'", msg, expr).into()
+    }
+}
+
+// --------------------------------------------------------------------------------
+// Expression Parser
+
+// Grammar, with not binding tighter than and, and and tighter than or:
+//   or_expr    := and_expr ('or' and_expr)*
+//   and_expr   := not_expr ('and' not_expr)*
+//   not_expr   := 'not' not_expr | primary
+//   primary    := '(' or_expr ')' | comparison
+//   comparison := field op value
+struct ExprParser<'a> {
+    expr: &'a str,
+    tokens: Vec<Token>,
+    next: usize
+}
+
+impl<'a> ExprParser<'a> {
+    fn parse(expr: &'a str, tokens: Vec<Token>) -> Result<FilterExpr, Error> {
+        let mut parser = ExprParser {
+            expr,
+            tokens,
+            next: 0
+        };
+
+        let filter = parser.parse_or()?;
+        match parser.advance() {
+            Some(token) => Err(parser.error(Some(token.pos), &format!("Unexpected '{}'", token.text))),
+            None => Ok(filter)
+        }
+    }
+
+    fn parse_or(&mut self) -> Result<FilterExpr, Error> {
+        let mut lhs = self.parse_and()?;
+        while self.next_is_keyword("or") {
+            self.next += 1;
+            let rhs = self.parse_and()?;
+            lhs = FilterExpr::Or(Box::new(lhs), Box::new(rhs));
+        }
+        Ok(lhs)
+    }
+
+    fn parse_and(&mut self) -> Result<FilterExpr, Error> {
+        let mut lhs = self.parse_not()?;
+        while self.next_is_keyword("and") {
+            self.next += 1;
+            let rhs = self.parse_not()?;
+            lhs = FilterExpr::And(Box::new(lhs), Box::new(rhs));
+        }
+        Ok(lhs)
+    }
+
+    fn parse_not(&mut self) -> Result<FilterExpr, Error> {
+        if self.next_is_keyword("not") {
+            self.next += 1;
+            Ok(FilterExpr::Not(Box::new(self.parse_not()?)))
+        }
+        else {
+            self.parse_primary()
+        }
+    }
+
+    fn parse_primary(&mut self) -> Result<FilterExpr, Error> {
+        match self.advance() {
+            Some(Token { kind: TokenKind::LParen, pos, .. }) => {
+                let filter = self.parse_or()?;
+                match self.advance() {
+                    Some(Token { kind: TokenKind::RParen, .. }) => Ok(filter),
+                    Some(token) => Err(self.error(Some(token.pos), &format!("Expected ')', found '{}'", token.text))),
+                    None => Err(self.error(Some(pos), "Unclosed '('"))
+                }
+            },
+            Some(token) if token.kind == TokenKind::Word && !KEYWORDS.contains(&token.text.as_str()) => self.parse_comparison(token),
+            Some(token) => Err(self.error(Some(token.pos), &format!("Expected field, found '{}'", token.text))),
+            None => Err(self.error(None, "Expected field"))
+        }
+    }
+
+    fn parse_comparison(&mut self, field_token: Token) -> Result<FilterExpr, Error> {
+        let field = match make_field(&field_token.text) {
+            Some(field) => field,
+            None => return Err(self.error(Some(field_token.pos), &format!("Unsupported field '{}'", field_token.text)))
+        };
+
+        let op = match self.advance() {
+            Some(Token { kind: TokenKind::Op(op), .. }) => op,
+            Some(token) => return Err(self.error(Some(token.pos), &format!("Expected operator, found '{}'", token.text))),
+            None => return Err(self.error(None, "Expected operator"))
+        };
+
+        let value = match self.advance() {
+            Some(token) if token.kind == TokenKind::Word || token.kind == TokenKind::Quoted => token,
+            Some(token) => return Err(self.error(Some(token.pos), &format!("Expected value, found '{}'", token.text))),
+            None => return Err(self.error(None, "Expected value"))
+        };
+
+        let cmp = match field {
+            Field::Number(ftn) => match value.text.parse::<f64>() {
+                Ok(number) => Comparison::Number(ftn, op, number),
+                Err(_) => return Err(self.error(Some(value.pos), &format!("Invalid number '{}'", value.text)))
+            },
+            Field::Text(ftn) => Comparison::Text(ftn, op, value.text),
+            Field::Type => match stock_type::str2stocktype(&value.text) {
+                Ok(stype) => Comparison::Type(op, stype),
+                Err(_) => return Err(self.error(Some(value.pos), &format!("Invalid stock type '{}'", value.text)))
+            },
+            Field::Date(ftn) => match datetime::parse_date(&value.text) {
+                Ok(date) => Comparison::Date(ftn, op, date),
+                Err(_) => return Err(self.error(Some(value.pos), &format!("Invalid date '{}'", value.text)))
+            }
+        };
+        Ok(FilterExpr::Compare(cmp))
+    }
+
+    fn advance(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.next).cloned();
+        if token.is_some() {
+            self.next += 1;
+        }
+        token
+    }
+
+    #[inline(always)]
+    fn next_is_keyword(&self, keyword: &str) -> bool {
+        self.tokens.get(self.next).is_some_and(|token| token.is_keyword(keyword))
+    }
+
+    #[inline(always)]
+    fn error(&self, pos: Option<usize>, msg: &str) -> Error {
+        expr_error(self.expr, pos, msg)
     }
 }
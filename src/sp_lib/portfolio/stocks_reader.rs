@@ -4,8 +4,9 @@ use std::io::BufReader;
 
 use crate::util::datetime;
 use crate::util::error::Error;
+use crate::portfolio::currency;
 use crate::portfolio::stock_type;
-use crate::portfolio::stock::{Price, Stock, StockList};
+use crate::portfolio::stock::{Price, Quantity, Stock, StockList};
 
 pub struct StocksReader {
     stocks_file: String
@@ -48,7 +49,7 @@ impl StocksReader {
             }
 
             let stock_tokens: Vec<&str> = stock_line.split(',').collect();
-            if stock_tokens.len() != 5 {
+            if stock_tokens.len() < 5 || stock_tokens.len() > 8 {
                 return Err(format!("StocksReader::parse_content - Invalid stock line '{}'", stock_line).into())
             }
 
@@ -56,8 +57,9 @@ impl StocksReader {
             let stype = stock_type::str2stocktype(stock_tokens[1])?;
             let date = datetime::parse_date(stock_tokens[2])?;
 
-            let quantity = match stock_tokens[3].parse::<u32>() {
-                Ok(qty) => qty,
+            let quantity = match stock_tokens[3].parse::<Quantity>() {
+                Ok(qty) if qty.is_finite() && qty >= 0.0 => qty,
+                Ok(_) => return Err(format!("StocksReader::parse_content - Invalid quantity '{}'", stock_tokens[3]).into()),
                 Err(e) => return Err(format!("StocksReader::parse_content - Invalid quantity '{}'", e).into())
             };
 
@@ -66,7 +68,21 @@ impl StocksReader {
                 Err(e) => return Err(format!("StocksReader::parse_content - Invalid base_price '{}'", e).into())
             };
 
-            stocks.push(Stock::new(symbol, stype, date, quantity, base_price));
+            let mut stock = Stock::new(symbol, stype, date, quantity, base_price);
+            if stock_tokens.len() >= 6 && !stock_tokens[5].is_empty() {
+                stock.drip = match stock_tokens[5].to_lowercase().parse::<bool>() {
+                    Ok(drip) => drip,
+                    Err(_) => return Err(format!("StocksReader::parse_content - Invalid drip '{}'", stock_tokens[5]).into())
+                };
+            }
+            if stock_tokens.len() >= 7 && !stock_tokens[6].is_empty() {
+                stock.currency = currency::parse_currency(stock_tokens[6])?;
+            }
+            if stock_tokens.len() == 8 {
+                stock.account = String::from(stock_tokens[7].trim());
+            }
+
+            stocks.push(stock);
         }
 
         Ok(stocks)
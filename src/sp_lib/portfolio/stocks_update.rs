@@ -1,11 +1,13 @@
 use crate::util::datetime;
 use crate::util::error::Error;
-use crate::portfolio::stock::{Price, Stock, StockList};
-use crate::yfinance::query::HistoryQuery;
-use crate::yfinance::types::{Interval, Events};
+use crate::portfolio::stock::{Stock, StockList};
+use crate::provider::provider_trait::MarketDataProvider;
 use crate::datastore::datastore::DataStore;
 use crate::datastore::history::History;
 use crate::datastore::dividends;
+use crate::datastore::dividends::Dividends;
+use crate::datastore::splits;
+use crate::datastore::splits::Splits;
 
 pub fn update_stock_from_csv(stock: &mut Stock, csv: &str) -> Result<bool, Error> {
     let hist = History::parse_csv(&stock.symbol, csv)?;
@@ -19,7 +21,9 @@ pub fn update_stock_from_csv(stock: &mut Stock, csv: &str) -> Result<bool, Error
     Ok(false)
 }
 
-pub fn update_stock(stock: &mut Stock, opt_day: Option<datetime::SPDate>) -> Result<bool, Error> {
+pub fn update_stock(stock: &mut Stock,
+                    provider: &dyn MarketDataProvider,
+                    opt_day: Option<datetime::SPDate>) -> Result<bool, Error> {
     let day = opt_day.unwrap_or_else(datetime::today);
     let back_delta =
         if datetime::is_monday(&day) {
@@ -29,15 +33,12 @@ pub fn update_stock(stock: &mut Stock, opt_day: Option<datetime::SPDate>) -> Res
         } else {
             -1
         };
-    let mut query = HistoryQuery::new(
-        stock.symbol.to_string(),
-        datetime::date_plus_days(&day, back_delta),
-        datetime::date_plus_days(&day, 1),
-        Interval::Daily,
-        Events::History);
-
-    query.execute()?;
-    match update_stock_from_csv(stock, &query.result) {
+    let csv = provider.history(
+        &stock.symbol,
+        &datetime::date_plus_days(&day, back_delta),
+        &datetime::date_plus_days(&day, 1))?;
+
+    match update_stock_from_csv(stock, &csv) {
         Ok(updated) => Ok(updated),
         Err(e) => Err(format!("Failed to update {} - {}", stock.symbol, e).into())
     }
@@ -50,8 +51,9 @@ pub fn update_stock_from_ds(stock: &mut Stock, ds: &DataStore) -> Result<bool, E
     }
 
     if ds.symbol_exists(dividends::tag(), &stock.symbol) {
-        let div = dividends::Dividends::ds_select_if(ds, &stock.symbol, |entry| entry.date > stock.date)?;
-        stock.cum_dividend = stock.quantity as Price * div.entries().iter().fold(0.0, |cum, d| cum + d.price);
+        let div = Dividends::ds_select_if(ds, &stock.symbol, |entry| entry.date > stock.date)?;
+        // Dividends of DRIP lots, including the reinvested lots, are paid in shares and not in cash.
+        stock.cum_dividend = if stock.drip { 0.0 } else { cum_dividend(stock, &div, &select_splits(stock, ds)?)? };
         if div.count() > 0 {
             let dent = &div.entries()[div.count() - 1];
             stock.set_latest_dividend(dent.price, dent.date);
@@ -66,10 +68,108 @@ pub fn update_stock_from_ds(stock: &mut Stock, ds: &DataStore) -> Result<bool, E
     Ok(false)
 }
 
-pub fn update_stocks(stocks: &mut StockList, opt_day: Option<datetime::SPDate>) -> Result<usize, Error> {
+// Splits of the stock symbol after the buy date, empty when the datastore has none.
+pub fn select_splits(stock: &Stock, ds: &DataStore) -> Result<Splits, Error> {
+    if ds.symbol_exists(splits::tag(), &stock.symbol) {
+        Splits::ds_select_if(ds, &stock.symbol, |entry| entry.date > stock.date)
+    } else {
+        Ok(Splits::new(&stock.symbol))
+    }
+}
+
+// Shares held on a date by a split adjusted stock lot, undoing the splits after the date.
+#[inline(always)]
+pub fn shares_on(stock: &Stock, date: &datetime::SPDate, splt: &Splits) -> Result<f64, Error> {
+    Ok(stock.quantity / splt.ratio_after(date)?)
+}
+
+// Cumulative dividend of a split adjusted stock lot, each dividend paid on the shares held on its date.
+pub fn cum_dividend(stock: &Stock, div: &Dividends, splt: &Splits) -> Result<f64, Error> {
+    let mut cum = 0.0;
+    for entry in div.entries().iter().filter(|entry| entry.date > stock.date) {
+        cum += shares_on(stock, &entry.date, splt)? * entry.price;
+    }
+    Ok(cum)
+}
+
+// Adjust quantity and base price of a stock lot for splits after its buy date.
+pub fn split_adjust(stock: &mut Stock, splt: &Splits) -> Result<bool, Error> {
+    let date = stock.date;
+    let mut adjusted = false;
+    for entry in splt.entries().iter().filter(|entry| entry.date > date) {
+        stock.apply_split(entry.ratio()?);
+        adjusted = true;
+    }
+    Ok(adjusted)
+}
+
+pub fn split_adjust_from_ds(stocks: &mut StockList, ds: &DataStore) -> Result<usize, Error> {
+    let mut count: usize = 0;
+    for stock in stocks.iter_mut() {
+        if !ds.symbol_exists(splits::tag(), &stock.symbol) {
+            continue;
+        }
+
+        let splt = Splits::ds_select_if(ds, &stock.symbol, |entry| entry.date > stock.date)?;
+        if split_adjust(stock, &splt)? {
+            count += 1;
+        }
+    }
+    Ok(count)
+}
+
+// Synthesize the lots bought by reinvesting dividends paid on a stock lot after its buy date.
+// Each dividend buys a fractional lot at the close on the dividend date, and later dividends
+// are paid on the compounded share count. The stock lot is not split adjusted yet, splits and
+// dividends are applied in date order and the lots are in shares of their dividend dates.
+pub fn reinvest_dividends(stock: &Stock, div: &Dividends, splt: &Splits, hist: &History) -> Result<StockList, Error> {
+    let mut lots = StockList::new();
+    let mut shares = stock.quantity;
+    let mut split_entries = splt.entries().iter().filter(|entry| entry.date > stock.date).peekable();
+    for dent in div.entries().iter().filter(|dent| dent.date > stock.date) {
+        while let Some(entry) = split_entries.next_if(|entry| entry.date <= dent.date) {
+            shares *= entry.ratio()?;
+        }
+
+        let close = match hist.entries().iter().rev().find(|entry| entry.date <= dent.date && entry.close > 0.0) {
+            Some(entry) => entry.close,
+            None => return Err(format!("Failed to find close for {} on {}", stock.symbol, dent.date.format("%Y-%m-%d")).into())
+        };
+
+        let quantity = shares * dent.price / close;
+        let mut lot = Stock::new(stock.symbol.clone(), stock.stype, dent.date, quantity, close);
+        lot.drip = true;
+        lots.push(lot);
+        shares += quantity;
+    }
+    Ok(lots)
+}
+
+pub fn reinvest_dividends_from_ds(stocks: &mut StockList, ds: &DataStore) -> Result<usize, Error> {
+    let mut reinvested = StockList::new();
+    for stock in stocks.iter().filter(|stock| stock.drip) {
+        if !ds.symbol_exists(dividends::tag(), &stock.symbol) {
+            continue;
+        }
+
+        let div = Dividends::ds_select_if(ds, &stock.symbol, |entry| entry.date > stock.date)?;
+        if div.count() > 0 {
+            let hist = History::ds_select_if(ds, &stock.symbol, |entry| entry.date <= div.entries()[div.count() - 1].date)?;
+            reinvested.extend(reinvest_dividends(stock, &div, &select_splits(stock, ds)?, &hist)?);
+        }
+    }
+
+    let count = reinvested.len();
+    stocks.extend(reinvested);
+    Ok(count)
+}
+
+pub fn update_stocks(stocks: &mut StockList,
+                     provider: &dyn MarketDataProvider,
+                     opt_day: Option<datetime::SPDate>) -> Result<usize, Error> {
     let mut count: usize = 0;
     for stock in stocks.iter_mut() {
-        if update_stock(stock, opt_day)? {
+        if update_stock(stock, provider, opt_day)? {
             count += 1;
         }
     }
@@ -0,0 +1,88 @@
+use crate::util::error::Error;
+use crate::portfolio::stock::Stock;
+use crate::portfolio::stock_type::{self, StockType};
+
+// Prefix of a stock type target name, e.g. type:etf
+const TYPE_PREFIX: &str = "type:";
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum TargetKey {
+    Symbol(String),
+    Type(StockType)
+}
+
+// Target allocation of a symbol or of all stocks of a type, in percent of portfolio value.
+// The tolerance is the drift in percent points allowed before rebalancing.
+pub struct Target {
+    pub key: TargetKey,
+    pub weight: f64,
+    pub tolerance: f64
+}
+
+pub type TargetList = Vec<Target>;
+
+impl Target {
+    pub fn new(key: TargetKey, weight: f64, tolerance: f64) -> Self {
+        Target {
+            key,
+            weight,
+            tolerance
+        }
+    }
+
+    // Symbol name, or type: followed by the stock type name.
+    pub fn parse_key(name: &str) -> Result<TargetKey, Error> {
+        match name.strip_prefix(TYPE_PREFIX) {
+            Some(stype) => Ok(TargetKey::Type(stock_type::str2stocktype(stype)?)),
+            None if !name.is_empty() => Ok(TargetKey::Symbol(String::from(name))),
+            None => Err("Empty target name".into())
+        }
+    }
+
+    pub fn name(&self) -> String {
+        match &self.key {
+            TargetKey::Symbol(symbol) => symbol.clone(),
+            TargetKey::Type(stype) => format!("{}{}", TYPE_PREFIX, stock_type::stocktype2str(*stype))
+        }
+    }
+}
+
+// Index of the target of a stock. A symbol target takes precedence over a stock type target.
+pub fn target_index(targets: &[Target], stock: &Stock) -> Option<usize> {
+    targets.iter().position(|target| target.key == TargetKey::Symbol(stock.symbol.clone()))
+        .or_else(|| targets.iter().position(|target| target.key == TargetKey::Type(stock.stype)))
+}
+
+// --------------------------------------------------------------------------------
+// Unit Tests
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::datetime;
+
+    #[test]
+    fn test_target_key() {
+        assert_eq!(Target::parse_key("AAPL").unwrap(), TargetKey::Symbol(String::from("AAPL")));
+        assert_eq!(Target::parse_key("type:etf").unwrap(), TargetKey::Type(StockType::ETF));
+        assert_eq!(format!("{}", Target::parse_key("type:bond").err().unwrap()), "Unknown stock type 'bond'");
+        assert_eq!(format!("{}", Target::parse_key("").err().unwrap()), "Empty target name");
+
+        assert_eq!(Target::new(TargetKey::Symbol(String::from("AAPL")), 10.0, 0.0).name(), "AAPL");
+        assert_eq!(Target::new(TargetKey::Type(StockType::ETF), 10.0, 0.0).name(), "type:etf");
+    }
+
+    #[test]
+    fn test_target_index() {
+        let targets: TargetList = vec![Target::new(TargetKey::Type(StockType::Cash), 50.0, 5.0),
+                                       Target::new(TargetKey::Symbol(String::from("AAPL")), 20.0, 2.0)];
+
+        let date = datetime::make_date(2021, 2, 10);
+        let aapl = Stock::new(String::from("AAPL"), StockType::Cash, date, 10.0, 120.0);
+        let dell = Stock::new(String::from("DELL"), StockType::Cash, date, 10.0, 80.0);
+        let icln = Stock::new(String::from("ICLN"), StockType::ETF, date, 10.0, 24.0);
+        assert_eq!(target_index(&targets, &aapl), Some(1));
+        assert_eq!(target_index(&targets, &dell), Some(0));
+        assert_eq!(target_index(&targets, &icln), None);
+    }
+}
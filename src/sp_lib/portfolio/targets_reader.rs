@@ -0,0 +1,129 @@
+use std::io::prelude::*;
+use std::fs::File;
+use std::io::BufReader;
+
+use crate::util::error::Error;
+use crate::portfolio::target::{Target, TargetList};
+
+pub struct TargetsReader {
+    targets_file: String
+}
+
+impl TargetsReader {
+    pub fn new(targets_file: String) -> Self {
+        TargetsReader {
+            targets_file
+        }
+    }
+
+    pub fn read(&self) -> Result<TargetList, Error> {
+        match File::open(&self.targets_file) {
+            Ok(file) => {
+                let mut reader = BufReader::new(file);
+                let mut content = String::new();
+                match reader.read_to_string(&mut content) {
+                    Ok(_) => Self::parse_content(&content),
+                    Err(e) => Err(format!("TargetsReader::read - {}", e).into())
+                }
+            },
+            Err(e) => Err(format!("TargetsReader::read - {}", e).into())
+        }
+    }
+
+    // Columns: target,weight,tolerance
+    // The target is a symbol, or type: followed by a stock type. Weights and tolerances are in percent.
+    // The tolerance column is optional and can be left empty.
+    pub fn parse_content(content: &str) -> Result<TargetList, Error> {
+        let mut targets = TargetList::new();
+
+        let mut skip_header: bool = true;
+        for target_line in content.lines() {
+            // Assume first line is a header and skip it.
+            if skip_header {
+                skip_header = false;
+                continue;
+            }
+
+            if target_line.is_empty() {
+                continue;
+            }
+
+            let target_tokens: Vec<&str> = target_line.split(',').map(|t| t.trim()).collect();
+            if target_tokens.len() != 2 && target_tokens.len() != 3 {
+                return Err(format!("TargetsReader::parse_content - Invalid target line '{}'", target_line).into())
+            }
+
+            let key = Target::parse_key(target_tokens[0])?;
+            let weight = Self::parse_percent("weight", target_tokens[1])?;
+            let tolerance = match target_tokens.get(2) {
+                Some(tol) if !tol.is_empty() => Self::parse_percent("tolerance", tol)?,
+                _ => 0.0
+            };
+
+            let target = Target::new(key, weight, tolerance);
+            if targets.iter().any(|t| t.key == target.key) {
+                return Err(format!("TargetsReader::parse_content - Duplicate target '{}'", target.name()).into())
+            }
+            targets.push(target);
+        }
+
+        let total = targets.iter().fold(0.0, |total, t| total + t.weight);
+        if total - 100.0 > 1e-6 {
+            return Err(format!("TargetsReader::parse_content - Target weights sum to {} percent", total).into())
+        }
+
+        Ok(targets)
+    }
+
+    fn parse_percent(which: &str, value: &str) -> Result<f64, Error> {
+        match value.parse::<f64>() {
+            Ok(pct) if (0.0..=100.0).contains(&pct) => Ok(pct),
+            _ => Err(format!("TargetsReader::parse_content - Invalid {} '{}'", which, value).into())
+        }
+    }
+}
+
+// --------------------------------------------------------------------------------
+// Unit Tests
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::portfolio::stock_type::StockType;
+    use crate::portfolio::target::TargetKey;
+
+    #[test]
+    fn test_parse_content() {
+        let content = "target,weight,tolerance\n\
+                       AAPL,25,5\n\
+                       \n\
+                       type:etf,40.5,\n\
+                       DELL,10\n";
+        let targets = TargetsReader::parse_content(content).unwrap();
+        assert_eq!(targets.len(), 3);
+        assert_eq!(targets[0].key, TargetKey::Symbol(String::from("AAPL")));
+        assert_eq!(targets[0].weight, 25.0);
+        assert_eq!(targets[0].tolerance, 5.0);
+        assert_eq!(targets[1].key, TargetKey::Type(StockType::ETF));
+        assert_eq!(targets[1].weight, 40.5);
+        assert_eq!(targets[1].tolerance, 0.0);
+        assert_eq!(targets[2].key, TargetKey::Symbol(String::from("DELL")));
+        assert_eq!(targets[2].tolerance, 0.0);
+    }
+
+    #[test]
+    fn test_parse_content_errors() {
+        fn check(content: &str, error: &str) {
+            let content = format!("target,weight,tolerance\n{}", content);
+            assert_eq!(format!("{}", TargetsReader::parse_content(&content).err().unwrap()), error);
+        }
+
+        check("AAPL\n", "TargetsReader::parse_content - Invalid target line 'AAPL'");
+        check("AAPL,abc\n", "TargetsReader::parse_content - Invalid weight 'abc'");
+        check("AAPL,120\n", "TargetsReader::parse_content - Invalid weight '120'");
+        check("AAPL,20,-1\n", "TargetsReader::parse_content - Invalid tolerance '-1'");
+        check("type:bond,20\n", "Unknown stock type 'bond'");
+        check("AAPL,20\nAAPL,30\n", "TargetsReader::parse_content - Duplicate target 'AAPL'");
+        check("AAPL,60\ntype:etf,50\n", "TargetsReader::parse_content - Target weights sum to 110 percent");
+    }
+}
@@ -0,0 +1,284 @@
+use std::collections::BTreeMap;
+use std::fmt;
+
+use crate::util::datetime;
+use crate::util::datetime::SPDate;
+use crate::util::error::Error;
+use crate::portfolio::closed_position::{ClosedPosition, ClosedPositionList};
+use crate::portfolio::stock::{Price, Quantity, Stock, StockList};
+use crate::portfolio::stock_type::StockType;
+
+// --------------------------------------------------------------------------------
+// Holding Term
+
+#[derive(Debug, Copy, Clone)]
+#[derive(PartialOrd, Ord, PartialEq, Eq)]
+pub enum HoldingTerm {
+    Short, // Held one year or less
+    Long   // Held more than one year
+}
+
+pub fn term2str(term: HoldingTerm) -> &'static str {
+    match term {
+        HoldingTerm::Short => "short",
+        HoldingTerm::Long => "long"
+    }
+}
+
+pub fn str2term(termstr: &str) -> Result<HoldingTerm, Error> {
+    match termstr.to_lowercase().as_str() {
+        "short" => Ok(HoldingTerm::Short),
+        "long" => Ok(HoldingTerm::Long),
+        _ => Err(format!("Unknown holding term '{}'", termstr).into())
+    }
+}
+
+impl fmt::Display for HoldingTerm {
+    fn fmt(self: &HoldingTerm, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", term2str(*self))
+    }
+}
+
+// Long term when sold after the one year anniversary of the acquired date.
+pub fn holding_term(acquired: &SPDate, sold: &SPDate) -> HoldingTerm {
+    if *sold > datetime::date_plus_year(acquired) {
+        HoldingTerm::Long
+    } else {
+        HoldingTerm::Short
+    }
+}
+
+// --------------------------------------------------------------------------------
+// Tax Lot
+
+pub struct TaxLot {
+    pub symbol: String,
+    pub account: String,
+    pub quantity: Quantity,
+    pub acquired: SPDate,
+    pub sold: SPDate,      // Exit date if realized, latest date otherwise
+    pub proceeds: Price,   // Net of exit fee
+    pub cost_basis: Price, // Including base fee
+    pub code: String,      // Form 8949 adjustment code, empty if none
+    pub adjustment: Price, // Form 8949 adjustment amount
+    pub realized: bool
+}
+
+pub type TaxLotList = Vec<TaxLot>;
+
+impl TaxLot {
+    #[inline(always)]
+    pub fn gain(&self) -> Price {
+        self.proceeds - self.cost_basis + self.adjustment
+    }
+
+    #[inline(always)]
+    pub fn term(&self) -> HoldingTerm {
+        holding_term(&self.acquired, &self.sold)
+    }
+
+    #[inline(always)]
+    pub fn tax_year(&self) -> i32 {
+        datetime::date_year(&self.sold)
+    }
+}
+
+// Short and long term gains.
+#[derive(Default)]
+pub struct TermGains {
+    pub short: Price,
+    pub long: Price
+}
+
+impl TermGains {
+    pub fn add(&mut self, lot: &TaxLot) {
+        match lot.term() {
+            HoldingTerm::Short => self.short += lot.gain(),
+            HoldingTerm::Long => self.long += lot.gain()
+        }
+    }
+
+    #[inline(always)]
+    pub fn total(&self) -> Price {
+        self.short + self.long
+    }
+}
+
+// Realized tax lot of a closed position.
+pub fn realized_lot(pos: &ClosedPosition) -> TaxLot {
+    TaxLot {
+        symbol: pos.symbol.clone(),
+        account: pos.account.clone(),
+        quantity: pos.quantity.to_float(),
+        acquired: pos.base_date,
+        sold: pos.exit_date,
+        proceeds: (pos.exit_notional() - pos.exit_fee).to_float(),
+        cost_basis: (pos.base_notional() + pos.base_fee).to_float(),
+        code: String::new(),
+        adjustment: 0.0,
+        realized: true
+    }
+}
+
+// Unrealized tax lot of an open stock valued at its latest price.
+pub fn unrealized_lot(stock: &Stock) -> TaxLot {
+    TaxLot {
+        symbol: stock.symbol.clone(),
+        account: stock.account.clone(),
+        quantity: stock.quantity,
+        acquired: stock.date,
+        sold: stock.latest_date,
+        proceeds: stock.latest_notional(),
+        cost_basis: stock.base_notional(),
+        code: String::new(),
+        adjustment: 0.0,
+        realized: false
+    }
+}
+
+// Realized tax lots of closed positions, excluding index positions.
+pub fn realized_lots(positions: &ClosedPositionList) -> TaxLotList {
+    positions.iter()
+        .filter(|pos| pos.stype != StockType::Index)
+        .map(realized_lot)
+        .collect()
+}
+
+// Unrealized tax lots of open stocks, excluding index stocks.
+pub fn unrealized_lots(stocks: &StockList) -> TaxLotList {
+    stocks.iter()
+        .filter(|stock| stock.stype != StockType::Index)
+        .map(unrealized_lot)
+        .collect()
+}
+
+// Short and long term gains of lots grouped by tax year.
+pub fn gains_by_year(lots: &[TaxLot]) -> BTreeMap<i32, TermGains> {
+    let mut years: BTreeMap<i32, TermGains> = BTreeMap::new();
+    for lot in lots.iter() {
+        years.entry(lot.tax_year()).or_default().add(lot);
+    }
+    years
+}
+
+// Short and long term gains of lots.
+pub fn term_gains(lots: &[TaxLot]) -> TermGains {
+    let mut gains = TermGains::default();
+    for lot in lots.iter() {
+        gains.add(lot);
+    }
+    gains
+}
+
+// --------------------------------------------------------------------------------
+// Unit Tests
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::fixed_price::FixedPrice;
+    use crate::util::price_type::price_eql;
+
+    #[test]
+    fn test_holding_term() {
+        assert_eq!(term2str(HoldingTerm::Short), "short");
+        assert_eq!(term2str(HoldingTerm::Long), "long");
+        assert_eq!(str2term("Long").unwrap(), HoldingTerm::Long);
+        assert_eq!(format!("{}", str2term("medium").err().unwrap()), "Unknown holding term 'medium'");
+
+        let acquired = datetime::make_date(2020, 3, 10);
+        assert_eq!(holding_term(&acquired, &datetime::make_date(2020, 3, 10)), HoldingTerm::Short);
+        assert_eq!(holding_term(&acquired, &datetime::make_date(2021, 3, 10)), HoldingTerm::Short);
+        assert_eq!(holding_term(&acquired, &datetime::make_date(2021, 3, 11)), HoldingTerm::Long);
+
+        let leap = datetime::make_date(2020, 2, 29);
+        assert_eq!(holding_term(&leap, &datetime::make_date(2021, 3, 1)), HoldingTerm::Short);
+        assert_eq!(holding_term(&leap, &datetime::make_date(2021, 3, 2)), HoldingTerm::Long);
+    }
+
+    #[test]
+    fn test_realized_lots() {
+        let positions: ClosedPositionList = vec![
+            ClosedPosition::new(String::from("AAPL"),
+                                StockType::Cash,
+                                datetime::make_date(2020, 9, 20),
+                                datetime::make_date(2021, 3, 1),
+                                FixedPrice::from_string("10"),
+                                FixedPrice::from_string("115.00"),
+                                FixedPrice::from_string("125.00"),
+                                FixedPrice::from_string("1.00"),
+                                FixedPrice::from_string("2.00"),
+                                FixedPrice::from_string("5.00")),
+            ClosedPosition::new(String::from("SPY"),
+                                StockType::Index,
+                                datetime::make_date(2020, 9, 20),
+                                datetime::make_date(2021, 3, 1),
+                                FixedPrice::from_string("1"),
+                                FixedPrice::from_string("300.00"),
+                                FixedPrice::from_string("350.00"),
+                                FixedPrice::from_string("0.00"),
+                                FixedPrice::from_string("0.00"),
+                                FixedPrice::from_string("0.00"))];
+
+        let lots = realized_lots(&positions);
+        assert_eq!(lots.len(), 1);
+
+        let lot = &lots[0];
+        assert_eq!(lot.symbol, "AAPL");
+        assert_eq!(lot.quantity, 10.0);
+        assert!(lot.realized);
+        assert!(price_eql(lot.proceeds, 1248.0));
+        assert!(price_eql(lot.cost_basis, 1151.0));
+        assert!(price_eql(lot.gain(), 97.0));
+        assert_eq!(lot.term(), HoldingTerm::Short);
+        assert_eq!(lot.tax_year(), 2021);
+    }
+
+    #[test]
+    fn test_unrealized_lots() {
+        let mut stock = Stock::new(String::from("DELL"), StockType::Cash, datetime::make_date(2020, 2, 10), 100.0, 75.50);
+        stock.set_latest_price(70.25, datetime::make_date(2021, 6, 30));
+        let stocks: StockList = vec![stock];
+
+        let lots = unrealized_lots(&stocks);
+        assert_eq!(lots.len(), 1);
+        assert!(!lots[0].realized);
+        assert!(price_eql(lots[0].gain(), -525.0));
+        assert_eq!(lots[0].term(), HoldingTerm::Long);
+    }
+
+    #[test]
+    fn test_gains_by_year() {
+        let lots: TaxLotList = vec![make_lot(datetime::make_date(2020, 1, 10), datetime::make_date(2020, 6, 1), 100.0),
+                                    make_lot(datetime::make_date(2019, 1, 10), datetime::make_date(2020, 6, 1), -40.0),
+                                    make_lot(datetime::make_date(2020, 1, 10), datetime::make_date(2021, 3, 1), 25.0),
+                                    make_lot(datetime::make_date(2021, 1, 10), datetime::make_date(2021, 3, 1), 10.0)];
+
+        let years = gains_by_year(&lots);
+        assert_eq!(years.keys().copied().collect::<Vec<i32>>(), vec![2020, 2021]);
+        assert!(price_eql(years[&2020].short, 100.0));
+        assert!(price_eql(years[&2020].long, -40.0));
+        assert!(price_eql(years[&2021].short, 10.0));
+        assert!(price_eql(years[&2021].long, 25.0));
+
+        let gains = term_gains(&lots);
+        assert!(price_eql(gains.short, 110.0));
+        assert!(price_eql(gains.long, -15.0));
+        assert!(price_eql(gains.total(), 95.0));
+    }
+
+    fn make_lot(acquired: SPDate, sold: SPDate, gain: Price) -> TaxLot {
+        TaxLot {
+            symbol: String::from("AAPL"),
+            account: String::new(),
+            quantity: 1.0,
+            acquired,
+            sold,
+            proceeds: 100.0 + gain,
+            cost_basis: 100.0,
+            code: String::new(),
+            adjustment: 0.0,
+            realized: true
+        }
+    }
+}
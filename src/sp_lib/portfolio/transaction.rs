@@ -0,0 +1,141 @@
+use std::fmt;
+
+use crate::util::datetime::SPDate;
+use crate::util::error::Error;
+use crate::util::fixed_price::FixedPrice;
+use crate::util::price_type::quantity_format;
+use crate::portfolio::stock_type::StockType;
+use crate::portfolio::symbol_trait::GetSymbol;
+
+pub type Price = FixedPrice;
+pub type Quantity = FixedPrice;
+
+// --------------------------------------------------------------------------------
+// Transaction Type
+
+#[derive(Debug, Copy, Clone)]
+#[derive(PartialOrd, Ord, PartialEq, Eq)]
+pub enum TransactionType {
+    Buy,
+    Sell
+}
+
+pub fn transactiontype2str(tt: TransactionType) -> &'static str {
+    match tt {
+        TransactionType::Buy => "buy",
+        TransactionType::Sell => "sell"
+    }
+}
+
+pub fn str2transactiontype(ttstr: &str) -> Result<TransactionType, Error> {
+    match ttstr.to_lowercase().as_str() {
+        "buy" => Ok(TransactionType::Buy),
+        "sell" => Ok(TransactionType::Sell),
+        _ => Err(format!("Unknown transaction type '{}'", ttstr).into())
+    }
+}
+
+impl fmt::Display for TransactionType {
+    fn fmt(self: &TransactionType, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", transactiontype2str(*self))
+    }
+}
+
+// --------------------------------------------------------------------------------
+// Transaction
+
+pub struct Transaction {
+    pub date: SPDate,
+    pub ttype: TransactionType,
+    pub symbol: String,
+    pub stype: StockType,
+    pub quantity: Quantity,
+    pub price: Price,
+    pub fee: Price,
+    pub lot: String // Lot id set on buys, and on sells matched with the specific lot method
+}
+
+pub type TransactionList = Vec<Transaction>;
+
+impl Transaction {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(date: SPDate,
+               ttype: TransactionType,
+               symbol: String,
+               stype: StockType,
+               quantity: Quantity,
+               price: Price,
+               fee: Price,
+               lot: String) -> Self {
+        Transaction {
+            date,
+            ttype,
+            symbol,
+            stype,
+            quantity,
+            price,
+            fee,
+            lot
+        }
+    }
+
+    #[inline(always)]
+    pub fn notional(&self) -> Price {
+        self.quantity * self.price
+    }
+}
+
+impl fmt::Display for Transaction {
+    fn fmt(self: &Transaction, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Transaction({} {} {}@{})", self.ttype, self.symbol, quantity_format(self.quantity.to_float()), self.price)
+    }
+}
+
+impl GetSymbol for Transaction {
+    fn get_symbol(&self) -> &String {
+        &self.symbol
+    }
+}
+
+// --------------------------------------------------------------------------------
+// Unit Tests
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::datetime;
+
+    #[test]
+    fn test_transaction_type() {
+        let buy = TransactionType::Buy;
+        let sell = TransactionType::Sell;
+
+        assert_eq!(transactiontype2str(buy), "buy");
+        assert_eq!(transactiontype2str(sell), "sell");
+        assert!(str2transactiontype("buy").unwrap() == buy);
+        assert!(str2transactiontype("Sell").unwrap() == sell);
+        assert_eq!(format!("{}", str2transactiontype("hold").unwrap_err()), "Unknown transaction type 'hold'");
+    }
+
+    #[test]
+    fn test_transaction_new() {
+        let tx = Transaction::new(datetime::make_date(2021, 3, 1),
+                                  TransactionType::Buy,
+                                  String::from("AAPL"),
+                                  StockType::Cash,
+                                  Quantity::from_string("100.5"),
+                                  Price::from_string("120.50"),
+                                  Price::from_string("1.00"),
+                                  String::from("L1"));
+        assert_eq!(tx.date, datetime::make_date(2021, 3, 1));
+        assert_eq!(tx.ttype, TransactionType::Buy);
+        assert_eq!(tx.get_symbol(), "AAPL");
+        assert_eq!(tx.stype, StockType::Cash);
+        assert_eq!(tx.quantity, Quantity::from_string("100.5"));
+        assert_eq!(tx.price, Price::from_string("120.50"));
+        assert_eq!(tx.fee, Price::from_string("1.00"));
+        assert_eq!(tx.lot, "L1");
+        assert_eq!(tx.notional(), Price::from_string("12110.25"));
+        assert_eq!(format!("{}", tx), "Transaction(buy AAPL 100.5@120.5000)");
+    }
+}
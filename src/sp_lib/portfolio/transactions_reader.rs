@@ -0,0 +1,136 @@
+use std::io::prelude::*;
+use std::fs::File;
+use std::io::BufReader;
+
+use crate::util::error::Error;
+use crate::util::datetime;
+use crate::util::fixed_price::FP_0;
+use crate::portfolio::stock_type;
+use crate::portfolio::transaction::{Price, Quantity, Transaction, TransactionList, str2transactiontype};
+
+pub struct TransactionsReader {
+    transactions_file: String
+}
+
+impl TransactionsReader {
+    pub fn new(transactions_file: String) -> Self {
+        TransactionsReader {
+            transactions_file
+        }
+    }
+
+    pub fn read(&self) -> Result<TransactionList, Error> {
+        match File::open(&self.transactions_file) {
+            Ok(file) => {
+                let mut reader = BufReader::new(file);
+                let mut content = String::new();
+                match reader.read_to_string(&mut content) {
+                    Ok(_) => Self::parse_content(&content),
+                    Err(e) => Err(format!("TransactionsReader::read - {}", e).into())
+                }
+            },
+            Err(e) => Err(format!("TransactionsReader::read - {}", e).into())
+        }
+    }
+
+    // Columns: date,action,symbol,type,quantity,price,fee,lot
+    // The fee and lot columns can be left empty.
+    pub fn parse_content(content: &str) -> Result<TransactionList, Error> {
+        let mut transactions = TransactionList::new();
+
+        let mut skip_header: bool = true;
+        for tx_line in content.lines() {
+            // Assume first line is a header and skip it.
+            if skip_header {
+                skip_header = false;
+                continue;
+            }
+
+            if tx_line.is_empty() {
+                continue;
+            }
+
+            let tx_tokens: Vec<&str> = tx_line.split(',').map(|t| t.trim()).collect();
+            if tx_tokens.len() != 8 {
+                return Err(format!("TransactionsReader::parse_content - Invalid transaction line '{}'", tx_line).into())
+            }
+
+            let date = datetime::parse_date(tx_tokens[0])?;
+            let ttype = str2transactiontype(tx_tokens[1])?;
+            let symbol = String::from(tx_tokens[2]);
+            let stype = stock_type::str2stocktype(tx_tokens[3])?;
+
+            let quantity = match Quantity::parse(tx_tokens[4]) {
+                Ok(qty) if qty > FP_0 => qty,
+                Ok(_) => return Err(format!("TransactionsReader::parse_content - Invalid quantity '{}'", tx_tokens[4]).into()),
+                Err(e) => return Err(format!("TransactionsReader::parse_content - Invalid quantity '{}'", e).into())
+            };
+
+            let price = Self::parse_price(tx_tokens[5], "price")?;
+            let fee = if tx_tokens[6].is_empty() { FP_0 } else { Self::parse_price(tx_tokens[6], "fee")? };
+            let lot = String::from(tx_tokens[7]);
+
+            transactions.push(Transaction::new(date, ttype, symbol, stype, quantity, price, fee, lot));
+        }
+
+        Ok(transactions)
+    }
+
+    fn parse_price(token: &str, which: &str) -> Result <Price, Error> {
+        match Price::parse(token) {
+            Ok(px) => Ok(px),
+            Err(e) => Err(format!("TransactionsReader::parse_price - Invalid {} '{}'", which, e).into())
+        }
+    }
+}
+
+// --------------------------------------------------------------------------------
+// Unit Tests
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::portfolio::stock_type::StockType;
+    use crate::portfolio::transaction::TransactionType;
+
+    #[test]
+    fn test_parse_content() {
+        let content = "date,action,symbol,type,quantity,price,fee,lot\n\
+                       2021-03-01,buy,AAPL,cash,100,120.50,1.00,L1\n\
+                       2021-06-01,sell,AAPL,cash,40.25,130.25,,\n";
+        let txs = TransactionsReader::parse_content(content).unwrap();
+        assert_eq!(txs.len(), 2);
+
+        assert_eq!(txs[0].date, datetime::make_date(2021, 3, 1));
+        assert_eq!(txs[0].ttype, TransactionType::Buy);
+        assert_eq!(txs[0].symbol, "AAPL");
+        assert_eq!(txs[0].stype, StockType::Cash);
+        assert_eq!(txs[0].quantity, Quantity::from_unsigned(100));
+        assert_eq!(txs[0].price, Price::from_string("120.50"));
+        assert_eq!(txs[0].fee, Price::from_string("1.00"));
+        assert_eq!(txs[0].lot, "L1");
+
+        assert_eq!(txs[1].date, datetime::make_date(2021, 6, 1));
+        assert_eq!(txs[1].ttype, TransactionType::Sell);
+        assert_eq!(txs[1].quantity, Quantity::from_string("40.25"));
+        assert_eq!(txs[1].price, Price::from_string("130.25"));
+        assert_eq!(txs[1].fee, FP_0);
+        assert_eq!(txs[1].lot, "");
+    }
+
+    #[test]
+    fn test_parse_content_errors() {
+        fn check(line: &str, err: &str) {
+            let content = format!("date,action,symbol,type,quantity,price,fee,lot\n{}\n", line);
+            assert_eq!(format!("{}", TransactionsReader::parse_content(&content).err().unwrap()), err);
+        }
+
+        check("2021-03-01,buy,AAPL,cash,100,120.50,1.00",
+              "TransactionsReader::parse_content - Invalid transaction line '2021-03-01,buy,AAPL,cash,100,120.50,1.00'");
+        check("2021-03-01,hold,AAPL,cash,100,120.50,1.00,", "Unknown transaction type 'hold'");
+        check("2021-03-01,buy,AAPL,cash,0,120.50,1.00,", "TransactionsReader::parse_content - Invalid quantity '0'");
+        check("2021-03-01,buy,AAPL,cash,-1.5,120.50,1.00,", "TransactionsReader::parse_content - Invalid quantity '-1.5'");
+        check("2021-03-01,buy,AAPL,cash,ten,120.50,1.00,",
+              "TransactionsReader::parse_content - Invalid quantity 'parse_scaled - Invalid value 'ten''");
+    }
+}
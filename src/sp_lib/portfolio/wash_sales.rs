@@ -0,0 +1,368 @@
+use std::collections::HashMap;
+use std::iter::zip;
+
+use crate::util::datetime;
+use crate::util::datetime::SPDate;
+use crate::portfolio::closed_position::{ClosedPosition, ClosedPositionList};
+use crate::portfolio::stock::{Price, Quantity, Stock, StockList};
+use crate::portfolio::stock_type::StockType;
+use crate::portfolio::tax_lots::{self, TaxLot, TaxLotList};
+
+// Days before and after a loss sale in which a buy of the same symbol is a replacement.
+pub const WASH_SALE_DAYS: i64 = 30;
+
+// Adjustment code of a wash sale on Form 8949.
+pub const WASH_SALE_CODE: &str = "W";
+
+// --------------------------------------------------------------------------------
+// Wash Sale
+
+pub struct WashSale {
+    pub symbol: String,
+    pub sold_index: usize,        // Index of the loss sale in the closed positions
+    pub sold_date: SPDate,
+    pub replacement_index: usize, // Index of the replacement lot in the stocks or closed positions
+    pub replacement_open: bool,   // Replacement lot is an open stock, otherwise a closed position
+    pub replacement_date: SPDate,
+    pub quantity: Quantity,       // Sold shares matched to replacement shares
+    pub disallowed: Price         // Loss disallowed on the matched shares
+}
+
+pub type WashSaleList = Vec<WashSale>;
+
+impl WashSale {
+    // Increase in cost basis per share of the replacement lot.
+    #[inline(always)]
+    pub fn basis_adjustment(&self) -> Price {
+        self.disallowed / self.quantity
+    }
+}
+
+// Match loss sales of closed positions to buys of the same symbol in open stocks or other closed
+// positions acquired within 30 days before or after the sale. Loss sales are matched in date sold
+// order to replacement lots in date acquired order, and each replacement share is matched once.
+// Lots acquired on the date the sold lot was acquired are treated as the same buy.
+// A disallowed loss is added to the cost basis of its replacement, which carries over to a later
+// loss sale of that replacement. Index stocks and positions are ignored.
+pub fn find_wash_sales(stocks: &StockList, positions: &ClosedPositionList) -> WashSaleList {
+    let mut replacements: HashMap<&str, Vec<Replacement>> = HashMap::new();
+    for (index, stock) in stocks.iter().enumerate().filter(|(_, stock)| stock.stype != StockType::Index) {
+        replacements.entry(stock.symbol.as_str()).or_default().push(Replacement {
+            index,
+            open: true,
+            date: stock.date,
+            remaining: stock.quantity
+        });
+    }
+    for (index, pos) in positions.iter().enumerate().filter(|(_, pos)| pos.stype != StockType::Index) {
+        replacements.entry(pos.symbol.as_str()).or_default().push(Replacement {
+            index,
+            open: false,
+            date: pos.base_date,
+            remaining: pos.quantity.to_float()
+        });
+    }
+    for lots in replacements.values_mut() {
+        lots.sort_by_key(|repl| repl.date);
+    }
+
+    let mut sold: Vec<usize> = (0..positions.len()).filter(|index| positions[*index].stype != StockType::Index).collect();
+    sold.sort_by_key(|index| positions[*index].exit_date);
+
+    let mut basis_adjustments: HashMap<usize, Price> = HashMap::new();
+    let mut sales = WashSaleList::new();
+    for sold_index in sold {
+        let lot = tax_lots::realized_lot(&positions[sold_index]);
+        let loss = -(lot.gain() - basis_adjustments.get(&sold_index).unwrap_or(&0.0));
+        if loss <= 0.0 || lot.quantity <= 0.0 {
+            continue;
+        }
+
+        let lots = match replacements.get_mut(lot.symbol.as_str()) {
+            Some(lots) => lots,
+            None => continue
+        };
+
+        let from = datetime::date_plus_days(&lot.sold, -WASH_SALE_DAYS);
+        let to = datetime::date_plus_days(&lot.sold, WASH_SALE_DAYS);
+        let mut unmatched = lot.quantity;
+        for repl in lots.iter_mut().filter(|repl| repl.date >= from && repl.date <= to && repl.date != lot.acquired) {
+            if unmatched <= 0.0 {
+                break;
+            }
+            if repl.remaining <= 0.0 {
+                continue;
+            }
+
+            let quantity = unmatched.min(repl.remaining);
+            let disallowed = loss * quantity / lot.quantity;
+            repl.remaining -= quantity;
+            unmatched -= quantity;
+
+            if !repl.open {
+                *basis_adjustments.entry(repl.index).or_insert(0.0) += disallowed;
+            }
+
+            sales.push(WashSale {
+                symbol: lot.symbol.clone(),
+                sold_index,
+                sold_date: lot.sold,
+                replacement_index: repl.index,
+                replacement_open: repl.open,
+                replacement_date: repl.date,
+                quantity,
+                disallowed
+            });
+        }
+    }
+    sales
+}
+
+// Latest buy of symbol in open stocks or closed positions within 30 days before the sale date, which
+// would make a loss sale on that date a wash sale. Lots acquired on the acquired date are ignored.
+pub fn recent_buy(stocks: &StockList, positions: &ClosedPositionList, symbol: &str, acquired: &SPDate, sale_date: &SPDate) -> Option<SPDate> {
+    let from = datetime::date_plus_days(sale_date, -WASH_SALE_DAYS);
+    stocks.iter().filter(|stock| stock.stype != StockType::Index && stock.symbol == symbol).map(|stock| stock.date)
+        .chain(positions.iter().filter(|pos| pos.stype != StockType::Index && pos.symbol == symbol).map(|pos| pos.base_date))
+        .filter(|date| *date >= from && *date <= *sale_date && *date != *acquired)
+        .max()
+}
+
+// Realized and unrealized tax lots with wash sales applied. Disallowed losses are added back to
+// the loss sales with adjustment code W, and added to the cost basis of their replacement lots.
+pub fn adjusted_tax_lots(stocks: &StockList, positions: &ClosedPositionList, sales: &[WashSale]) -> (TaxLotList, TaxLotList) {
+    let (realized, unrealized) = adjust_lots(stocks, positions, sales);
+    (realized.into_iter().flatten().collect(), unrealized.into_iter().flatten().collect())
+}
+
+// Wash sales found among all stocks and positions, keeping those with both the sold lot and the replacement
+// lot among the shown stocks and positions. Sale indexes refer to all stocks and positions.
+pub fn shown_wash_sales(all_stocks: &StockList,
+                        all_positions: &ClosedPositionList,
+                        stocks: &StockList,
+                        positions: &ClosedPositionList) -> WashSaleList {
+    find_wash_sales(all_stocks, all_positions).into_iter()
+        .filter(|sale| is_shown_position(&all_positions[sale.sold_index], positions))
+        .filter(|sale| if sale.replacement_open {
+            is_shown_stock(&all_stocks[sale.replacement_index], stocks)
+        } else {
+            is_shown_position(&all_positions[sale.replacement_index], positions)
+        })
+        .collect()
+}
+
+// Realized and unrealized tax lots of the shown stocks and positions, with the wash sales found among all
+// stocks and positions applied, so that which sales are wash sales does not depend on the lots shown.
+pub fn shown_tax_lots(all_stocks: &StockList,
+                      all_positions: &ClosedPositionList,
+                      stocks: &StockList,
+                      positions: &ClosedPositionList) -> (TaxLotList, TaxLotList) {
+    let sales = find_wash_sales(all_stocks, all_positions);
+    let (realized, unrealized) = adjust_lots(all_stocks, all_positions, &sales);
+    (zip(all_positions, realized).filter(|(pos, _)| is_shown_position(pos, positions)).filter_map(|(_, lot)| lot).collect(),
+     zip(all_stocks, unrealized).filter(|(stock, _)| is_shown_stock(stock, stocks)).filter_map(|(_, lot)| lot).collect())
+}
+
+// --------------------------------------------------------------------------------
+// Private
+
+struct Replacement {
+    index: usize,
+    open: bool,
+    date: SPDate,
+    remaining: Quantity
+}
+
+// Tax lots aligned with the positions and stocks, None for index stocks.
+fn adjust_lots(stocks: &StockList, positions: &ClosedPositionList, sales: &[WashSale]) -> (Vec<Option<TaxLot>>, Vec<Option<TaxLot>>) {
+    let mut realized: Vec<Option<_>> = positions.iter()
+        .map(|pos| if pos.stype != StockType::Index { Some(tax_lots::realized_lot(pos)) } else { None })
+        .collect();
+    let mut unrealized: Vec<Option<_>> = stocks.iter()
+        .map(|stock| if stock.stype != StockType::Index { Some(tax_lots::unrealized_lot(stock)) } else { None })
+        .collect();
+
+    for sale in sales.iter() {
+        if let Some(lot) = realized[sale.sold_index].as_mut() {
+            lot.code = String::from(WASH_SALE_CODE);
+            lot.adjustment += sale.disallowed;
+        }
+
+        let replacement = if sale.replacement_open {
+            unrealized[sale.replacement_index].as_mut()
+        } else {
+            realized[sale.replacement_index].as_mut()
+        };
+        if let Some(lot) = replacement {
+            lot.cost_basis += sale.disallowed;
+        }
+    }
+
+    (realized, unrealized)
+}
+
+#[inline(always)]
+fn is_shown_stock(stock: &Stock, stocks: &StockList) -> bool {
+    stocks.iter().any(|shown| shown.same_lot(stock))
+}
+
+#[inline(always)]
+fn is_shown_position(pos: &ClosedPosition, positions: &ClosedPositionList) -> bool {
+    positions.iter().any(|shown| shown.same_lot(pos))
+}
+
+// --------------------------------------------------------------------------------
+// Unit Tests
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::portfolio::closed_position::ClosedPosition;
+    use crate::portfolio::stock::Stock;
+    use crate::util::fixed_price::FixedPrice;
+    use crate::util::price_type::price_eql;
+
+    #[test]
+    fn test_find_wash_sales() {
+        let stocks: StockList = vec![make_stock("AAPL", datetime::make_date(2021, 3, 15), 6.0, 110.0),
+                                     make_stock("AAPL", datetime::make_date(2021, 5, 10), 10.0, 112.0),
+                                     make_stock("DELL", datetime::make_date(2021, 3, 15), 10.0, 80.0)];
+        let positions: ClosedPositionList = vec![make_position("AAPL", datetime::make_date(2021, 1, 4), datetime::make_date(2021, 3, 1), "10", "120.00", "100.00"),
+                                                 make_position("AAPL", datetime::make_date(2021, 2, 20), datetime::make_date(2021, 4, 1), "2", "105.00", "110.00")];
+
+        let sales = find_wash_sales(&stocks, &positions);
+        assert_eq!(sales.len(), 2);
+
+        let sale = &sales[0];
+        assert_eq!(sale.symbol, "AAPL");
+        assert_eq!(sale.sold_index, 0);
+        assert_eq!(sale.sold_date, datetime::make_date(2021, 3, 1));
+        assert_eq!(sale.replacement_index, 1);
+        assert!(!sale.replacement_open);
+        assert_eq!(sale.replacement_date, datetime::make_date(2021, 2, 20));
+        assert_eq!(sale.quantity, 2.0);
+        assert!(price_eql(sale.disallowed, 40.0));
+        assert!(price_eql(sale.basis_adjustment(), 20.0));
+
+        let sale = &sales[1];
+        assert_eq!(sale.sold_index, 0);
+        assert_eq!(sale.replacement_index, 0);
+        assert!(sale.replacement_open);
+        assert_eq!(sale.quantity, 6.0);
+        assert!(price_eql(sale.disallowed, 120.0));
+    }
+
+    #[test]
+    fn test_find_wash_sales_carry_over() {
+        let stocks: StockList = vec![make_stock("AAPL", datetime::make_date(2021, 3, 20), 10.0, 95.0)];
+        let positions: ClosedPositionList = vec![make_position("AAPL", datetime::make_date(2021, 1, 4), datetime::make_date(2021, 3, 1), "10", "120.00", "100.00"),
+                                                 make_position("AAPL", datetime::make_date(2021, 2, 20), datetime::make_date(2021, 3, 10), "10", "100.00", "105.00")];
+
+        // The second position gains 50 but its basis rises by the 200 disallowed on the first,
+        // so its 150 loss is washed by the open lot.
+        let sales = find_wash_sales(&stocks, &positions);
+        assert_eq!(sales.len(), 2);
+        assert_eq!(sales[0].sold_index, 0);
+        assert_eq!(sales[0].replacement_index, 1);
+        assert!(price_eql(sales[0].disallowed, 200.0));
+        assert_eq!(sales[1].sold_index, 1);
+        assert!(sales[1].replacement_open);
+        assert!(price_eql(sales[1].disallowed, 150.0));
+    }
+
+    #[test]
+    fn test_find_wash_sales_none() {
+        // Gains, buys outside the window, buys on the sold lot date and index positions are not wash sales
+        let stocks: StockList = vec![make_stock("AAPL", datetime::make_date(2021, 4, 1), 10.0, 110.0),
+                                     make_stock("DELL", datetime::make_date(2021, 1, 4), 10.0, 80.0)];
+        let mut positions: ClosedPositionList = vec![make_position("AAPL", datetime::make_date(2021, 1, 4), datetime::make_date(2021, 3, 1), "10", "120.00", "100.00"),
+                                                     make_position("DELL", datetime::make_date(2021, 1, 4), datetime::make_date(2021, 1, 20), "5", "90.00", "80.00"),
+                                                     make_position("MSFT", datetime::make_date(2021, 1, 4), datetime::make_date(2021, 1, 20), "5", "200.00", "220.00"),
+                                                     make_position("SPY", datetime::make_date(2021, 1, 4), datetime::make_date(2021, 1, 20), "1", "380.00", "370.00"),
+                                                     make_position("SPY", datetime::make_date(2021, 1, 10), datetime::make_date(2021, 1, 25), "1", "375.00", "380.00")];
+        positions[3].stype = StockType::Index;
+        positions[4].stype = StockType::Index;
+
+        assert!(find_wash_sales(&stocks, &positions).is_empty());
+    }
+
+    #[test]
+    fn test_adjusted_tax_lots() {
+        let stocks: StockList = vec![make_stock("AAPL", datetime::make_date(2021, 3, 15), 6.0, 110.0)];
+        let positions: ClosedPositionList = vec![make_position("AAPL", datetime::make_date(2021, 1, 4), datetime::make_date(2021, 3, 1), "10", "120.00", "100.00"),
+                                                 make_position("AAPL", datetime::make_date(2021, 2, 20), datetime::make_date(2021, 4, 1), "2", "105.00", "110.00")];
+
+        let sales = find_wash_sales(&stocks, &positions);
+        let (realized, unrealized) = adjusted_tax_lots(&stocks, &positions, &sales);
+
+        assert_eq!(realized.len(), 2);
+        assert_eq!(realized[0].code, "W");
+        assert!(price_eql(realized[0].adjustment, 160.0));
+        assert!(price_eql(realized[0].gain(), -40.0));
+        assert_eq!(realized[1].code, "");
+        assert!(price_eql(realized[1].cost_basis, 250.0));
+        assert!(price_eql(realized[1].gain(), -30.0));
+
+        assert_eq!(unrealized.len(), 1);
+        assert!(price_eql(unrealized[0].cost_basis, 780.0));
+    }
+
+    #[test]
+    fn test_shown_lots() {
+        let stocks: StockList = vec![make_stock("AAPL", datetime::make_date(2021, 3, 15), 6.0, 110.0),
+                                     make_stock("DELL", datetime::make_date(2021, 3, 15), 10.0, 80.0)];
+        let positions: ClosedPositionList = vec![make_position("AAPL", datetime::make_date(2021, 1, 4), datetime::make_date(2021, 3, 1), "10", "120.00", "100.00"),
+                                                 make_position("AAPL", datetime::make_date(2021, 2, 20), datetime::make_date(2021, 4, 1), "2", "105.00", "110.00")];
+        let shown_stocks: StockList = vec![stocks[1].clone()];
+
+        // The open AAPL replacement is not shown, but still washes the loss sale
+        let (realized, unrealized) = shown_tax_lots(&stocks, &positions, &shown_stocks, &positions);
+        assert_eq!(realized.len(), 2);
+        assert_eq!(realized[0].code, "W");
+        assert!(price_eql(realized[0].adjustment, 160.0));
+        assert_eq!(unrealized.len(), 1);
+        assert_eq!(unrealized[0].symbol, "DELL");
+
+        let sales = shown_wash_sales(&stocks, &positions, &shown_stocks, &positions);
+        assert_eq!(sales.len(), 1);
+        assert!(!sales[0].replacement_open);
+        assert!(price_eql(sales[0].disallowed, 40.0));
+
+        let sales = shown_wash_sales(&stocks, &positions, &shown_stocks, &positions[1..].to_vec());
+        assert!(sales.is_empty());
+    }
+
+    #[test]
+    fn test_recent_buy() {
+        let stocks: StockList = vec![make_stock("AAPL", datetime::make_date(2021, 1, 4), 10.0, 120.0),
+                                     make_stock("AAPL", datetime::make_date(2021, 2, 10), 2.0, 110.0),
+                                     make_stock("DELL", datetime::make_date(2021, 2, 20), 10.0, 80.0)];
+        let positions: ClosedPositionList = vec![make_position("AAPL", datetime::make_date(2021, 2, 15), datetime::make_date(2021, 2, 25), "2", "105.00", "110.00")];
+
+        let acquired = datetime::make_date(2021, 1, 4);
+        assert_eq!(recent_buy(&stocks, &positions, "AAPL", &acquired, &datetime::make_date(2021, 3, 1)), Some(datetime::make_date(2021, 2, 15)));
+        assert_eq!(recent_buy(&stocks, &positions, "AAPL", &acquired, &datetime::make_date(2021, 2, 12)), Some(datetime::make_date(2021, 2, 10)));
+        assert_eq!(recent_buy(&stocks, &positions, "AAPL", &acquired, &datetime::make_date(2021, 3, 20)), None);
+        assert_eq!(recent_buy(&stocks, &positions, "AAPL", &acquired, &datetime::make_date(2021, 1, 20)), None);
+        assert_eq!(recent_buy(&stocks, &positions, "MSFT", &acquired, &datetime::make_date(2021, 3, 1)), None);
+    }
+
+    fn make_stock(symbol: &str, date: SPDate, quantity: Quantity, price: Price) -> Stock {
+        let mut stock = Stock::new(String::from(symbol), StockType::Cash, date, quantity, price);
+        stock.set_latest_price(price, date);
+        stock
+    }
+
+    fn make_position(symbol: &str, base_date: SPDate, exit_date: SPDate, quantity: &str, base_price: &str, exit_price: &str) -> ClosedPosition {
+        ClosedPosition::new(String::from(symbol),
+                            StockType::Cash,
+                            base_date,
+                            exit_date,
+                            FixedPrice::from_string(quantity),
+                            FixedPrice::from_string(base_price),
+                            FixedPrice::from_string(exit_price),
+                            FixedPrice::from_string("0.00"),
+                            FixedPrice::from_string("0.00"),
+                            FixedPrice::from_string("0.00"))
+    }
+}
@@ -0,0 +1,6 @@
+pub mod provider_trait;
+pub mod provider_type;
+pub mod providers;
+
+pub mod prv_local_provider;
+pub mod prv_yahoo_provider;
@@ -0,0 +1,11 @@
+use crate::util::datetime::SPDate;
+use crate::util::error::Error;
+
+// Market data source used to populate the datastore and update stock prices.
+// Each function returns CSV content in the format accepted by the datastore History, Dividends
+// and Splits parse_csv functions, for dates in the half open range [from_date, to_date).
+pub trait MarketDataProvider: Send + Sync {
+    fn history(&self, symbol: &str, from_date: &SPDate, to_date: &SPDate) -> Result<String, Error>;
+    fn dividends(&self, symbol: &str, from_date: &SPDate, to_date: &SPDate) -> Result<String, Error>;
+    fn splits(&self, symbol: &str, from_date: &SPDate, to_date: &SPDate) -> Result<String, Error>;
+}
@@ -0,0 +1,55 @@
+use std::fmt;
+use crate::util::error::Error;
+
+#[derive(Debug, Copy, Clone)]
+#[derive(PartialOrd, Ord, PartialEq, Eq)]
+pub enum ProviderType {
+    Yahoo, // Yahoo Finance
+    Local  // Local CSV Files
+}
+
+pub fn providertype2str(pt: ProviderType) -> &'static str {
+    match pt {
+        ProviderType::Yahoo => "yahoo",
+        ProviderType::Local => "local"
+    }
+}
+
+pub fn str2providertype(ptstr: &str) -> Result<ProviderType, Error> {
+    match ptstr.to_lowercase().as_str() {
+        "yahoo" => Ok(ProviderType::Yahoo),
+        "local" => Ok(ProviderType::Local),
+        _ => Err(format!("Unknown provider type '{}'", ptstr).into())
+    }
+}
+
+impl fmt::Display for ProviderType {
+    fn fmt(self: &ProviderType, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", providertype2str(*self))
+    }
+}
+
+// --------------------------------------------------------------------------------
+// Unit Tests
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_provider_type() {
+        let yahoo = ProviderType::Yahoo;
+        let local = ProviderType::Local;
+        let yahoo_str = "yahoo";
+        let local_str = "local";
+
+        assert_eq!(providertype2str(yahoo), yahoo_str);
+        assert_eq!(providertype2str(local), local_str);
+        assert!(str2providertype(yahoo_str).unwrap() == yahoo);
+        assert!(str2providertype(local_str).unwrap() == local);
+        assert!(str2providertype("YAHOO").unwrap() == yahoo);
+
+        let err = str2providertype("foobar").unwrap_err();
+        assert_eq!(format!("{}", err), "Unknown provider type 'foobar'");
+    }
+}
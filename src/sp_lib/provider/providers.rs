@@ -0,0 +1,19 @@
+use crate::portfolio::stocks_config::StocksConfig;
+use crate::provider::provider_trait::MarketDataProvider;
+use crate::provider::provider_type::ProviderType;
+use crate::util::error::Error;
+
+use crate::provider::prv_local_provider::LocalProvider;
+use crate::provider::prv_yahoo_provider::YahooProvider;
+
+pub fn make_provider(config: &StocksConfig) -> Result<Box<dyn MarketDataProvider>, Error> {
+    match config.provider() {
+        ProviderType::Yahoo => Ok(Box::new(YahooProvider::new(config.http_config().clone()))),
+        ProviderType::Local => {
+            if config.provider_root().is_empty() {
+                return Err("Missing provider_root for local provider".into());
+            }
+            Ok(Box::new(LocalProvider::new(config.provider_root())))
+        }
+    }
+}
@@ -0,0 +1,146 @@
+use std::fs;
+use std::path::PathBuf;
+
+use crate::provider::provider_trait::MarketDataProvider;
+use crate::datastore::{history, dividends, splits};
+use crate::util::datetime;
+use crate::util::datetime::SPDate;
+use crate::util::error::Error;
+
+// Reads market data from a local directory tree laid out as
+// <root>/history/<symbol>.csv, <root>/dividends/<symbol>.csv and <root>/splits/<symbol>.csv
+pub struct LocalProvider {
+    root: PathBuf
+}
+
+impl LocalProvider {
+    pub fn new(root: &str) -> Self {
+        LocalProvider {
+            root: PathBuf::from(root)
+        }
+    }
+
+    fn make_symbol_file(&self, tag: &str, symbol: &str) -> PathBuf {
+        let mut pbuf = self.root.clone();
+        pbuf.push(tag);
+        pbuf.push(format!("{}.csv", symbol));
+        pbuf
+    }
+
+    fn select(&self, tag: &str, symbol: &str, from_date: &SPDate, to_date: &SPDate, required: bool) -> Result<String, Error> {
+        let sym_file = self.make_symbol_file(tag, symbol);
+        if !sym_file.exists() {
+            if required {
+                return Err(format!("Local {} file '{}' does not exist", tag, sym_file.display()).into());
+            }
+            return Ok(String::new());
+        }
+
+        let content = fs::read_to_string(&sym_file)?;
+        filter_csv(&content, from_date, to_date)
+    }
+}
+
+impl MarketDataProvider for LocalProvider {
+    fn history(&self, symbol: &str, from_date: &SPDate, to_date: &SPDate) -> Result<String, Error> {
+        self.select(history::tag(), symbol, from_date, to_date, true)
+    }
+
+    fn dividends(&self, symbol: &str, from_date: &SPDate, to_date: &SPDate) -> Result<String, Error> {
+        self.select(dividends::tag(), symbol, from_date, to_date, false)
+    }
+
+    fn splits(&self, symbol: &str, from_date: &SPDate, to_date: &SPDate) -> Result<String, Error> {
+        self.select(splits::tag(), symbol, from_date, to_date, false)
+    }
+}
+
+// --------------------------------------------------------------------------------
+// Private
+
+fn filter_csv(content: &str, from_date: &SPDate, to_date: &SPDate) -> Result<String, Error> {
+    let mut result = String::new();
+    for line in content.lines() {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+
+        if line.starts_with(char::is_alphabetic) {
+            // Keep header
+            if result.is_empty() {
+                result.push_str(line);
+                result.push('\n');
+            }
+            continue;
+        }
+
+        let date_str = match line.find(',') {
+            Some(pos) => &line[..pos],
+            None => line
+        };
+        let date = datetime::parse_date(date_str.trim())?;
+        if date >= *from_date && date < *to_date {
+            result.push_str(line);
+            result.push('\n');
+        }
+    }
+    Ok(result)
+}
+
+// --------------------------------------------------------------------------------
+// Unit Tests
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::datetime::make_date;
+    use crate::util::temp_file;
+
+    #[test]
+    fn test_local_provider() {
+        let root = temp_file::make_path("sp_test_local_provider");
+        if root.exists() {
+            fs::remove_dir_all(&root).unwrap();
+        }
+
+        let mut hist_dir = root.clone();
+        hist_dir.push(history::tag());
+        fs::create_dir_all(&hist_dir).unwrap();
+
+        let mut hist_file = hist_dir.clone();
+        hist_file.push("AAPL.csv");
+        fs::write(&hist_file, "Date,Open,High,Low,Close,Adj Close,Volume\n\
+                               2021-02-22,10.0,12.0,8.0,11.0,11.0,10000\n\
+                               2021-02-23,11.0,12.5,9.0,12.0,12.0,12000\n\
+                               2021-02-24,12.0,13.0,11.0,12.5,12.5,11000\n").unwrap();
+
+        let provider = LocalProvider::new(root.to_str().unwrap());
+        let from = make_date(2021, 2, 23);
+        let to = make_date(2021, 2, 24);
+
+        let csv = provider.history("AAPL", &from, &to).unwrap();
+        assert_eq!(csv, "Date,Open,High,Low,Close,Adj Close,Volume\n2021-02-23,11.0,12.5,9.0,12.0,12.0,12000\n");
+
+        let hist = history::History::parse_csv("AAPL", &csv).unwrap();
+        assert_eq!(hist.count(), 1);
+
+        assert!(provider.history("MSFT", &from, &to).is_err());
+        assert_eq!(provider.dividends("AAPL", &from, &to).unwrap(), "");
+        assert_eq!(provider.splits("AAPL", &from, &to).unwrap(), "");
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_filter_csv() {
+        let from = make_date(2021, 2, 22);
+        let to = make_date(2021, 3, 1);
+
+        let csv = "Date,Dividends\n2021-02-01,0.1\n2021-02-22,0.2\n\n2021-03-01,0.3\n";
+        assert_eq!(filter_csv(csv, &from, &to).unwrap(), "Date,Dividends\n2021-02-22,0.2\n");
+
+        let csv = "Date,Dividends\n2021-13-01,0.1\n";
+        assert!(filter_csv(csv, &from, &to).is_err());
+    }
+}
@@ -0,0 +1,45 @@
+use crate::provider::provider_trait::MarketDataProvider;
+use crate::util::datetime::SPDate;
+use crate::util::error::Error;
+use crate::yfinance::http::HttpConfig;
+use crate::yfinance::query::HistoryQuery;
+use crate::yfinance::types::{Interval, Events};
+
+pub struct YahooProvider {
+    http: HttpConfig
+}
+
+impl YahooProvider {
+    pub fn new(http: HttpConfig) -> Self {
+        YahooProvider { http }
+    }
+}
+
+impl MarketDataProvider for YahooProvider {
+    fn history(&self, symbol: &str, from_date: &SPDate, to_date: &SPDate) -> Result<String, Error> {
+        run_query(symbol, from_date, to_date, Events::History, &self.http)
+    }
+
+    fn dividends(&self, symbol: &str, from_date: &SPDate, to_date: &SPDate) -> Result<String, Error> {
+        run_query(symbol, from_date, to_date, Events::Dividend, &self.http)
+    }
+
+    fn splits(&self, symbol: &str, from_date: &SPDate, to_date: &SPDate) -> Result<String, Error> {
+        run_query(symbol, from_date, to_date, Events::Split, &self.http)
+    }
+}
+
+// --------------------------------------------------------------------------------
+// Private
+
+fn run_query(symbol: &str, from_date: &SPDate, to_date: &SPDate, events: Events, http: &HttpConfig) -> Result<String, Error> {
+    let mut query = HistoryQuery::new(
+        symbol.to_string(),
+        *from_date,
+        *to_date,
+        Interval::Daily,
+        events);
+
+    query.execute_with(http)?;
+    Ok(query.result)
+}
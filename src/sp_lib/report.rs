@@ -3,10 +3,18 @@ pub mod report_trait;
 pub mod report_type;
 pub mod reports;
 
+pub mod rpt_bench_report;
 pub mod rpt_closed_report;
 pub mod rpt_daych_report;
 pub mod rpt_divid_report;
+pub mod rpt_harvest_report;
+pub mod rpt_hist_report;
+pub mod rpt_perf_report;
+pub mod rpt_rebal_report;
+pub mod rpt_risk_report;
 pub mod rpt_sum_report;
+pub mod rpt_tax_report;
 pub mod rpt_top_report;
 pub mod rpt_value_report;
 pub mod rpt_volat_report;
+pub mod rpt_wash_report;
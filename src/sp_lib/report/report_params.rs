@@ -1,14 +1,27 @@
+use std::collections::HashMap;
+
 use crate::datastore::datastore::DataStore;
 use crate::portfolio::closed_position::ClosedPositionList;
 use crate::portfolio::stock::StockList;
 use crate::portfolio::stocks_config::StocksConfig;
 use crate::report::report_type::ReportType;
+use crate::util::datetime::{Period, SPDate};
 
 pub struct ReportParams<'a, 'b> {
     rtype: ReportType,
     config: &'a StocksConfig,
+    all_lots: Option<(&'a StockList, &'a ClosedPositionList)>,
     ds: Option<&'b DataStore>,
-    groupby: bool
+    groupby: bool,
+    from: Option<SPDate>,
+    to: Option<SPDate>,
+    period: Period,
+    bench: Option<String>,
+    rf_rate: f64,
+    min_loss: f64,
+    min_pct_loss: f64,
+    cash_only: bool,
+    currencies: HashMap<String, String>
 }
 
 impl<'a, 'b> ReportParams<'a, 'b> {
@@ -16,8 +29,18 @@ impl<'a, 'b> ReportParams<'a, 'b> {
         ReportParams {
             rtype,
             config,
+            all_lots: None,
             ds: None,
-            groupby: false
+            groupby: false,
+            from: None,
+            to: None,
+            period: Period::Daily,
+            bench: None,
+            rf_rate: 0.0,
+            min_loss: 0.0,
+            min_pct_loss: 0.0,
+            cash_only: false,
+            currencies: HashMap::new()
         }
     }
 
@@ -26,11 +49,54 @@ impl<'a, 'b> ReportParams<'a, 'b> {
         self
     }
 
+    // Stocks and closed positions before the config ones were filtered
+    pub fn with_all_lots(mut self, stocks: &'a StockList, positions: &'a ClosedPositionList) -> Self {
+        self.all_lots = Some((stocks, positions));
+        self
+    }
+
     pub fn with_datastore(mut self, ds: &'b DataStore) -> Self {
         self.ds = Some(ds);
         self
     }
 
+    pub fn with_date_range(mut self, from: Option<SPDate>, to: Option<SPDate>) -> Self {
+        self.from = from;
+        self.to = to;
+        self
+    }
+
+    pub fn with_period(mut self, period: Period) -> Self {
+        self.period = period;
+        self
+    }
+
+    pub fn with_benchmark(mut self, bench: Option<&str>) -> Self {
+        self.bench = bench.map(String::from);
+        self
+    }
+
+    pub fn with_risk_free_rate(mut self, rf_rate: f64) -> Self {
+        self.rf_rate = rf_rate;
+        self
+    }
+
+    pub fn with_loss_thresholds(mut self, min_loss: f64, min_pct_loss: f64) -> Self {
+        self.min_loss = min_loss;
+        self.min_pct_loss = min_pct_loss;
+        self
+    }
+
+    pub fn with_cash_only(mut self, cash_only: bool) -> Self {
+        self.cash_only = cash_only;
+        self
+    }
+
+    pub fn with_currencies(mut self, currencies: &HashMap<String, String>) -> Self {
+        self.currencies = currencies.clone();
+        self
+    }
+
     #[inline(always)]
     pub fn rtype(&self) -> ReportType { self.rtype }
 
@@ -43,9 +109,49 @@ impl<'a, 'b> ReportParams<'a, 'b> {
     #[inline(always)]
     pub fn closed_positions(&self) -> &'a ClosedPositionList { self.config.closed_positions() }
 
+    // Unfiltered stocks, the config stocks when not set
+    #[inline(always)]
+    pub fn all_stocks(&self) -> &'a StockList { self.all_lots.map_or(self.config.stocks(), |(stocks, _)| stocks) }
+
+    // Unfiltered closed positions, the config closed positions when not set
+    #[inline(always)]
+    pub fn all_closed_positions(&self) -> &'a ClosedPositionList { self.all_lots.map_or(self.config.closed_positions(), |(_, positions)| positions) }
+
     #[inline(always)]
     pub fn datastore(&self) -> Option<&'b DataStore> { self.ds }
 
     #[inline(always)]
     pub fn groupby(&self) -> bool { self.groupby }
+
+    #[inline(always)]
+    pub fn from_date(&self) -> Option<SPDate> { self.from }
+
+    #[inline(always)]
+    pub fn to_date(&self) -> Option<SPDate> { self.to }
+
+    #[inline(always)]
+    pub fn period(&self) -> Period { self.period }
+
+    #[inline(always)]
+    pub fn benchmark(&self) -> Option<&str> { self.bench.as_deref() }
+
+    // Annual risk-free rate in percent
+    #[inline(always)]
+    pub fn risk_free_rate(&self) -> f64 { self.rf_rate }
+
+    // Minimum lot loss
+    #[inline(always)]
+    pub fn min_loss(&self) -> f64 { self.min_loss }
+
+    // Minimum lot loss in percent
+    #[inline(always)]
+    pub fn min_pct_loss(&self) -> f64 { self.min_pct_loss }
+
+    // Rebalance using cash only, without sales
+    #[inline(always)]
+    pub fn cash_only(&self) -> bool { self.cash_only }
+
+    // Listing currency of symbols in a foreign currency, before their prices were converted to base
+    #[inline(always)]
+    pub fn currencies(&self) -> &HashMap<String, String> { &self.currencies }
 }
@@ -11,6 +11,14 @@ pub enum ReportType {
     Closed, // Closed Positions Value
     Divid,  // Dividend
     Sum,    // Summary
+    Perf,   // Performance
+    Hist,   // Value History
+    Bench,  // Benchmark Comparison
+    Risk,   // Risk Metrics
+    Tax,    // Tax Lots
+    Wash,   // Wash Sales
+    Harvest, // Tax-Loss Harvesting
+    Rebal,  // Rebalancing
 }
 
 pub fn reporttype2str(rt: ReportType) -> &'static str {
@@ -21,7 +29,15 @@ pub fn reporttype2str(rt: ReportType) -> &'static str {
         ReportType::Daych => "daych",
         ReportType::Closed => "closed",
         ReportType::Divid => "divid",
-        ReportType::Sum => "sum"
+        ReportType::Sum => "sum",
+        ReportType::Perf => "perf",
+        ReportType::Hist => "hist",
+        ReportType::Bench => "bench",
+        ReportType::Risk => "risk",
+        ReportType::Tax => "tax",
+        ReportType::Wash => "wash",
+        ReportType::Harvest => "harvest",
+        ReportType::Rebal => "rebal"
     }
 }
 
@@ -34,6 +50,14 @@ pub fn str2reporttype(rtstr: &str) -> Result<ReportType, Error> {
         "closed" => Ok(ReportType::Closed),
         "divid" => Ok(ReportType::Divid),
         "sum" => Ok(ReportType::Sum),
+        "perf" => Ok(ReportType::Perf),
+        "hist" => Ok(ReportType::Hist),
+        "bench" => Ok(ReportType::Bench),
+        "risk" => Ok(ReportType::Risk),
+        "tax" => Ok(ReportType::Tax),
+        "wash" => Ok(ReportType::Wash),
+        "harvest" => Ok(ReportType::Harvest),
+        "rebal" => Ok(ReportType::Rebal),
         _ => Err(format!("Unknown report type '{}'", rtstr).into())
     }
 }
@@ -60,6 +84,14 @@ mod tests {
         let closed = ReportType::Closed;
         let divid = ReportType::Divid;
         let sum = ReportType::Sum;
+        let perf = ReportType::Perf;
+        let hist = ReportType::Hist;
+        let bench = ReportType::Bench;
+        let risk = ReportType::Risk;
+        let tax = ReportType::Tax;
+        let wash = ReportType::Wash;
+        let harvest = ReportType::Harvest;
+        let rebal = ReportType::Rebal;
         let value_str = "value";
         let top_str = "top";
         let volat_str = "volat";
@@ -67,6 +99,14 @@ mod tests {
         let closed_str = "closed";
         let divid_str = "divid";
         let sum_str = "sum";
+        let perf_str = "perf";
+        let hist_str = "hist";
+        let bench_str = "bench";
+        let risk_str = "risk";
+        let tax_str = "tax";
+        let wash_str = "wash";
+        let harvest_str = "harvest";
+        let rebal_str = "rebal";
 
         assert_eq!(reporttype2str(value), value_str);
         assert_eq!(reporttype2str(top), top_str);
@@ -75,6 +115,14 @@ mod tests {
         assert_eq!(reporttype2str(closed), closed_str);
         assert_eq!(reporttype2str(divid), divid_str);
         assert_eq!(reporttype2str(sum), sum_str);
+        assert_eq!(reporttype2str(perf), perf_str);
+        assert_eq!(reporttype2str(hist), hist_str);
+        assert_eq!(reporttype2str(bench), bench_str);
+        assert_eq!(reporttype2str(risk), risk_str);
+        assert_eq!(reporttype2str(tax), tax_str);
+        assert_eq!(reporttype2str(wash), wash_str);
+        assert_eq!(reporttype2str(harvest), harvest_str);
+        assert_eq!(reporttype2str(rebal), rebal_str);
         assert!(str2reporttype(&value_str).unwrap() == value);
         assert!(str2reporttype(&top_str).unwrap() == top);
         assert!(str2reporttype(&volat_str).unwrap() == volat);
@@ -82,6 +130,14 @@ mod tests {
         assert!(str2reporttype(&closed_str).unwrap() == closed);
         assert!(str2reporttype(&divid_str).unwrap() == divid);
         assert!(str2reporttype(&sum_str).unwrap() == sum);
+        assert!(str2reporttype(&perf_str).unwrap() == perf);
+        assert!(str2reporttype(&hist_str).unwrap() == hist);
+        assert!(str2reporttype(&bench_str).unwrap() == bench);
+        assert!(str2reporttype(&risk_str).unwrap() == risk);
+        assert!(str2reporttype(&tax_str).unwrap() == tax);
+        assert!(str2reporttype(&wash_str).unwrap() == wash);
+        assert!(str2reporttype(&harvest_str).unwrap() == harvest);
+        assert!(str2reporttype(&rebal_str).unwrap() == rebal);
 
         match str2reporttype("foobar") {
             Ok(_) => assert!(false),
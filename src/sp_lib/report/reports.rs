@@ -3,13 +3,21 @@ use crate::report::report_params::ReportParams;
 use crate::report::report_trait::Report;
 use crate::report::report_type::ReportType;
 
+use crate::report::rpt_bench_report::BenchReport;
 use crate::report::rpt_closed_report::ClosedReport;
 use crate::report::rpt_daych_report::DaychReport;
 use crate::report::rpt_divid_report::DividReport;
+use crate::report::rpt_harvest_report::HarvestReport;
+use crate::report::rpt_hist_report::HistReport;
+use crate::report::rpt_perf_report::PerfReport;
+use crate::report::rpt_rebal_report::RebalReport;
+use crate::report::rpt_risk_report::RiskReport;
 use crate::report::rpt_sum_report::SumReport;
+use crate::report::rpt_tax_report::TaxReport;
 use crate::report::rpt_top_report::TopReport;
 use crate::report::rpt_value_report::ValueReport;
 use crate::report::rpt_volat_report::VolatReport;
+use crate::report::rpt_wash_report::WashReport;
 
 pub fn print_report(params: ReportParams) {
     make_report(params.rtype()).write(&params)
@@ -27,6 +35,14 @@ fn make_report(rtype: ReportType) -> Box<dyn Report> {
         ReportType::Daych => Box::new(DaychReport{}),
         ReportType::Closed => Box::new(ClosedReport{}),
         ReportType::Divid => Box::new(DividReport{}),
-        ReportType::Sum => Box::new(SumReport{})
+        ReportType::Sum => Box::new(SumReport{}),
+        ReportType::Perf => Box::new(PerfReport{}),
+        ReportType::Hist => Box::new(HistReport{}),
+        ReportType::Bench => Box::new(BenchReport{}),
+        ReportType::Risk => Box::new(RiskReport{}),
+        ReportType::Tax => Box::new(TaxReport{}),
+        ReportType::Wash => Box::new(WashReport{}),
+        ReportType::Harvest => Box::new(HarvestReport{}),
+        ReportType::Rebal => Box::new(RebalReport{})
     }
 }
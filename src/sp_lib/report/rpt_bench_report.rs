@@ -0,0 +1,117 @@
+use std::fs::File;
+use std::io::prelude::*;
+
+use crate::datastore::datastore::DataStore;
+use crate::portfolio::benchmark::{self, Comparison, ComparisonList};
+use crate::portfolio::performance::MarketHistory;
+use crate::report::report_params::ReportParams;
+use crate::report::report_trait::Report;
+use crate::util::datetime;
+use crate::util::error::Error;
+
+pub struct BenchReport {}
+
+impl Report for BenchReport {
+    fn write(&self, params: &ReportParams) {
+        let ds = params.datastore().expect("Bench report missing datastore");
+        let (bench, lots) = match calc_lots(params, ds) {
+            Ok(table) => table,
+            Err(e) => {
+                eprintln!("Bench report failed - {}", e);
+                return;
+            }
+        };
+
+        println!("Benchmark Comparison Report");
+        println!("---------------------------");
+        println!("            Date: {}", datetime::today().format("%Y-%m-%d"));
+        println!("       Benchmark: {}", bench);
+        println!("Number of Stocks: {}", lots.len());
+        if let Some(total) = benchmark::compare_total(&lots, "Total") {
+            println!("      Base Value: {:.2}", total.cost);
+            println!("    Latest Value: {:.2}", total.value);
+            println!("     Bench Value: {:.2}", total.bench_value);
+            println!("  Percent Change: {:.2}", total.pct_change());
+            println!("    Bench Change: {:.2}", total.bench_pct_change());
+            println!("      Excess Pct: {:.2}", total.excess_pct());
+        }
+        println!();
+
+        write_table("Symbol", &lots);
+        println!();
+        write_table("GroupBy", &benchmark::compare_by_symbol(&lots));
+    }
+
+    fn export(&self, params: &ReportParams, filename: &str) -> Result<(), Error> {
+        let ds = params.datastore().expect("Bench export missing datastore");
+        let (bench, lots) = calc_lots(params, ds)?;
+        let groups = benchmark::compare_by_symbol(&lots);
+        let total = benchmark::compare_total(&lots, "Total");
+
+        let mut file = File::create(filename)?;
+        writeln!(file, "Level,Symbol,Buy Date,Base Value,Cur Value,Pct,Bench,Bench Value,Bench Pct,Excess Pct")?;
+        let rows = lots.iter().map(|cmp| ("lot", cmp))
+            .chain(groups.iter().map(|cmp| ("symbol", cmp)))
+            .chain(total.iter().map(|cmp| ("total", cmp)));
+        for (level, cmp) in rows {
+            writeln!(file, "{},{},{},{:.2},{:.2},{:.2},{},{:.2},{:.2},{:.2}",
+                     level,
+                     cmp.symbol,
+                     cmp.date.format("%Y-%m-%d"),
+                     cmp.cost,
+                     cmp.value,
+                     cmp.pct_change(),
+                     bench,
+                     cmp.bench_value,
+                     cmp.bench_pct_change(),
+                     cmp.excess_pct())?;
+        }
+        Ok(())
+    }
+}
+
+// --------------------------------------------------------------------------------
+// Private
+
+fn calc_lots<'a>(params: &'a ReportParams, ds: &DataStore) -> Result<(&'a str, ComparisonList), Error> {
+    let bench = match params.benchmark() {
+        Some(bench) => bench,
+        None => return Err("Missing benchmark symbol".into())
+    };
+
+    let mut mkt = MarketHistory::new();
+    mkt.add_symbol_from_ds(ds, bench, &datetime::today())?;
+    Ok((bench, benchmark::compare_lots(params.stocks(), &mkt, bench)?))
+}
+
+fn write_table(name: &str, rows: &[Comparison]) {
+    println!("{:8} {:10} {:12} {:12} {:8} {:12} {:8} {:8}",
+             name,
+             "Buy Date",
+             "Base Value",
+             "Cur Value",
+             "Pct",
+             "Bench Value",
+             "Bench",
+             "Excess");
+    println!("{:8} {:10} {:12} {:12} {:8} {:12} {:8} {:8}",
+             "-".repeat(name.len()),
+             "--------",
+             "----------",
+             "---------",
+             "---",
+             "-----------",
+             "-----",
+             "------");
+    for cmp in rows {
+        println!("{:8} {:10} {:12.2} {:12.2} {:8.2} {:12.2} {:8.2} {:8.2}",
+                 cmp.symbol,
+                 cmp.date.format("%Y-%m-%d"),
+                 cmp.cost,
+                 cmp.value,
+                 cmp.pct_change(),
+                 cmp.bench_value,
+                 cmp.bench_pct_change(),
+                 cmp.excess_pct());
+    }
+}
@@ -1,3 +1,4 @@
+use std::collections::BTreeMap;
 use std::fs::File;
 use std::io::prelude::*;
 
@@ -31,6 +32,7 @@ impl Report for ClosedReport {
         println!("Closed Positions Report");
         println!("-----------------------");
         println!("            Date: {}", datetime::today().format("%Y-%m-%d"));
+        println!("        Currency: {}", params.config().base_currency());
         println!("Total Base Value: {}", base_ntnl.to_formatted(2));
         println!("Total Exit Value: {}", exit_ntnl.to_formatted(2));
         println!(" Total Net Value: {}", net_ntnl.to_formatted(2));
@@ -69,6 +71,36 @@ impl Report for ClosedReport {
                      (pos.base_fee + pos.exit_fee).to_formatted(2),
                      pos.dividend.to_formatted(2));
         }
+
+        if positions.iter().any(|pos| !pos.account.is_empty()) {
+            println!();
+            println!("{:12} {:9} {:12} {:12} {:12} {:6} {:10}",
+                     "Account", "Positions", "Base Value", "Exit Value", "Net Value", "Fees", "Dividend");
+            println!("{:12} {:9} {:12} {:12} {:12} {:6} {:10}",
+                     "-------", "---------", "----------", "----------", "---------", "----", "--------");
+
+            let mut accounts: BTreeMap<&str, (usize, Price, Price, Price, Price, Price)> = BTreeMap::new();
+            for pos in positions.iter() {
+                let entry = accounts.entry(pos.account.as_str()).or_insert((0, FP_0, FP_0, FP_0, FP_0, FP_0));
+                entry.0 += 1;
+                entry.1 += pos.base_notional();
+                entry.2 += pos.exit_notional();
+                entry.3 += pos.net_notional();
+                entry.4 += pos.base_fee + pos.exit_fee;
+                entry.5 += pos.dividend;
+            }
+
+            for (name, (count, base_value, exit_value, net_value, fees, dividend)) in accounts.iter() {
+                println!("{:12} {:>9} {:>12} {:>12} {:>12} {:>6} {:>10}",
+                         if name.is_empty() { "-" } else { name },
+                         count,
+                         base_value.to_formatted(2),
+                         exit_value.to_formatted(2),
+                         net_value.to_formatted(2),
+                         fees.to_formatted(2),
+                         dividend.to_formatted(2));
+            }
+        }
     }
 
     fn export(&self, params: &ReportParams, filename: &str) -> Result<(), Error> {
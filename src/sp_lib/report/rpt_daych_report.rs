@@ -143,7 +143,7 @@ fn calc_daych(stock: &Stock, ds: &DataStore) -> Option<DayChange> {
                 price: entries[1].adj_close,
                 change: delta,
                 pct_change: 100.0 * if prev_price > 0.0 { delta / prev_price } else { 0.00 },
-                val_change: if stock.stype != StockType::Index { stock.quantity as Price * delta } else { 0.0 },
+                val_change: if stock.stype != StockType::Index { stock.quantity * delta } else { 0.0 },
                 low: entries[1].low,
                 high: entries[1].high,
                 volume: entries[1].volume
@@ -7,6 +7,7 @@ use crate::report::report_params::ReportParams;
 use crate::report::report_trait::Report;
 use crate::util::datetime;
 use crate::util::error::Error;
+use crate::util::price_type::quantity_format;
 
 pub struct DividReport {}
 
@@ -22,6 +23,7 @@ impl Report for DividReport {
         println!("----------------------");
         println!("            Date: {}", datetime::today().format("%Y-%m-%d"));
         println!("Number of Stocks: {}", stocks.len());
+        println!("        Currency: {}", params.config().base_currency());
         println!(" Latest Dividend: {:.2}", algorithms::latest_dividend(stocks));
         println!("    Cum Dividend: {:.2}", cum_div);
         println!("Cum Dividend Ret: {:.2}", 100.0 * cum_div / bas_val);
@@ -52,12 +54,12 @@ impl Report for DividReport {
                  "------------",
                  "-----------");
         for stock in stocks.iter() {
-            println!("{:8} {:10} {:10} {:6} {:8} {:10} {:10.2} {:10.2} {:10.2} {:12.6} {:11.2}",
+            println!("{:8} {:10} {:10} {:6} {:>8} {:10} {:10.2} {:10.2} {:10.2} {:12.6} {:11.2}",
                      stock.symbol,
                      stock.date.format("%Y-%m-%d"),
                      stock.latest_date.format("%Y-%m-%d"),
                      stock.days_held,
-                     stock.quantity,
+                     quantity_format(stock.quantity),
                      stock.latest_div_date.format("%Y-%m-%d"),
                      stock.latest_dividend(),
                      stock.cum_dividend,
@@ -79,7 +81,7 @@ impl Report for DividReport {
                 seen.insert(&stock.symbol);
 
                 let size_prices = groupby.get(&stock.symbol).unwrap();
-                println!("{:8} {:8} {:10.2} {:11.2}", stock.symbol, size_prices.0, size_prices.1, 100.0 * size_prices.1 / size_prices.2);
+                println!("{:8} {:>8} {:10.2} {:11.2}", stock.symbol, quantity_format(size_prices.0), size_prices.1, 100.0 * size_prices.1 / size_prices.2);
             }
         }
     }
@@ -94,7 +96,7 @@ impl Report for DividReport {
                      stock.date.format("%Y-%m-%d"),
                      stock.latest_date.format("%Y-%m-%d"),
                      stock.days_held,
-                     stock.quantity,
+                     quantity_format(stock.quantity),
                      stock.latest_div_date.format("%Y-%m-%d"),
                      stock.latest_dividend(),
                      stock.cum_dividend,
@@ -0,0 +1,111 @@
+use std::fs::File;
+use std::io::prelude::*;
+
+use crate::portfolio::harvest::{self, HarvestCandidate, HarvestCandidateList};
+use crate::portfolio::tax_lots::{self, HoldingTerm};
+use crate::report::report_params::ReportParams;
+use crate::report::report_trait::Report;
+use crate::util::datetime;
+use crate::util::error::Error;
+use crate::util::price_type::quantity_format;
+
+pub struct HarvestReport {}
+
+impl Report for HarvestReport {
+    fn write(&self, params: &ReportParams) {
+        let stocks = params.stocks();
+        let candidates = candidates(params);
+
+        let loss_of = |term: HoldingTerm| candidates.iter().filter(|c| c.term == term).fold(0.0, |total, c| total + c.loss);
+        let short_loss = loss_of(HoldingTerm::Short);
+        let long_loss = loss_of(HoldingTerm::Long);
+
+        println!("Tax-Loss Harvesting Report");
+        println!("--------------------------");
+        println!("            Date: {}", datetime::today().format("%Y-%m-%d"));
+        println!("        Currency: {}", params.config().base_currency());
+        println!("        Min Loss: {:.2}", params.min_loss());
+        println!("    Min Loss Pct: {:.2}", params.min_pct_loss());
+        println!("      Candidates: {}", candidates.len());
+        println!("  Wash Sale Risk: {}", candidates.iter().filter(|c| c.wash_buy.is_some()).count());
+        println!(" Short Term Loss: {:.2}", short_loss);
+        println!("  Long Term Loss: {:.2}", long_loss);
+        println!("      Total Loss: {:.2}", short_loss + long_loss);
+        println!();
+
+        println!("{:8} {:10} {:10} {:>8} {:12} {:12} {:10} {:8} {:5} {:10}",
+                 "Symbol",
+                 "Buy Date",
+                 "Upd Date",
+                 "Size",
+                 "Base Value",
+                 "Cur Value",
+                 "Loss",
+                 "Loss Pct",
+                 "Term",
+                 "Wash Buy");
+        println!("{:8} {:10} {:10} {:>8} {:12} {:12} {:10} {:8} {:5} {:10}",
+                 "------",
+                 "--------",
+                 "--------",
+                 "----",
+                 "----------",
+                 "---------",
+                 "----",
+                 "--------",
+                 "----",
+                 "--------");
+        for candidate in candidates.iter() {
+            let stock = &stocks[candidate.index];
+            println!("{:8} {:10} {:10} {:>8} {:12.2} {:12.2} {:10.2} {:8.2} {:5} {:10}",
+                     stock.symbol,
+                     stock.date.format("%Y-%m-%d"),
+                     stock.latest_date.format("%Y-%m-%d"),
+                     quantity_format(stock.quantity),
+                     stock.base_notional(),
+                     stock.latest_notional(),
+                     candidate.loss,
+                     candidate.pct_loss,
+                     tax_lots::term2str(candidate.term),
+                     wash_buy_format(candidate));
+        }
+    }
+
+    fn export(&self, params: &ReportParams, filename: &str) -> Result<(), Error> {
+        let stocks = params.stocks();
+        let candidates = candidates(params);
+
+        let mut file = File::create(filename)?;
+        writeln!(file, "Symbol,Buy Date,Upd Date,Size,Base Value,Cur Value,Loss,Loss Pct,Term,Wash Buy")?;
+        for candidate in candidates.iter() {
+            let stock = &stocks[candidate.index];
+            writeln!(file, "{},{},{},{},{:.2},{:.2},{:.2},{:.2},{},{}",
+                     stock.symbol,
+                     stock.date.format("%Y-%m-%d"),
+                     stock.latest_date.format("%Y-%m-%d"),
+                     quantity_format(stock.quantity),
+                     stock.base_notional(),
+                     stock.latest_notional(),
+                     candidate.loss,
+                     candidate.pct_loss,
+                     tax_lots::term2str(candidate.term),
+                     candidate.wash_buy.map(|date| date.format("%Y-%m-%d").to_string()).unwrap_or_default())?;
+        }
+        Ok(())
+    }
+}
+
+// --------------------------------------------------------------------------------
+// Private
+
+#[inline(always)]
+fn candidates(params: &ReportParams) -> HarvestCandidateList {
+    harvest::harvest_candidates(params.stocks(), params.all_stocks(), params.all_closed_positions(), params.min_loss(), params.min_pct_loss())
+}
+
+fn wash_buy_format(candidate: &HarvestCandidate) -> String {
+    match candidate.wash_buy {
+        Some(date) => date.format("%Y-%m-%d").to_string(),
+        None => String::from("-")
+    }
+}
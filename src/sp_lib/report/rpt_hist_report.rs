@@ -0,0 +1,107 @@
+use std::fs::File;
+use std::io::prelude::*;
+
+use crate::datastore::datastore::DataStore;
+use crate::portfolio::currency;
+use crate::portfolio::performance::{self, MarketHistory, ValuePoint};
+use crate::report::report_params::ReportParams;
+use crate::report::report_trait::Report;
+use crate::util::datetime;
+use crate::util::error::Error;
+
+pub struct HistReport {}
+
+impl Report for HistReport {
+    fn write(&self, params: &ReportParams) {
+        let ds = params.datastore().expect("Hist report missing datastore");
+        let series = match calc_series(params, ds) {
+            Ok(series) => series,
+            Err(e) => {
+                eprintln!("Hist report failed - {}", e);
+                return;
+            }
+        };
+
+        println!("Portfolio Value History");
+        println!("-----------------------");
+        println!("            Date: {}", datetime::today().format("%Y-%m-%d"));
+        println!("          Period: {}", datetime::period2str(params.period()));
+        println!("          Points: {}", series.len());
+        println!();
+
+        println!("{:10} {:12} {:12} {:12} {:12} {:8} {:12} {:12}",
+                 "Date",
+                 "Value",
+                 "Cash",
+                 "Cost",
+                 "Unrealized",
+                 "Pct",
+                 "Inflow",
+                 "Outflow");
+        println!("{:10} {:12} {:12} {:12} {:12} {:8} {:12} {:12}",
+                 "----",
+                 "-----",
+                 "----",
+                 "----",
+                 "----------",
+                 "---",
+                 "------",
+                 "-------");
+        for pt in series.iter() {
+            println!("{:10} {:12.2} {:12.2} {:12.2} {:12.2} {:8.2} {:12.2} {:12.2}",
+                     pt.date.format("%Y-%m-%d"),
+                     pt.value,
+                     pt.cash,
+                     pt.cost,
+                     pt.unrealized(),
+                     pt.unrealized_pct(),
+                     pt.inflow,
+                     pt.outflow);
+        }
+    }
+
+    fn export(&self, params: &ReportParams, filename: &str) -> Result<(), Error> {
+        let ds = params.datastore().expect("Hist export missing datastore");
+        let series = calc_series(params, ds)?;
+
+        let mut file = File::create(filename)?;
+        writeln!(file, "Date,Value,Cash,Cost,Unrealized,Unrealized Pct,Inflow,Outflow")?;
+        for pt in series.iter() {
+            writeln!(file, "{},{:.2},{:.2},{:.2},{:.2},{:.2},{:.2},{:.2}",
+                     pt.date.format("%Y-%m-%d"),
+                     pt.value,
+                     pt.cash,
+                     pt.cost,
+                     pt.unrealized(),
+                     pt.unrealized_pct(),
+                     pt.inflow,
+                     pt.outflow)?;
+        }
+        Ok(())
+    }
+}
+
+// --------------------------------------------------------------------------------
+// Private
+
+// Portfolio value series over the report date range, resampled to the report period.
+fn calc_series(params: &ReportParams, ds: &DataStore) -> Result<Vec<ValuePoint>, Error> {
+    let stocks = params.stocks();
+    let positions = params.closed_positions();
+    let to = params.to_date().unwrap_or_else(datetime::today);
+    if let Some(from) = params.from_date() {
+        if from > to {
+            return Err(format!("Invalid date range from {} to {}", from.format("%Y-%m-%d"), to.format("%Y-%m-%d")).into());
+        }
+    }
+
+    let config = params.config();
+    let mut mkt = MarketHistory::from_ds(ds, stocks, positions, &to)?;
+    currency::convert_market_from_ds(&mut mkt, params.currencies(), ds, config.base_currency())?;
+    let series = performance::value_series(stocks, positions, config.cash(), &config.series_cash_flows(), &mkt, &to);
+    let series: Vec<ValuePoint> = match params.from_date() {
+        Some(from) => series.into_iter().filter(|pt| pt.date >= from).collect(),
+        None => series
+    };
+    Ok(performance::resample_series(&series, params.period()))
+}
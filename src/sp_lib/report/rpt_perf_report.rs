@@ -0,0 +1,140 @@
+use std::fs::File;
+use std::io::prelude::*;
+
+use crate::datastore::datastore::DataStore;
+use crate::portfolio::cash_flow::CashFlowList;
+use crate::portfolio::currency;
+use crate::portfolio::performance::{self, MarketHistory, Performance};
+use crate::portfolio::stock_type::StockType;
+use crate::report::report_params::ReportParams;
+use crate::report::report_trait::Report;
+use crate::util::datetime;
+use crate::util::error::Error;
+
+pub struct PerfReport {}
+
+impl Report for PerfReport {
+    fn write(&self, params: &ReportParams) {
+        let ds = params.datastore().expect("Perf report missing datastore");
+        let (total, rows) = match calc_table(params, ds) {
+            Ok(table) => table,
+            Err(e) => {
+                eprintln!("Perf report failed - {}", e);
+                return;
+            }
+        };
+
+        println!("Portfolio Performance Report");
+        println!("----------------------------");
+        println!("            Date: {}", datetime::today().format("%Y-%m-%d"));
+        println!("       From Date: {}", total.from.format("%Y-%m-%d"));
+        println!("         To Date: {}", total.to.format("%Y-%m-%d"));
+        println!("     Start Value: {:.2}", total.start_value);
+        println!("       End Value: {:.2}", total.end_value);
+        println!("          Inflow: {:.2}", total.inflow);
+        println!("         Outflow: {:.2}", total.outflow);
+        println!("        Deposits: {:.2}", total.deposits);
+        println!("     Withdrawals: {:.2}", total.withdrawals);
+        println!("         TWR Pct: {:.2}", 100.0 * total.twr);
+        println!("     TWR Ann Pct: {:.2}", 100.0 * total.twr_annualized());
+        println!("     MWR Ann Pct: {}", mwr_format(&total));
+        println!();
+
+        println!("{:8} {:12} {:12} {:12} {:12} {:8} {:8} {:8}",
+                 "Symbol",
+                 "Start Value",
+                 "End Value",
+                 "Inflow",
+                 "Outflow",
+                 "TWR",
+                 "TWR Ann",
+                 "MWR Ann");
+        println!("{:8} {:12} {:12} {:12} {:12} {:8} {:8} {:8}",
+                 "------",
+                 "-----------",
+                 "---------",
+                 "------",
+                 "-------",
+                 "---",
+                 "-------",
+                 "-------");
+        for (symbol, perf) in rows.iter() {
+            println!("{:8} {:12.2} {:12.2} {:12.2} {:12.2} {:8.2} {:8.2} {:>8}",
+                     symbol,
+                     perf.start_value,
+                     perf.end_value,
+                     perf.inflow,
+                     perf.outflow,
+                     100.0 * perf.twr,
+                     100.0 * perf.twr_annualized(),
+                     mwr_format(perf));
+        }
+    }
+
+    fn export(&self, params: &ReportParams, filename: &str) -> Result<(), Error> {
+        let ds = params.datastore().expect("Perf export missing datastore");
+        let (total, rows) = calc_table(params, ds)?;
+
+        let mut file = File::create(filename)?;
+        writeln!(file, "Symbol,From Date,To Date,Start Value,End Value,Inflow,Outflow,TWR,TWR Ann,MWR Ann")?;
+        for (symbol, perf) in rows.iter().chain([(String::from("Total"), total)].iter()) {
+            writeln!(file, "{},{},{},{:.2},{:.2},{:.2},{:.2},{:.2},{:.2},{}",
+                     symbol,
+                     perf.from.format("%Y-%m-%d"),
+                     perf.to.format("%Y-%m-%d"),
+                     perf.start_value,
+                     perf.end_value,
+                     perf.inflow,
+                     perf.outflow,
+                     100.0 * perf.twr,
+                     100.0 * perf.twr_annualized(),
+                     mwr_format(perf))?;
+        }
+        Ok(())
+    }
+}
+
+// --------------------------------------------------------------------------------
+// Private
+
+// Performance of the whole portfolio and of each symbol over the report date range.
+fn calc_table(params: &ReportParams, ds: &DataStore) -> Result<(Performance, Vec<(String, Performance)>), Error> {
+    let stocks = params.stocks();
+    let positions = params.closed_positions();
+    let to = params.to_date().unwrap_or_else(datetime::today);
+
+    let config = params.config();
+    let mut mkt = MarketHistory::from_ds(ds, stocks, positions, &to)?;
+    currency::convert_market_from_ds(&mut mkt, params.currencies(), ds, config.base_currency())?;
+    let series = performance::value_series(stocks, positions, config.cash(), &config.series_cash_flows(), &mkt, &to);
+    let from = match params.from_date() {
+        Some(date) => date,
+        None => series.first().map(|pt| pt.date).unwrap_or(to)
+    };
+    if from > to {
+        return Err(format!("Invalid date range from {} to {}", from.format("%Y-%m-%d"), to.format("%Y-%m-%d")).into());
+    }
+
+    let mut symbols: Vec<&str> = stocks.iter().filter(|s| s.stype != StockType::Index).map(|s| s.symbol.as_str())
+        .chain(positions.iter().filter(|p| p.stype != StockType::Index).map(|p| p.symbol.as_str()))
+        .collect();
+    symbols.sort_unstable();
+    symbols.dedup();
+
+    // Cash is part of the portfolio, not of any symbol
+    let no_flows = CashFlowList::new();
+    let mut rows: Vec<(String, Performance)> = Vec::new();
+    for symbol in symbols {
+        let sym_series = performance::value_series_if(stocks, positions, &mkt, &to, |sym| sym == symbol);
+        rows.push((String::from(symbol), performance::calc_performance(&sym_series, &no_flows, &from, &to)));
+    }
+
+    Ok((performance::calc_performance(&series, config.cash_flows(), &from, &to), rows))
+}
+
+fn mwr_format(perf: &Performance) -> String {
+    match perf.mwr {
+        Some(mwr) => format!("{:.2}", 100.0 * mwr),
+        None => String::from("n/a")
+    }
+}
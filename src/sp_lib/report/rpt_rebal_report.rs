@@ -0,0 +1,144 @@
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::prelude::*;
+
+use crate::datastore::history::History;
+use crate::portfolio::rebalance::{self, Rebalance};
+use crate::portfolio::stock::Price;
+use crate::portfolio::target::TargetKey;
+use crate::report::report_params::ReportParams;
+use crate::report::report_trait::Report;
+use crate::util::datetime;
+use crate::util::error::Error;
+use crate::util::price_type::quantity_format;
+
+pub struct RebalReport {}
+
+impl Report for RebalReport {
+    fn write(&self, params: &ReportParams) {
+        let rebal = calc_rebalance(params);
+
+        println!("Rebalance Report");
+        println!("----------------");
+        println!("        Date: {}", datetime::today().format("%Y-%m-%d"));
+        println!("    Currency: {}", params.config().base_currency());
+        println!(" Total Value: {:.2}", rebal.total);
+        println!("        Cash: {:.2}", rebal.cash);
+        println!(" Other Value: {:.2}", rebal.other);
+        println!("   Cash Only: {}", if params.cash_only() { "yes" } else { "no" });
+        println!("  Cash After: {:.2}", rebal.cash_after());
+        println!();
+
+        println!("{:12} {:>8} {:>6} {:>12} {:>8} {:>8} {:>12}",
+                 "Target",
+                 "Weight",
+                 "Tol",
+                 "Cur Value",
+                 "Cur Wt",
+                 "Drift",
+                 "Trade Value");
+        println!("{:12} {:>8} {:>6} {:>12} {:>8} {:>8} {:>12}",
+                 "------",
+                 "------",
+                 "---",
+                 "---------",
+                 "------",
+                 "-----",
+                 "-----------");
+        for drift in rebal.drifts.iter() {
+            println!("{:12} {:8.2} {:6.2} {:12.2} {:8.2} {:8.2} {:12.2}",
+                     drift.name,
+                     drift.weight,
+                     drift.tolerance,
+                     drift.value,
+                     drift.cur_weight,
+                     drift.drift(),
+                     drift.trade);
+        }
+        println!();
+
+        println!("{:8} {:>10} {:>8} {:>12}",
+                 "Symbol",
+                 "Price",
+                 "Shares",
+                 "Trade Value");
+        println!("{:8} {:>10} {:>8} {:>12}",
+                 "------",
+                 "-----",
+                 "------",
+                 "-----------");
+        for trade in rebal.trades.iter() {
+            println!("{:8} {:10.2} {:>8} {:12.2}",
+                     trade.symbol,
+                     trade.price,
+                     quantity_format(trade.shares),
+                     trade.value());
+        }
+        for symbol in rebal.unpriced.iter() {
+            println!("{:8} {:>10} {:>8} {:>12}",
+                     symbol,
+                     "no price",
+                     "-",
+                     "-");
+        }
+        for name in rebal.untradable.iter() {
+            println!("{:8} {:>10} {:>8} {:>12}",
+                     name,
+                     "no holding",
+                     "-",
+                     "-");
+        }
+    }
+
+    fn export(&self, params: &ReportParams, filename: &str) -> Result<(), Error> {
+        let rebal = calc_rebalance(params);
+
+        let mut file = File::create(filename)?;
+        writeln!(file, "Symbol,Price,Shares,Trade Value")?;
+        for trade in rebal.trades.iter() {
+            writeln!(file, "{},{:.2},{},{:.2}",
+                     trade.symbol,
+                     trade.price,
+                     quantity_format(trade.shares),
+                     trade.value())?;
+        }
+        Ok(())
+    }
+}
+
+// --------------------------------------------------------------------------------
+// Private
+
+fn calc_rebalance(params: &ReportParams) -> Rebalance {
+    let config = params.config();
+    rebalance::rebalance(params.stocks(),
+                         config.cash_balance(&datetime::today()),
+                         config.targets(),
+                         &latest_prices(params),
+                         params.cash_only())
+}
+
+// Latest prices of the stocks, and of symbol targets not held from the datastore.
+fn latest_prices(params: &ReportParams) -> HashMap<String, Price> {
+    let mut prices: HashMap<String, Price> = params.stocks().iter()
+        .map(|stock| (stock.symbol.clone(), stock.latest_price))
+        .collect();
+
+    if let Some(ds) = params.datastore() {
+        for target in params.config().targets().iter() {
+            if let TargetKey::Symbol(symbol) = &target.key {
+                if prices.contains_key(symbol) {
+                    continue;
+                }
+
+                if let Ok(hist) = History::ds_select_last(ds, symbol) {
+                    if let Some(entry) = hist.entries().last() {
+                        prices.insert(symbol.clone(), entry.adj_close);
+                    }
+                }
+            }
+        }
+    }
+
+    prices
+}
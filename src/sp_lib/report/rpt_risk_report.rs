@@ -0,0 +1,270 @@
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::prelude::*;
+
+use crate::datastore::datastore::DataStore;
+use crate::portfolio::currency;
+use crate::portfolio::performance::{self, MarketHistory};
+use crate::portfolio::stock::Price;
+use crate::portfolio::stock_type::StockType;
+use crate::report::report_params::ReportParams;
+use crate::report::report_trait::Report;
+use crate::stats::risk_ftns::{self, Drawdown};
+use crate::util::datetime;
+use crate::util::datetime::SPDate;
+use crate::util::error::Error;
+
+pub struct RiskReport {}
+
+impl Report for RiskReport {
+    fn write(&self, params: &ReportParams) {
+        let ds = params.datastore().expect("Risk report missing datastore");
+        let (total, rows) = match calc_table(params, ds) {
+            Ok(table) => table,
+            Err(e) => {
+                eprintln!("Risk report failed - {}", e);
+                return;
+            }
+        };
+
+        println!("Portfolio Risk Report");
+        println!("---------------------");
+        println!("            Date: {}", datetime::today().format("%Y-%m-%d"));
+        println!("       From Date: {}", total.from.format("%Y-%m-%d"));
+        println!("         To Date: {}", total.to.format("%Y-%m-%d"));
+        println!("       Benchmark: {}", params.benchmark().unwrap_or("n/a"));
+        println!("  Risk-Free Rate: {:.2}", params.risk_free_rate());
+        println!("      Volatility: {}", pct_format(total.volat));
+        println!("Corr. Volatility: {}", pct_format(total.corr_volat));
+        println!("            Beta: {}", opt_format(total.beta));
+        println!("    Sharpe Ratio: {}", opt_format(total.sharpe));
+        println!("   Sortino Ratio: {}", opt_format(total.sortino));
+        println!("    Max Drawdown: {}", drawdown_format(&total.drawdown));
+        println!();
+
+        println!("{:8} {:6} {:8} {:8} {:8} {:8} {:8} {:8} {:10} {:10}",
+                 "Symbol",
+                 "Days",
+                 "Weight",
+                 "Volat",
+                 "Beta",
+                 "Sharpe",
+                 "Sortino",
+                 "Max DD",
+                 "Peak Date",
+                 "Trough");
+        println!("{:8} {:6} {:8} {:8} {:8} {:8} {:8} {:8} {:10} {:10}",
+                 "------",
+                 "----",
+                 "------",
+                 "-----",
+                 "----",
+                 "------",
+                 "-------",
+                 "------",
+                 "---------",
+                 "------");
+        for row in rows.iter() {
+            let (dd_pct, peak, trough) = drawdown_fields(&row.drawdown);
+            println!("{:8} {:6} {:>8} {:>8} {:>8} {:>8} {:>8} {:>8} {:10} {:10}",
+                     row.symbol,
+                     row.days,
+                     format!("{:.2}", 100.0 * row.weight),
+                     pct_format(row.volat),
+                     opt_format(row.beta),
+                     opt_format(row.sharpe),
+                     opt_format(row.sortino),
+                     dd_pct,
+                     peak,
+                     trough);
+        }
+    }
+
+    fn export(&self, params: &ReportParams, filename: &str) -> Result<(), Error> {
+        let ds = params.datastore().expect("Risk export missing datastore");
+        let (total, rows) = calc_table(params, ds)?;
+
+        let mut file = File::create(filename)?;
+        writeln!(file, "Symbol,Days,Weight,Volat,Corr Volat,Beta,Sharpe,Sortino,Max DD,Peak Date,Trough Date")?;
+        for row in rows.iter() {
+            let (dd_pct, peak, trough) = drawdown_fields(&row.drawdown);
+            writeln!(file, "{},{},{:.2},{},,{},{},{},{},{},{}",
+                     row.symbol,
+                     row.days,
+                     100.0 * row.weight,
+                     pct_format(row.volat),
+                     opt_format(row.beta),
+                     opt_format(row.sharpe),
+                     opt_format(row.sortino),
+                     dd_pct,
+                     peak,
+                     trough)?;
+        }
+
+        let (dd_pct, peak, trough) = drawdown_fields(&total.drawdown);
+        writeln!(file, "Total,{},100.00,{},{},{},{},{},{},{},{}",
+                 total.days,
+                 pct_format(total.volat),
+                 pct_format(total.corr_volat),
+                 opt_format(total.beta),
+                 opt_format(total.sharpe),
+                 opt_format(total.sortino),
+                 dd_pct,
+                 peak,
+                 trough)?;
+        Ok(())
+    }
+}
+
+// --------------------------------------------------------------------------------
+// Private
+
+struct RiskRow {
+    symbol: String,
+    days: usize,
+    weight: Price,
+    volat: Option<Price>,
+    beta: Option<Price>,
+    sharpe: Option<Price>,
+    sortino: Option<Price>,
+    drawdown: Option<Drawdown>
+}
+
+struct RiskTotal {
+    from: SPDate,
+    to: SPDate,
+    days: usize,
+    volat: Option<Price>,
+    corr_volat: Option<Price>,
+    beta: Option<Price>,
+    sharpe: Option<Price>,
+    sortino: Option<Price>,
+    drawdown: Option<Drawdown>
+}
+
+// Risk metrics of a dated price or growth series, with beta against the benchmark closes.
+fn calc_metrics(symbol: &str, prices: &[(SPDate, Price)], bench: &[(SPDate, Price)], rf_rate: Price) -> RiskRow {
+    let returns: Vec<Price> = match risk_ftns::returns(prices) {
+        Ok(rets) => rets.into_iter().map(|(_, ret)| ret).collect(),
+        Err(_) => Vec::new()
+    };
+
+    let beta = match risk_ftns::aligned_returns(&[prices.to_vec(), bench.to_vec()]) {
+        Ok(rets) => risk_ftns::beta(&rets[0], &rets[1]).ok(),
+        Err(_) => None
+    };
+
+    RiskRow {
+        symbol: String::from(symbol),
+        days: returns.len(),
+        weight: 0.0,
+        volat: risk_ftns::annual_volatility(&returns).ok(),
+        beta,
+        sharpe: risk_ftns::sharpe_ratio(&returns, rf_rate).ok(),
+        sortino: risk_ftns::sortino_ratio(&returns, rf_rate).ok(),
+        drawdown: risk_ftns::max_drawdown(prices)
+    }
+}
+
+// Portfolio and per-symbol risk over the report date range, with current weights of open lots.
+fn calc_table(params: &ReportParams, ds: &DataStore) -> Result<(RiskTotal, Vec<RiskRow>), Error> {
+    let stocks = params.stocks();
+    let positions = params.closed_positions();
+    let rf_rate = params.risk_free_rate() / 100.0;
+    let to = params.to_date().unwrap_or_else(datetime::today);
+
+    let mut mkt = MarketHistory::from_ds(ds, stocks, positions, &to)?;
+    currency::convert_market_from_ds(&mut mkt, params.currencies(), ds, params.config().base_currency())?;
+    if let Some(bench) = params.benchmark() {
+        mkt.add_symbol_from_ds(ds, bench, &to)?;
+    }
+
+    let series = performance::value_series(stocks, positions, params.config().cash(), &params.config().series_cash_flows(), &mkt, &to);
+    let from = match params.from_date() {
+        Some(date) => date,
+        None => series.first().map(|pt| pt.date).unwrap_or(to)
+    };
+    if from > to {
+        return Err(format!("Invalid date range from {} to {}", from.format("%Y-%m-%d"), to.format("%Y-%m-%d")).into());
+    }
+
+    let bench_closes = |from: &SPDate| match params.benchmark() {
+        Some(bench) => mkt.closes_between(bench, from, &to),
+        None => Vec::new()
+    };
+
+    // First buy date and current value of each symbol
+    let mut symbols: BTreeMap<&str, (SPDate, Price)> = BTreeMap::new();
+    for stock in stocks.iter().filter(|s| s.stype != StockType::Index) {
+        let entry = symbols.entry(&stock.symbol).or_insert((stock.date, 0.0));
+        entry.0 = entry.0.min(stock.date);
+        entry.1 += stock.latest_notional();
+    }
+    for pos in positions.iter().filter(|p| p.stype != StockType::Index) {
+        let entry = symbols.entry(&pos.symbol).or_insert((pos.base_date, 0.0));
+        entry.0 = entry.0.min(pos.base_date);
+    }
+    let total_value: Price = symbols.values().map(|(_, value)| value).sum();
+
+    let mut rows: Vec<RiskRow> = Vec::new();
+    for (symbol, (first, value)) in symbols.iter() {
+        let sym_from = from.max(*first);
+        let closes = mkt.closes_between(symbol, &sym_from, &to);
+        let mut row = calc_metrics(symbol, &closes, &bench_closes(&sym_from), rf_rate);
+        row.weight = if total_value > 0.0 { value / total_value } else { 0.0 };
+        rows.push(row);
+    }
+
+    // Volatility of the current holdings from the covariance of their returns
+    let held: Vec<&RiskRow> = rows.iter().filter(|row| row.weight > 0.0).collect();
+    let held_closes: Vec<Vec<(SPDate, Price)>> = held.iter().map(|row| mkt.closes_between(&row.symbol, &from, &to)).collect();
+    let weights: Vec<Price> = held.iter().map(|row| row.weight).collect();
+    let corr_volat = match risk_ftns::aligned_returns(&held_closes) {
+        Ok(rets) => risk_ftns::covariance_matrix(&rets).and_then(|cov| risk_ftns::portfolio_volatility(&weights, &cov)).ok(),
+        Err(_) => None
+    };
+
+    let points: Vec<_> = series.into_iter().filter(|pt| pt.date >= from).collect();
+    let growth = performance::growth_series(&points);
+    let portfolio = calc_metrics("Total", &growth, &bench_closes(&from), rf_rate);
+
+    let total = RiskTotal {
+        from,
+        to,
+        days: portfolio.days,
+        volat: portfolio.volat,
+        corr_volat: if held.is_empty() { None } else { corr_volat },
+        beta: portfolio.beta,
+        sharpe: portfolio.sharpe,
+        sortino: portfolio.sortino,
+        drawdown: portfolio.drawdown
+    };
+    Ok((total, rows))
+}
+
+fn opt_format(value: Option<Price>) -> String {
+    match value {
+        Some(value) => format!("{:.2}", value),
+        None => String::from("n/a")
+    }
+}
+
+fn pct_format(value: Option<Price>) -> String {
+    opt_format(value.map(|value| 100.0 * value))
+}
+
+fn drawdown_fields(drawdown: &Option<Drawdown>) -> (String, String, String) {
+    match drawdown {
+        Some(dd) => (format!("{:.2}", dd.pct),
+                     dd.peak_date.format("%Y-%m-%d").to_string(),
+                     dd.trough_date.format("%Y-%m-%d").to_string()),
+        None => (String::from("n/a"), String::new(), String::new())
+    }
+}
+
+fn drawdown_format(drawdown: &Option<Drawdown>) -> String {
+    match drawdown {
+        Some(dd) => format!("{:.2} ({} to {})", dd.pct, dd.peak_date.format("%Y-%m-%d"), dd.trough_date.format("%Y-%m-%d")),
+        None => String::from("n/a")
+    }
+}
@@ -18,6 +18,8 @@ impl Report for SumReport {
         println!("---------------------");
         println!("            Date: {}", datetime::today().format("%Y-%m-%d"));
         println!("Number of Stocks: {}", stocks.len());
+        println!("        Currency: {}", params.config().base_currency());
+        println!("            Cash: {:.2}", params.config().cash_balance(&datetime::today()));
         println!();
 
         println!("{:11} {:12} {:12} {:12} {:12}", "Name", "Value", "Minimum", "Average", "Maximum");
@@ -0,0 +1,122 @@
+use std::fs::File;
+use std::io::prelude::*;
+
+use crate::portfolio::tax_lots::{self, TaxLot, TaxLotList};
+use crate::portfolio::wash_sales;
+use crate::report::report_params::ReportParams;
+use crate::report::report_trait::Report;
+use crate::util::datetime;
+use crate::util::error::Error;
+use crate::util::price_type::quantity_format;
+
+pub struct TaxReport {}
+
+impl Report for TaxReport {
+    fn write(&self, params: &ReportParams) {
+        let (realized, unrealized) = adjusted_lots(params);
+
+        let realized_gains = tax_lots::term_gains(&realized);
+        let unrealized_gains = tax_lots::term_gains(&unrealized);
+
+        println!("Tax Lots Report");
+        println!("---------------");
+        println!("            Date: {}", datetime::today().format("%Y-%m-%d"));
+        println!("        Currency: {}", params.config().base_currency());
+        println!("   Realized Lots: {}", realized.len());
+        println!("     Realized ST: {:.2}", realized_gains.short);
+        println!("     Realized LT: {:.2}", realized_gains.long);
+        println!(" Unrealized Lots: {}", unrealized.len());
+        println!("   Unrealized ST: {:.2}", unrealized_gains.short);
+        println!("   Unrealized LT: {:.2}", unrealized_gains.long);
+        println!();
+
+        println!("{:8} {:12} {:12} {:12}", "Tax Year", "Short Term", "Long Term", "Total");
+        println!("{:8} {:12} {:12} {:12}", "--------", "----------", "---------", "-----");
+        for (year, gains) in tax_lots::gains_by_year(&realized).iter() {
+            println!("{:<8} {:12.2} {:12.2} {:12.2}", year, gains.short, gains.long, gains.total());
+        }
+
+        println!();
+        println!("Realized");
+        write_lots(&realized, "Sold");
+
+        println!();
+        println!("Unrealized");
+        write_lots(&unrealized, "Upd Date");
+    }
+
+    // Form 8949 worksheet of realized lots.
+    fn export(&self, params: &ReportParams, filename: &str) -> Result<(), Error> {
+        let (realized, _) = adjusted_lots(params);
+
+        let mut file = File::create(filename)?;
+        writeln!(file, "Description,Date Acquired,Date Sold,Proceeds,Cost Basis,Code,Adjustment,Gain or Loss,Term,Tax Year")?;
+        for lot in realized.iter() {
+            writeln!(file, "{} {},{},{},{:.2},{:.2},{},{:.2},{:.2},{},{}",
+                     quantity_format(lot.quantity),
+                     lot.symbol,
+                     lot.acquired.format("%Y-%m-%d"),
+                     lot.sold.format("%Y-%m-%d"),
+                     lot.proceeds,
+                     lot.cost_basis,
+                     lot.code,
+                     lot.adjustment,
+                     lot.gain(),
+                     tax_lots::term2str(lot.term()),
+                     lot.tax_year())?;
+        }
+        Ok(())
+    }
+}
+
+// --------------------------------------------------------------------------------
+// Private
+
+// Realized and unrealized lots with wash sales among all lots applied. Realized lots are those sold
+// within the report date range, ordered by date sold.
+fn adjusted_lots(params: &ReportParams) -> (TaxLotList, TaxLotList) {
+    let (mut lots, unrealized) = wash_sales::shown_tax_lots(params.all_stocks(), params.all_closed_positions(),
+                                                            params.stocks(), params.closed_positions());
+    lots.retain(|lot| params.from_date().is_none_or(|from| lot.sold >= from) &&
+                      params.to_date().is_none_or(|to| lot.sold <= to));
+    lots.sort_by_key(|lot| lot.sold);
+    (lots, unrealized)
+}
+
+fn write_lots(lots: &[TaxLot], sold_header: &str) {
+    println!("{:8} {:10} {:10} {:>8} {:12} {:12} {:4} {:10} {:12} {:5}",
+             "Symbol",
+             "Acquired",
+             sold_header,
+             "Size",
+             "Proceeds",
+             "Cost Basis",
+             "Code",
+             "Adjustment",
+             "Gain",
+             "Term");
+    println!("{:8} {:10} {:10} {:>8} {:12} {:12} {:4} {:10} {:12} {:5}",
+             "------",
+             "--------",
+             "-".repeat(sold_header.len()),
+             "----",
+             "--------",
+             "----------",
+             "----",
+             "----------",
+             "----",
+             "----");
+    for lot in lots.iter() {
+        println!("{:8} {:10} {:10} {:>8} {:12.2} {:12.2} {:4} {:10.2} {:12.2} {:5}",
+                 lot.symbol,
+                 lot.acquired.format("%Y-%m-%d"),
+                 lot.sold.format("%Y-%m-%d"),
+                 quantity_format(lot.quantity),
+                 lot.proceeds,
+                 lot.cost_basis,
+                 lot.code,
+                 lot.adjustment,
+                 lot.gain(),
+                 tax_lots::term2str(lot.term()));
+    }
+}
@@ -7,6 +7,7 @@ use crate::report::report_params::ReportParams;
 use crate::report::report_trait::Report;
 use crate::util::datetime;
 use crate::util::error::Error;
+use crate::util::price_type::quantity_format;
 
 pub struct ValueReport {}
 
@@ -22,14 +23,16 @@ impl Report for ValueReport {
         println!("-------------------");
         println!("            Date: {}", datetime::today().format("%Y-%m-%d"));
         println!("Number of Stocks: {}", stocks.len());
+        println!("        Currency: {}", params.config().base_currency());
         println!("      Base Value: {:.2}", algorithms::base_notional(stocks));
         println!("    Latest Value: {:.2}", latest_value);
         println!("       Net Value: {:.2}", algorithms::net_notional(stocks));
         println!("    Cum Dividend: {:.2}", algorithms::cumulative_dividend(stocks));
         println!("  Percent Change: {:.2}", pct_chg);
         println!("  Pct Chg w/ Div: {:.2}", pct_chg_wd);
-        println!("            Cash: {:.2}", params.config().cash());
-        println!("   Value w/ Cash: {:.2}", latest_value + params.config().cash());
+        let cash = params.config().cash_balance(&datetime::today());
+        println!("            Cash: {:.2}", cash);
+        println!("   Value w/ Cash: {:.2}", latest_value + cash);
         println!();
 
         println!("{:8} {:10} {:10} {:6} {:8} {:8} {:8} {:8} {:8} {:12} {:12} {:10} {:8}",
@@ -61,12 +64,12 @@ impl Report for ValueReport {
                  "---------",
                  "-------");
         for stock in stocks.iter() {
-            println!("{:8} {:10} {:10} {:6} {:8} {:8.2} {:8.2} {:8.2} {:8.2} {:12.2} {:12.2} {:10.2} {:8.2}",
+            println!("{:8} {:10} {:10} {:6} {:>8} {:8.2} {:8.2} {:8.2} {:8.2} {:12.2} {:12.2} {:10.2} {:8.2}",
                      stock.symbol,
                      stock.date.format("%Y-%m-%d"),
                      stock.latest_date.format("%Y-%m-%d"),
                      stock.days_held,
-                     stock.quantity,
+                     quantity_format(stock.quantity),
                      stock.base_price,
                      stock.latest_price,
                      stock.net_price(),
@@ -90,7 +93,28 @@ impl Report for ValueReport {
                 seen.insert(&stock.symbol);
 
                 let size_values = groupby.get(&stock.symbol).unwrap();
-                println!("{:8} {:8} {:12.2} {:12.2}", stock.symbol, size_values.0, size_values.1, size_values.2);
+                println!("{:8} {:>8} {:12.2} {:12.2}", stock.symbol, quantity_format(size_values.0), size_values.1, size_values.2);
+            }
+        }
+
+        if stocks.iter().any(|stock| !stock.account.is_empty()) {
+            println!();
+            println!("{:12} {:6} {:12} {:12} {:10} {:8}", "Account", "Lots", "Base Value", "Cur Value", "Net Value", "Cum Div");
+            println!("{:12} {:6} {:12} {:12} {:10} {:8}", "-------", "----", "----------", "---------", "---------", "-------");
+
+            let accounts = algorithms::account_aggregate(stocks);
+
+            let mut names: Vec<&String> = accounts.keys().collect();
+            names.sort_unstable();
+            for name in names {
+                let (lots, base_value, cur_value, cum_div) = accounts[name];
+                println!("{:12} {:>6} {:12.2} {:12.2} {:10.2} {:8.2}",
+                         if name.is_empty() { "-" } else { name },
+                         lots,
+                         base_value,
+                         cur_value,
+                         cur_value - base_value,
+                         cum_div);
             }
         }
     }
@@ -105,7 +129,7 @@ impl Report for ValueReport {
                      stock.date.format("%Y-%m-%d"),
                      stock.latest_date.format("%Y-%m-%d"),
                      stock.days_held,
-                     stock.quantity,
+                     quantity_format(stock.quantity),
                      stock.base_price,
                      stock.latest_price,
                      stock.net_price(),
@@ -118,3 +142,4 @@ impl Report for ValueReport {
         Ok(())
     }
 }
+
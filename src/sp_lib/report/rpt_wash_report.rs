@@ -0,0 +1,88 @@
+use std::fs::File;
+use std::io::prelude::*;
+
+use crate::portfolio::wash_sales::{self, WashSale, WashSaleList};
+use crate::report::report_params::ReportParams;
+use crate::report::report_trait::Report;
+use crate::util::datetime;
+use crate::util::error::Error;
+use crate::util::price_type::quantity_format;
+
+pub struct WashReport {}
+
+impl Report for WashReport {
+    fn write(&self, params: &ReportParams) {
+        let sales = shown_sales(params);
+        let disallowed = sales.iter().fold(0.0, |total, sale| total + sale.disallowed);
+
+        println!("Wash Sales Report");
+        println!("-----------------");
+        println!("            Date: {}", datetime::today().format("%Y-%m-%d"));
+        println!("        Currency: {}", params.config().base_currency());
+        println!("      Wash Sales: {}", sales.len());
+        println!(" Disallowed Loss: {:.2}", disallowed);
+        println!();
+
+        println!("{:8} {:10} {:10} {:10} {:6} {:>8} {:12} {:10}",
+                 "Symbol",
+                 "Acquired",
+                 "Sold",
+                 "Repl Date",
+                 "Repl",
+                 "Size",
+                 "Disallowed",
+                 "Basis Adj");
+        println!("{:8} {:10} {:10} {:10} {:6} {:>8} {:12} {:10}",
+                 "------",
+                 "--------",
+                 "----",
+                 "---------",
+                 "----",
+                 "----",
+                 "----------",
+                 "---------");
+        for sale in sales.iter() {
+            println!("{:8} {:10} {:10} {:10} {:6} {:>8} {:12.2} {:10.2}",
+                     sale.symbol,
+                     params.all_closed_positions()[sale.sold_index].base_date.format("%Y-%m-%d"),
+                     sale.sold_date.format("%Y-%m-%d"),
+                     sale.replacement_date.format("%Y-%m-%d"),
+                     replacement_kind(sale),
+                     quantity_format(sale.quantity),
+                     sale.disallowed,
+                     sale.basis_adjustment());
+        }
+    }
+
+    fn export(&self, params: &ReportParams, filename: &str) -> Result<(), Error> {
+        let sales = shown_sales(params);
+
+        let mut file = File::create(filename)?;
+        writeln!(file, "Symbol,Acquired,Sold,Repl Date,Repl,Size,Disallowed,Basis Adj")?;
+        for sale in sales.iter() {
+            writeln!(file, "{},{},{},{},{},{},{:.2},{:.2}",
+                     sale.symbol,
+                     params.all_closed_positions()[sale.sold_index].base_date.format("%Y-%m-%d"),
+                     sale.sold_date.format("%Y-%m-%d"),
+                     sale.replacement_date.format("%Y-%m-%d"),
+                     replacement_kind(sale),
+                     quantity_format(sale.quantity),
+                     sale.disallowed,
+                     sale.basis_adjustment())?;
+        }
+        Ok(())
+    }
+}
+
+// --------------------------------------------------------------------------------
+// Private
+
+// Wash sales found among all lots, of the lots shown.
+fn shown_sales(params: &ReportParams) -> WashSaleList {
+    wash_sales::shown_wash_sales(params.all_stocks(), params.all_closed_positions(), params.stocks(), params.closed_positions())
+}
+
+#[inline(always)]
+fn replacement_kind(sale: &WashSale) -> &'static str {
+    if sale.replacement_open { "open" } else { "closed" }
+}
@@ -2,3 +2,4 @@ pub mod description;
 pub mod hist_ftns;
 pub mod hist_desc;
 pub mod reduce_ftns;
+pub mod risk_ftns;
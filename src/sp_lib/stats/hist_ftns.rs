@@ -7,6 +7,8 @@ use crate::stats::reduce_ftns;
 const DEFAULT_FIELD: &str = "adj_close";
 
 pub type DatePriceList = Vec<(SPDate, Price)>;
+pub type DatePricePairList = Vec<(SPDate, Price, Price)>;
+pub type DatePriceTripleList = Vec<(SPDate, Price, Price, Price)>;
 
 fn field_to_ftn(field: &str) -> impl Fn(&HistoryEntry) -> Price {
     match field {
@@ -18,6 +20,19 @@ fn field_to_ftn(field: &str) -> impl Fn(&HistoryEntry) -> Price {
     }
 }
 
+// --------------------------------------------------------------------------------
+// Field Prices
+
+pub fn entries_field_prices(entries: &[HistoryEntry], field: &str) -> DatePriceList {
+    let field_ftn = field_to_ftn(field);
+    entries.iter().map(|entry| (entry.date, field_ftn(entry))).collect()
+}
+
+#[inline(always)]
+pub fn hist_field_prices(hist: &History, field: &str) -> DatePriceList {
+    entries_field_prices(hist.entries(), field)
+}
+
 // --------------------------------------------------------------------------------
 // Volume Weighted Average Price
 
@@ -369,15 +384,313 @@ pub fn hist_rsi(hist: &History, days: usize) -> Result<DatePriceList, Error> {
     entries_rsi(hist.entries(), days)
 }
 
+// --------------------------------------------------------------------------------
+// Exponential Moving Average Price
+
+// EMA of values seeded with the simple average of the first days values.
+// Output is aligned with values from index days - 1.
+fn values_ema(values: &[Price], days: usize) -> Vec<Price> {
+    let alpha = 2.0 / (days + 1) as Price;
+    let mut ema = values[..days].iter().sum::<Price>() / days as Price;
+    let mut emas: Vec<Price> = Vec::with_capacity(values.len() - days + 1);
+    emas.push(ema);
+    for value in values.iter().skip(days) {
+        ema += alpha * (value - ema);
+        emas.push(ema);
+    }
+    emas
+}
+
+pub fn entries_field_ema(entries: &[HistoryEntry], field: &str, days: usize) -> Result<DatePriceList, Error> {
+    if days < 1 {
+        return Err("entries_ema: days < 1".into())
+    }
+    if days > entries.len() {
+        return Err("entries_ema: days > len".into())
+    }
+
+    let field_ftn = field_to_ftn(field);
+    let values: Vec<Price> = entries.iter().map(field_ftn).collect();
+    let emas = values_ema(&values, days);
+    Ok(entries[(days - 1)..].iter().map(|e| e.date).zip(emas).collect())
+}
+
+#[inline(always)]
+pub fn entries_ema(entries: &[HistoryEntry], days: usize) -> Result<DatePriceList, Error> {
+    entries_field_ema(entries, DEFAULT_FIELD, days)
+}
+
+#[inline(always)]
+pub fn hist_field_ema(hist: &History, field: &str, days: usize) -> Result<DatePriceList, Error> {
+    entries_field_ema(hist.entries(), field, days)
+}
+
+#[inline(always)]
+pub fn hist_ema(hist: &History, days: usize) -> Result<DatePriceList, Error> {
+    entries_field_ema(hist.entries(), DEFAULT_FIELD, days)
+}
+
+// --------------------------------------------------------------------------------
+// Moving Average Convergence Divergence
+//
+// (date, macd, signal, histogram) where macd is fast EMA - slow EMA, signal is the EMA
+// of macd over signal days and histogram is macd - signal.
+
+pub fn entries_field_macd(entries: &[HistoryEntry], field: &str, fast: usize, slow: usize, signal: usize) -> Result<DatePriceTripleList, Error> {
+    if fast < 1 || signal < 1 {
+        return Err("entries_macd: fast or signal days < 1".into())
+    }
+    if fast >= slow {
+        return Err("entries_macd: fast days >= slow days".into())
+    }
+    if slow + signal - 1 > entries.len() {
+        return Err("entries_macd: slow + signal days - 1 > len".into())
+    }
+
+    let field_ftn = field_to_ftn(field);
+    let values: Vec<Price> = entries.iter().map(field_ftn).collect();
+    let fast_ema = values_ema(&values, fast);
+    let slow_ema = values_ema(&values, slow);
+
+    // Both EMAs are available from index slow - 1
+    let macd: Vec<Price> = slow_ema.iter().enumerate().map(|(i, slow_px)| fast_ema[i + slow - fast] - slow_px).collect();
+    let signals = values_ema(&macd, signal);
+
+    let base = slow + signal - 2;
+    Ok(signals
+        .iter()
+        .enumerate()
+        .map(|(i, sig)| {
+            let line = macd[i + signal - 1];
+            (entries[base + i].date, line, *sig, line - sig)
+        })
+        .collect())
+}
+
+#[inline(always)]
+pub fn entries_macd(entries: &[HistoryEntry], fast: usize, slow: usize, signal: usize) -> Result<DatePriceTripleList, Error> {
+    entries_field_macd(entries, DEFAULT_FIELD, fast, slow, signal)
+}
+
+#[inline(always)]
+pub fn hist_field_macd(hist: &History, field: &str, fast: usize, slow: usize, signal: usize) -> Result<DatePriceTripleList, Error> {
+    entries_field_macd(hist.entries(), field, fast, slow, signal)
+}
+
+#[inline(always)]
+pub fn hist_macd(hist: &History, fast: usize, slow: usize, signal: usize) -> Result<DatePriceTripleList, Error> {
+    entries_field_macd(hist.entries(), DEFAULT_FIELD, fast, slow, signal)
+}
+
+// --------------------------------------------------------------------------------
+// Bollinger Bands
+//
+// (date, lower, middle, upper) where middle is the SMA over days and the bands are
+// mult standard deviations below and above it.
+
+pub fn entries_field_bollinger(entries: &[HistoryEntry], field: &str, days: usize, mult: Price) -> Result<DatePriceTripleList, Error> {
+    if days < 2 {
+        return Err("entries_bollinger: days < 2".into())
+    }
+    if days > entries.len() {
+        return Err("entries_bollinger: days > len".into())
+    }
+    if mult <= 0.0 {
+        return Err("entries_bollinger: mult <= 0".into())
+    }
+
+    let field_ftn = field_to_ftn(field);
+    let mut bands: DatePriceTripleList = Vec::with_capacity(entries.len() - days + 1);
+    for window in entries.windows(days) {
+        let mean = reduce_ftns::mean(window, &field_ftn)?;
+        let stddev = reduce_ftns::stddev(window, &field_ftn)?;
+        bands.push((window[days - 1].date, mean - mult * stddev, mean, mean + mult * stddev));
+    }
+
+    Ok(bands)
+}
+
+#[inline(always)]
+pub fn entries_bollinger(entries: &[HistoryEntry], days: usize, mult: Price) -> Result<DatePriceTripleList, Error> {
+    entries_field_bollinger(entries, DEFAULT_FIELD, days, mult)
+}
+
+#[inline(always)]
+pub fn hist_field_bollinger(hist: &History, field: &str, days: usize, mult: Price) -> Result<DatePriceTripleList, Error> {
+    entries_field_bollinger(hist.entries(), field, days, mult)
+}
+
+#[inline(always)]
+pub fn hist_bollinger(hist: &History, days: usize, mult: Price) -> Result<DatePriceTripleList, Error> {
+    entries_field_bollinger(hist.entries(), DEFAULT_FIELD, days, mult)
+}
+
+// --------------------------------------------------------------------------------
+// Average True Range
+//
+// Uses high, low and close. The first true range is high - low, then Wilder smoothing
+// is applied from the simple average of the first days true ranges.
+
+pub fn entries_atr(entries: &[HistoryEntry], days: usize) -> Result<DatePriceList, Error> {
+    if days < 1 {
+        return Err("entries_atr: days < 1".into())
+    }
+    if days > entries.len() {
+        return Err("entries_atr: days > len".into())
+    }
+
+    let true_ranges: Vec<Price> = entries
+        .iter()
+        .enumerate()
+        .map(|(i, e)| match i {
+            0 => e.high - e.low,
+            _ => {
+                let prev_close = entries[i - 1].close;
+                (e.high - e.low).max((e.high - prev_close).abs()).max((e.low - prev_close).abs())
+            }
+        })
+        .collect();
+
+    let days_f = days as Price;
+    let mut atr = true_ranges[..days].iter().sum::<Price>() / days_f;
+    let mut atrs: DatePriceList = Vec::with_capacity(entries.len() - days + 1);
+    atrs.push((entries[days - 1].date, atr));
+    for i in days..entries.len() {
+        atr = (atr * (days_f - 1.0) + true_ranges[i]) / days_f;
+        atrs.push((entries[i].date, atr));
+    }
+
+    Ok(atrs)
+}
+
+#[inline(always)]
+pub fn hist_atr(hist: &History, days: usize) -> Result<DatePriceList, Error> {
+    entries_atr(hist.entries(), days)
+}
+
+// --------------------------------------------------------------------------------
+// Stochastic Oscillator
+//
+// (date, %K, %D) where %K is the close relative to the low-high range over days and
+// %D is the simple average of %K over smooth days. Uses high, low and close.
+
+pub fn entries_stochastic(entries: &[HistoryEntry], days: usize, smooth: usize) -> Result<DatePricePairList, Error> {
+    if days < 1 || smooth < 1 {
+        return Err("entries_stochastic: days or smooth days < 1".into())
+    }
+    if days + smooth - 1 > entries.len() {
+        return Err("entries_stochastic: days + smooth days - 1 > len".into())
+    }
+
+    let mut pct_k: Vec<Price> = Vec::with_capacity(entries.len() - days + 1);
+    for window in entries.windows(days) {
+        let low = reduce_ftns::min(window, |e| e.low)?;
+        let high = reduce_ftns::max(window, |e| e.high)?;
+        if price_zero(high - low) {
+            return Err("entries_stochastic: Cannot divide by zero high low range".into())
+        }
+        pct_k.push(100.0 * (window[days - 1].close - low) / (high - low));
+    }
+
+    let base = days + smooth - 2;
+    Ok(pct_k
+        .windows(smooth)
+        .enumerate()
+        .map(|(i, ks)| (entries[base + i].date, ks[smooth - 1], ks.iter().sum::<Price>() / smooth as Price))
+        .collect())
+}
+
+#[inline(always)]
+pub fn hist_stochastic(hist: &History, days: usize, smooth: usize) -> Result<DatePricePairList, Error> {
+    entries_stochastic(hist.entries(), days, smooth)
+}
+
+// --------------------------------------------------------------------------------
+// On-Balance Volume
+//
+// Cumulative volume, added on up days and subtracted on down days, starting at zero.
+
+pub fn entries_field_obv(entries: &[HistoryEntry], field: &str) -> Result<DatePriceList, Error> {
+    if entries.is_empty() {
+        return Err("entries_obv: len < 1".into())
+    }
+
+    let field_ftn = field_to_ftn(field);
+    let mut obv: Price = 0.0;
+    let mut obvs: DatePriceList = Vec::with_capacity(entries.len());
+    obvs.push((entries[0].date, obv));
+    for pair in entries.windows(2) {
+        let (prev, cur) = (field_ftn(&pair[0]), field_ftn(&pair[1]));
+        if cur > prev {
+            obv += pair[1].volume as Price;
+        } else if cur < prev {
+            obv -= pair[1].volume as Price;
+        }
+        obvs.push((pair[1].date, obv));
+    }
+
+    Ok(obvs)
+}
+
+#[inline(always)]
+pub fn entries_obv(entries: &[HistoryEntry]) -> Result<DatePriceList, Error> {
+    entries_field_obv(entries, DEFAULT_FIELD)
+}
+
+#[inline(always)]
+pub fn hist_field_obv(hist: &History, field: &str) -> Result<DatePriceList, Error> {
+    entries_field_obv(hist.entries(), field)
+}
+
+#[inline(always)]
+pub fn hist_obv(hist: &History) -> Result<DatePriceList, Error> {
+    entries_field_obv(hist.entries(), DEFAULT_FIELD)
+}
+
+// --------------------------------------------------------------------------------
+// Rolling High Low Channel
+//
+// (date, lowest low, highest high) over days.
+
+pub fn entries_channel(entries: &[HistoryEntry], days: usize) -> Result<DatePricePairList, Error> {
+    if days < 1 {
+        return Err("entries_channel: days < 1".into())
+    }
+    if days > entries.len() {
+        return Err("entries_channel: days > len".into())
+    }
+
+    let mut channel: DatePricePairList = Vec::with_capacity(entries.len() - days + 1);
+    for window in entries.windows(days) {
+        channel.push((window[days - 1].date, reduce_ftns::min(window, |e| e.low)?, reduce_ftns::max(window, |e| e.high)?));
+    }
+
+    Ok(channel)
+}
+
+#[inline(always)]
+pub fn hist_channel(hist: &History, days: usize) -> Result<DatePricePairList, Error> {
+    entries_channel(hist.entries(), days)
+}
+
 // --------------------------------------------------------------------------------
 // Unit Tests
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::util::price_type::price_eql;
+    use crate::util::price_type::{price_eql, prices_eql};
     use crate::util::datetime::{make_date, date_plus_days, is_weekend};
 
+    #[test]
+    fn test_field_prices() {
+        let hist = hist_data();
+        let prices = hist_field_prices(&hist, "close");
+        assert_eq!(prices.len(), hist.count());
+        assert_eq!(prices[0], (make_date(2021, 10, 1), 142.649994));
+        assert_eq!(entries_field_prices(&hist.entries()[1..], DEFAULT_FIELD)[0], (make_date(2021, 10, 4), 138.937225));
+    }
+
     #[test]
     fn test_entries_vwap() {
         let hist = hist_data();
@@ -636,6 +949,97 @@ mod tests {
         assert!(date_prices_eql(&actual, &expect));
     }
 
+    #[test]
+    fn test_entries_ema() {
+        let hist = hist_data();
+        let entries = hist.entries();
+        let actual = entries_ema(entries, 5).unwrap();
+        assert_eq!(actual.len(), 17);
+        assert_eq!(actual[0].0, make_date(2021, 10, 7));
+        assert!(prices_eql(&actual[..3].iter().map(|dp| dp.1).collect::<Vec<Price>>(), &[141.431586, 141.851638, 142.101719]));
+        assert!(price_eql(actual[16].1, 149.707565));
+
+        // One day EMA is the field value
+        let actual = hist_field_ema(&hist, "close", 1).unwrap();
+        assert!(actual.iter().zip(entries.iter()).all(|(dp, e)| dp.0 == e.date && price_eql(dp.1, e.close)));
+        assert!(entries_ema(entries, 0).is_err());
+        assert!(hist_ema(&hist, 22).is_err());
+    }
+
+    #[test]
+    fn test_entries_macd() {
+        let hist = hist_data();
+        let actual = hist_macd(&hist, 3, 5, 2).unwrap();
+        assert_eq!(actual.len(), 16);
+        assert_eq!(actual[0].0, make_date(2021, 10, 8));
+        assert!(prices_eql(&[actual[0].1, actual[0].2, actual[0].3], &[0.583814, 0.665695, -0.081881]));
+        assert!(prices_eql(&[actual[1].1, actual[1].2, actual[1].3], &[0.416948, 0.499863, -0.082916]));
+
+        assert_eq!(format!("{}", entries_macd(hist.entries(), 5, 5, 2).unwrap_err()), "entries_macd: fast days >= slow days");
+        assert_eq!(format!("{}", entries_macd(hist.entries(), 3, 20, 3).unwrap_err()), "entries_macd: slow + signal days - 1 > len");
+        assert!(entries_macd(hist.entries(), 3, 5, 0).is_err());
+    }
+
+    #[test]
+    fn test_entries_bollinger() {
+        let hist = hist_data();
+        let actual = hist_bollinger(&hist, 5, 2.0).unwrap();
+        let sma = hist_sma(&hist, 5).unwrap();
+        assert_eq!(actual.len(), sma.len());
+        assert_eq!(actual[0].0, make_date(2021, 10, 7));
+        assert!(prices_eql(&[actual[0].1, actual[0].2, actual[0].3], &[138.210906, 141.431586, 144.652265]));
+        assert!(actual.iter().zip(sma.iter()).all(|(band, dp)| price_eql(band.2, dp.1) && price_eql(band.2 - band.1, band.3 - band.2)));
+
+        assert!(entries_bollinger(hist.entries(), 1, 2.0).is_err());
+        assert!(entries_bollinger(hist.entries(), 5, 0.0).is_err());
+    }
+
+    #[test]
+    fn test_entries_atr() {
+        let hist = hist_data();
+        let actual = hist_atr(&hist, 5).unwrap();
+        assert_eq!(actual.len(), 17);
+        assert_eq!(actual[0].0, make_date(2021, 10, 7));
+        assert!(price_eql(actual[0].1, 3.457999));
+        assert!(price_eql(actual[1].1, 3.090398));
+        assert!(price_eql(actual[16].1, 3.171390));
+        assert!(entries_atr(hist.entries(), 0).is_err());
+    }
+
+    #[test]
+    fn test_entries_stochastic() {
+        let hist = hist_data();
+        let actual = hist_stochastic(&hist, 5, 3).unwrap();
+        assert_eq!(actual.len(), 15);
+        assert_eq!(actual[0].0, make_date(2021, 10, 11));
+        assert!(prices_eql(&[actual[0].1, actual[0].2], &[68.944114, 77.042906]));
+        assert!(prices_eql(&[actual[1].1, actual[1].2], &[48.757741, 65.172284]));
+        assert!(actual.iter().all(|dp| dp.1 >= 0.0 && dp.1 <= 100.0));
+
+        assert!(entries_stochastic(hist.entries(), 5, 0).is_err());
+        assert!(entries_stochastic(hist.entries(), 20, 3).is_err());
+    }
+
+    #[test]
+    fn test_entries_obv() {
+        let hist = hist_data();
+        let actual = hist_obv(&hist).unwrap();
+        assert_eq!(actual.len(), hist.count());
+        assert!(prices_eql(&actual[..4].iter().map(|dp| dp.1).collect::<Vec<Price>>(), &[0.0, -98322000.0, -17460900.0, 65760200.0]));
+        assert_eq!(actual[20].1, 142545600.0);
+        assert!(entries_obv(&[]).is_err());
+    }
+
+    #[test]
+    fn test_entries_channel() {
+        let hist = hist_data();
+        let actual = hist_channel(&hist, 5).unwrap();
+        assert_eq!(actual.len(), 17);
+        assert_eq!(actual[0], (make_date(2021, 10, 7), 138.270004, 144.220001));
+        assert_eq!(actual[16], (make_date(2021, 10, 29), 146.410004, 153.169998));
+        assert!(entries_channel(hist.entries(), 22).is_err());
+    }
+
     fn hist_data() -> History {
         History::parse_csv(
             "AAPL",
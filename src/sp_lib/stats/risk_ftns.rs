@@ -0,0 +1,316 @@
+use std::collections::BTreeMap;
+
+use crate::util::error::Error;
+use crate::datastore::history::Price;
+use crate::util::datetime::SPDate;
+use crate::util::price_type::price_zero;
+use crate::stats::reduce_ftns;
+use crate::stats::hist_ftns::DatePriceList;
+
+// Trading days per year, used to annualize daily statistics
+pub const TRADING_DAYS: Price = 252.0;
+
+// --------------------------------------------------------------------------------
+// Returns
+
+// Daily returns as fractions, dated at the end of each interval.
+pub fn returns(prices: &[(SPDate, Price)]) -> Result<DatePriceList, Error> {
+    let mut rets: DatePriceList = Vec::with_capacity(prices.len().saturating_sub(1));
+    for pair in prices.windows(2) {
+        let p0 = pair[0].1;
+        if price_zero(p0) {
+            return Err("returns: Cannot divide by zero price".into())
+        }
+        rets.push((pair[1].0, (pair[1].1 - p0) / p0));
+    }
+    Ok(rets)
+}
+
+// Align dated series on the dates common to all of them, dropping dates missing from any series.
+// Returns the common dates and the values of each series on those dates.
+pub fn align_series(series: &[DatePriceList]) -> (Vec<SPDate>, Vec<Vec<Price>>) {
+    let mut common: BTreeMap<SPDate, Vec<Price>> = match series.first() {
+        Some(first) => first.iter().map(|(date, px)| (*date, vec![*px])).collect(),
+        None => BTreeMap::new()
+    };
+
+    for other in series.iter().skip(1) {
+        let other: BTreeMap<SPDate, Price> = other.iter().cloned().collect();
+        common.retain(|date, _| other.contains_key(date));
+        for (date, values) in common.iter_mut() {
+            values.push(other[date]);
+        }
+    }
+
+    let dates: Vec<SPDate> = common.keys().cloned().collect();
+    let values: Vec<Vec<Price>> = (0..series.len())
+        .map(|i| common.values().map(|vals| vals[i]).collect())
+        .collect();
+    (dates, values)
+}
+
+// Daily returns of each series over their common dates.
+pub fn aligned_returns(series: &[DatePriceList]) -> Result<Vec<Vec<Price>>, Error> {
+    let (dates, values) = align_series(series);
+    values
+        .iter()
+        .map(|vals| {
+            let prices: DatePriceList = dates.iter().cloned().zip(vals.iter().cloned()).collect();
+            Ok(returns(&prices)?.into_iter().map(|(_, ret)| ret).collect())
+        })
+        .collect()
+}
+
+// --------------------------------------------------------------------------------
+// Covariance, Correlation and Beta
+
+pub fn covariance(lhs: &[Price], rhs: &[Price]) -> Result<Price, Error> {
+    if lhs.len() != rhs.len() {
+        return Err("covariance: Mismatched lengths".into())
+    }
+    if lhs.len() < 2 {
+        return Err("covariance: len < 2".into())
+    }
+
+    let lhs_avg = reduce_ftns::mean(lhs, |v| *v)?;
+    let rhs_avg = reduce_ftns::mean(rhs, |v| *v)?;
+    let sum: Price = lhs.iter().zip(rhs.iter()).map(|(l, r)| (l - lhs_avg) * (r - rhs_avg)).sum();
+    Ok(sum / (lhs.len() - 1) as Price)
+}
+
+pub fn correlation(lhs: &[Price], rhs: &[Price]) -> Result<Price, Error> {
+    let cov = covariance(lhs, rhs)?;
+    let denom = reduce_ftns::stddev(lhs, |v| *v)? * reduce_ftns::stddev(rhs, |v| *v)?;
+    if price_zero(denom) {
+        return Err("correlation: Cannot divide by zero standard deviation".into())
+    }
+    Ok(cov / denom)
+}
+
+// Sensitivity of returns to benchmark returns
+pub fn beta(returns: &[Price], bench_returns: &[Price]) -> Result<Price, Error> {
+    let cov = covariance(returns, bench_returns)?;
+    let var = covariance(bench_returns, bench_returns)?;
+    if price_zero(var) {
+        return Err("beta: Cannot divide by zero benchmark variance".into())
+    }
+    Ok(cov / var)
+}
+
+// Covariance of each pair of series, which must have equal lengths.
+pub fn covariance_matrix(series: &[Vec<Price>]) -> Result<Vec<Vec<Price>>, Error> {
+    series
+        .iter()
+        .map(|lhs| series.iter().map(|rhs| covariance(lhs, rhs)).collect())
+        .collect()
+}
+
+// Correlation of each pair of series, which must have equal lengths.
+pub fn correlation_matrix(series: &[Vec<Price>]) -> Result<Vec<Vec<Price>>, Error> {
+    series
+        .iter()
+        .map(|lhs| series.iter().map(|rhs| correlation(lhs, rhs)).collect())
+        .collect()
+}
+
+// --------------------------------------------------------------------------------
+// Volatility and Risk Adjusted Returns
+
+// Annualized volatility of daily returns
+pub fn annual_volatility(returns: &[Price]) -> Result<Price, Error> {
+    if returns.len() < 2 {
+        return Err("annual_volatility: len < 2".into())
+    }
+    Ok(reduce_ftns::stddev(returns, |v| *v)? * TRADING_DAYS.sqrt())
+}
+
+// Annualized volatility of a weighted portfolio from the covariance of daily returns.
+pub fn portfolio_volatility(weights: &[Price], cov: &[Vec<Price>]) -> Result<Price, Error> {
+    if weights.len() != cov.len() || cov.iter().any(|row| row.len() != weights.len()) {
+        return Err("portfolio_volatility: Mismatched weights and covariance matrix".into())
+    }
+
+    let mut var: Price = 0.0;
+    for (i, wi) in weights.iter().enumerate() {
+        for (j, wj) in weights.iter().enumerate() {
+            var += wi * wj * cov[i][j];
+        }
+    }
+    Ok(var.max(0.0).sqrt() * TRADING_DAYS.sqrt())
+}
+
+// Annualized Sharpe ratio of daily returns, given an annual risk-free rate as a fraction.
+pub fn sharpe_ratio(returns: &[Price], rf_rate: Price) -> Result<Price, Error> {
+    if returns.len() < 2 {
+        return Err("sharpe_ratio: len < 2".into())
+    }
+
+    let excess: Vec<Price> = returns.iter().map(|ret| ret - rf_rate / TRADING_DAYS).collect();
+    let stddev = reduce_ftns::stddev(&excess, |v| *v)?;
+    if price_zero(stddev) {
+        return Err("sharpe_ratio: Cannot divide by zero standard deviation".into())
+    }
+    Ok(reduce_ftns::mean(&excess, |v| *v)? / stddev * TRADING_DAYS.sqrt())
+}
+
+// Annualized Sortino ratio of daily returns, given an annual risk-free rate as a fraction.
+// Only returns below the risk-free rate count towards the downside deviation.
+pub fn sortino_ratio(returns: &[Price], rf_rate: Price) -> Result<Price, Error> {
+    if returns.len() < 2 {
+        return Err("sortino_ratio: len < 2".into())
+    }
+
+    let excess: Vec<Price> = returns.iter().map(|ret| ret - rf_rate / TRADING_DAYS).collect();
+    let downside = (excess.iter().map(|ex| ex.min(0.0).powi(2)).sum::<Price>() / excess.len() as Price).sqrt();
+    if price_zero(downside) {
+        return Err("sortino_ratio: Cannot divide by zero downside deviation".into())
+    }
+    Ok(reduce_ftns::mean(&excess, |v| *v)? / downside * TRADING_DAYS.sqrt())
+}
+
+// --------------------------------------------------------------------------------
+// Drawdown
+
+pub struct Drawdown {
+    pub pct: Price,          // Decline from peak to trough, in percent
+    pub peak_date: SPDate,
+    pub trough_date: SPDate
+}
+
+// Largest decline from a running peak of a dated series, if any.
+pub fn max_drawdown(values: &[(SPDate, Price)]) -> Option<Drawdown> {
+    let (first_date, first_value) = values.first()?;
+    let mut peak = (*first_date, *first_value);
+    let mut max_dd: Option<Drawdown> = None;
+
+    for (date, value) in values.iter().skip(1) {
+        if *value > peak.1 {
+            peak = (*date, *value);
+        } else if peak.1 > 0.0 {
+            let pct = 100.0 * (peak.1 - value) / peak.1;
+            let deeper = match &max_dd {
+                Some(dd) => pct > dd.pct,
+                None => pct > 0.0
+            };
+            if deeper {
+                max_dd = Some(Drawdown { pct, peak_date: peak.0, trough_date: *date });
+            }
+        }
+    }
+    max_dd
+}
+
+// --------------------------------------------------------------------------------
+// Unit Tests
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::datetime::make_date;
+    use crate::util::price_type::{price_eql, prices_eql};
+
+    #[test]
+    fn test_returns() {
+        let prices = vec![(make_date(2021, 3, 1), 100.0), (make_date(2021, 3, 2), 110.0), (make_date(2021, 3, 3), 99.0)];
+        let rets = returns(&prices).unwrap();
+        assert_eq!(rets.len(), 2);
+        assert_eq!(rets[0].0, make_date(2021, 3, 2));
+        assert!(price_eql(rets[0].1, 0.1));
+        assert!(price_eql(rets[1].1, -0.1));
+
+        assert!(returns(&prices[..1]).unwrap().is_empty());
+        let prices = vec![(make_date(2021, 3, 1), 0.0), (make_date(2021, 3, 2), 110.0)];
+        assert_eq!(format!("{}", returns(&prices).unwrap_err()), "returns: Cannot divide by zero price");
+    }
+
+    #[test]
+    fn test_align_series() {
+        let lhs = vec![(make_date(2021, 3, 1), 1.0), (make_date(2021, 3, 2), 2.0), (make_date(2021, 3, 4), 4.0)];
+        let rhs = vec![(make_date(2021, 3, 2), 20.0), (make_date(2021, 3, 3), 30.0), (make_date(2021, 3, 4), 40.0)];
+
+        let (dates, values) = align_series(&[lhs.clone(), rhs.clone()]);
+        assert_eq!(dates, vec![make_date(2021, 3, 2), make_date(2021, 3, 4)]);
+        assert_eq!(values, vec![vec![2.0, 4.0], vec![20.0, 40.0]]);
+
+        let rets = aligned_returns(&[lhs, rhs]).unwrap();
+        assert!(prices_eql(&rets[0], &[1.0]));
+        assert!(prices_eql(&rets[1], &[1.0]));
+
+        let (dates, values) = align_series(&[]);
+        assert!(dates.is_empty());
+        assert!(values.is_empty());
+    }
+
+    #[test]
+    fn test_covariance() {
+        let lhs = [0.01, -0.02, 0.03, 0.0];
+        let rhs = [0.02, -0.04, 0.06, 0.0];
+        let neg = [-0.01, 0.02, -0.03, 0.0];
+
+        assert!(price_eql(covariance(&lhs, &lhs).unwrap(), reduce_ftns::variance(&lhs, |v| *v).unwrap()));
+        assert!(price_eql(covariance(&lhs, &rhs).unwrap(), 2.0 * covariance(&lhs, &lhs).unwrap()));
+        assert!(price_eql(correlation(&lhs, &rhs).unwrap(), 1.0));
+        assert!(price_eql(correlation(&lhs, &neg).unwrap(), -1.0));
+        assert!(price_eql(beta(&rhs, &lhs).unwrap(), 2.0));
+        assert!(price_eql(beta(&lhs, &rhs).unwrap(), 0.5));
+
+        assert_eq!(format!("{}", covariance(&lhs, &rhs[1..]).unwrap_err()), "covariance: Mismatched lengths");
+        assert_eq!(format!("{}", covariance(&lhs[..1], &rhs[..1]).unwrap_err()), "covariance: len < 2");
+        assert_eq!(format!("{}", beta(&lhs, &[0.01, 0.01, 0.01, 0.01]).unwrap_err()),
+                   "beta: Cannot divide by zero benchmark variance");
+
+        let corr = correlation_matrix(&[lhs.to_vec(), neg.to_vec()]).unwrap();
+        assert!(prices_eql(&corr[0], &[1.0, -1.0]));
+        assert!(prices_eql(&corr[1], &[-1.0, 1.0]));
+    }
+
+    #[test]
+    fn test_portfolio_volatility() {
+        let lhs = vec![0.01, -0.02, 0.03, 0.0];
+        let neg = vec![-0.01, 0.02, -0.03, 0.0];
+
+        // Perfectly offsetting holdings have no volatility
+        let cov = covariance_matrix(&[lhs.clone(), neg]).unwrap();
+        assert!(price_eql(portfolio_volatility(&[0.5, 0.5], &cov).unwrap(), 0.0));
+
+        // A single holding has its own volatility
+        let cov = covariance_matrix(std::slice::from_ref(&lhs)).unwrap();
+        assert!(price_eql(portfolio_volatility(&[1.0], &cov).unwrap(), annual_volatility(&lhs).unwrap()));
+
+        assert!(portfolio_volatility(&[0.5, 0.5], &cov).is_err());
+    }
+
+    #[test]
+    fn test_sharpe_sortino() {
+        let rets = [0.01, -0.005, 0.02, -0.01, 0.015];
+        let avg = reduce_ftns::mean(&rets, |v| *v).unwrap();
+        let stddev = reduce_ftns::stddev(&rets, |v| *v).unwrap();
+        assert!(price_eql(sharpe_ratio(&rets, 0.0).unwrap(), avg / stddev * TRADING_DAYS.sqrt()));
+
+        let downside = ((0.005_f64.powi(2) + 0.01_f64.powi(2)) / 5.0).sqrt();
+        assert!(price_eql(sortino_ratio(&rets, 0.0).unwrap(), avg / downside * TRADING_DAYS.sqrt()));
+
+        // Risk-free rate lowers the ratios
+        assert!(sharpe_ratio(&rets, 0.05).unwrap() < sharpe_ratio(&rets, 0.0).unwrap());
+        assert!(sortino_ratio(&[0.01, 0.02], 0.0).is_err());
+        assert!(sharpe_ratio(&[0.01], 0.0).is_err());
+    }
+
+    #[test]
+    fn test_max_drawdown() {
+        let values = vec![
+            (make_date(2021, 3, 1), 100.0),
+            (make_date(2021, 3, 2), 120.0),
+            (make_date(2021, 3, 3), 90.0),
+            (make_date(2021, 3, 4), 130.0),
+            (make_date(2021, 3, 5), 110.0)
+        ];
+        let dd = max_drawdown(&values).unwrap();
+        assert!(price_eql(dd.pct, 25.0));
+        assert_eq!(dd.peak_date, make_date(2021, 3, 2));
+        assert_eq!(dd.trough_date, make_date(2021, 3, 3));
+
+        assert!(max_drawdown(&values[..2]).is_none());
+        assert!(max_drawdown(&[]).is_none());
+    }
+}
@@ -25,6 +25,8 @@ pub fn ds_name_help() -> &'static str {
 
 pub fn stocks_file_help() -> &'static str {
     "CSV file containing stocks in portfolio, formatted as 'symbol,type,date,quantity,base_price' including a header line. \
+     An optional drip column enables dividend reinvestment, and optional currency and account columns set the listing \
+     currency and account. \
      Supported type values include stock, etf and index"
 }
 
@@ -33,16 +35,72 @@ pub fn stocks_config_help() -> &'static str {
      Both root and name can be set to \"$default\" which will use home path for root and sp_datastore for name.\n\
      \n\
      The stocks CSV block \"csv{\" should contain stocks in portfolio, with the following columns:\n\
-     \tsymbol\n\ttype\n\tdate\n\tquantity\n\tbase_price\n\
-     including a header line. Supported type values include cash, etf and index. A stocks CSV file block \"csv_file{\" can \
-     be used instead of a stocks CSV block. It should contain the path to a CSV file. The file should contain the CSV stocks data.\n\
+     \tsymbol\n\ttype\n\tdate\n\tquantity\n\tbase_price\n\tdrip (optional)\n\tcurrency (optional)\n\taccount (optional)\n\
+     including a header line. Supported type values include cash, etf and index. Quantities can be fractional. \
+     Set drip to true to reinvest dividends from the datastore into fractional lots bought at the close on the dividend date. \
+     Splits in the datastore after a stock buy date adjust its quantity and base_price, and dividends are paid on the shares held on their dates. \
+     A stocks CSV file block \"csv_file{\" can \
+     be used instead of a stocks CSV block. It should contain the path to a CSV file, relative paths are read from the directory of the config file. The file should contain the CSV stocks data.\n\
      \n\
      The closed positions CSV block \"csv{\" should contain closed positions in portfolio, with the following columns:\n\
-     \tsymbol\n\ttype\n\tbase_date\n\texit_date\n\tquantity\n\tbase_price\n\texit_price\n\tbase_fee\n\texit_fee\n\tdividend\n\
+     \tsymbol\n\ttype\n\tbase_date\n\texit_date\n\tquantity\n\tbase_price\n\texit_price\n\tbase_fee\n\texit_fee\n\tdividend\n\tcurrency (optional)\n\taccount (optional)\n\
      including a header line. Supported type values include cash, etf and index. The closed positions CSV \
      file block \"csv_file{\" can be used instead of a closed positions CSV block. It should contain the path to a CSV file. \
      The file should contain the CSV closed positions data.\n\
      \n\
+     The transactions CSV block \"csv{\" should contain a ledger of buys and sells, with the following columns:\n\
+     \tdate\n\taction\n\tsymbol\n\ttype\n\tquantity\n\tprice\n\tfee\n\tlot\n\
+     including a header line. Supported action values include buy and sell. The fee and lot columns can be empty. \
+     Open lots and closed positions are derived from the ledger and added to the stocks and closed positions. \
+     Sells are matched to buys of the same symbol using the lot_method, one of fifo, lifo, specific and average. \
+     Default is fifo. The lot_method applies to all sells of the config, there is no per-sell override. With specific, \
+     the sell lot column names the buy lot to sell from. The buy fee not matched to sells is added to the base_price \
+     of the open lot. The transactions CSV file \
+     block \"csv_file{\" can be used instead of a transactions CSV block.\n\
+     \n\
+     The cash flows CSV block \"csv{\" should contain a dated cash ledger, with the following columns:\n\
+     \tdate\n\ttype\n\tamount\n\tsymbol\n\
+     including a header line. Supported type values include deposit, withdrawal, interest, fee, dividend, purchase \
+     and proceeds. Amounts are positive, the type gives the direction. The symbol column can be empty. Purchases \
+     and proceeds of transactions are added to the cash flows. The cash balance on a date is the cash value plus the \
+     net of cash flows up to that date. Proceeds and dividends of closed positions and cash dividends of stocks from \
+     the datastore are not part of the cash balance, since their purchases are not in the cash flows. The perf, hist \
+     and risk reports hold the cash value from the first date and all of these cash flows as part of the portfolio, \
+     with deposits and withdrawals as external flows. The cash flows CSV file block \
+     \"csv_file{\" can be used instead of a cash flows CSV block.\n\
+     \n\
+     The currency columns hold three letter codes, such as EUR, and default to the base_currency. Default base_currency \
+     is USD. Prices, fees, dividends and the closes used by the perf, hist and risk reports in another currency are \
+     converted to the base currency on their dates using fx rates in the datastore, which the datastore update fetches from the provider as <currency><base>=X history, \
+     e.g. EURUSD=X.\n\
+     \n\
+     The accounts CSV block \"csv{\" should list other config files to include, with the following columns:\n\
+     \taccount\n\tconfig_file\n\
+     including a header line. A relative config_file is read from the directory of the including config file, \
+     and a config file cannot include itself, directly or through other included configs. Stocks and closed positions of each included config are added to this config, with \
+     their account column set to the account name when empty. Their cash, cash flows and transactions are added too, \
+     while their datastore, provider, http values and targets are ignored. The value and closed reports show subtotals by \
+     account when accounts are set.\n\
+     \n\
+     The targets CSV block \"csv{\" should contain target allocations used by the rebal report, with the following columns:\n\
+     \ttarget\n\tweight\n\ttolerance (optional)\n\
+     including a header line. The target is a symbol, or type: followed by a stock type, e.g. type:etf. A symbol \
+     target takes precedence over the type target of a stock. Weights are percents of the stocks latest value plus \
+     cash and should not sum to more than 100. The tolerance is the drift in percent points allowed before a target \
+     is rebalanced, default 0. The datastore update fetches recent prices of symbol targets that are not held, and \
+     the rebal report lists symbols it cannot price with no price, and type targets without held symbols with no \
+     holding, instead of a trade. The targets CSV file block \
+     \"csv_file{\" can be used instead of a targets CSV block.\n\
+     \n\
+     The provider selects the market data source used to update the datastore. Supported provider values \
+     include yahoo and local. Default is yahoo. The local provider reads CSV files from the directory set by \
+     provider_root, laid out as history/<symbol>.csv, dividends/<symbol>.csv and splits/<symbol>.csv.\n\
+     \n\
+     HTTP requests made by the yahoo provider can be tuned with http_connect_timeout and http_timeout (seconds, \
+     default 10 and 30), http_retries (default 3), http_backoff (initial retry delay in milliseconds, doubled on \
+     every retry, default 500), http_rate_limit (minimum milliseconds between requests to the same host, default 250), \
+     http_proxy and http_user_agent.\n\
+     \n\
      Sample config 1:\n\
      \tds_root: $default\n\
      \tds_name: my_datastore\n\
@@ -55,6 +113,9 @@ pub fn stocks_config_help() -> &'static str {
      \tds_root: $default\n\
      \tds_name: my_datastore\n\
      \tcash: 1250.00\n\
+     \tprovider: yahoo\n\
+     \thttp_timeout: 60\n\
+     \thttp_proxy: http://proxy.local:8080\n\
      \tstocks: csv_file{\n\
      \t  /path/to/my/stocks.csv\n\
      \t}\n\
@@ -79,14 +140,22 @@ pub fn export_file_help() -> &'static str {
     "Export file"
 }
 
+pub fn period_help() -> &'static str {
+    "Sampling period, one of daily, weekly, monthly, quarterly (default: daily)"
+}
+
 pub fn filter_help() -> &'static str {
-    "Filter stocks by type, symbols or expression;\n\
+    "Filter stocks by type, symbols, accounts or expression;\n\
      If type, must be one of 'cash', 'etf', or 'index'.\n\
-     If symbols, must be a comma separated list of symbol names.\n\
-     If expression, must follow the format '<field> <op> <value>', where:\n\
-     <field> : one of days, price, net, pct, div, size, value\n\
+     If symbols, must be a comma separated list of symbol names, e.g. AAPL,EURUSD=X.\n\
+     If accounts, must be 'account:' followed by a comma separated list of account names.\n\
+     Otherwise, an expression of comparisons '<field> <op> <value>' combined with and, or, not and parentheses, where:\n\
+     <field> : symbol, account (text); type (stock type); date, laddt (YYYY-MM-DD);\n\
+     \x20         days, price, net, pct, size, value (number);\n\
+     \x20         div, ladiv, yrdiv, dudiv, divret (number)\n\
      <op>    : one of =, !=, <, >, <=, >=\n\
-     Example : 'days > 365'"
+     <value> : a word, or text in single or double quotes\n\
+     Example : 'days > 365 and not (type = index or symbol = SPY)'"
 }
 
 // --------------------------------------------------------------------------------
@@ -153,6 +222,14 @@ pub fn symbol(required: bool, custom_help: Option<&'static str>) -> Arg<'static,
         .takes_value(true)
 }
 
+pub fn period(custom_help: Option<&'static str>) -> Arg<'static, 'static> {
+    Arg::with_name("period")
+        .short("u")
+        .long("period")
+        .help(custom_help.unwrap_or_else(period_help))
+        .takes_value(true)
+}
+
 pub fn export_file(custom_help: Option<&'static str>) -> Arg<'static, 'static> {
     Arg::with_name("export_file")
         .short("e")
@@ -195,6 +272,10 @@ pub fn parsed_symbol(parsed_args: &ArgMatches) -> Option<String> {
     parsed_args.value_of("symbol").map(String::from)
 }
 
+pub fn parsed_period(parsed_args: &ArgMatches) -> Option<datetime::Period> {
+    parsed_args.value_of("period").map(|period| datetime::str2period(period).expect("Invalid period"))
+}
+
 pub fn parsed_export_file(parsed_args: &ArgMatches) -> Option<String> {
     parsed_args.value_of("export_file").map(String::from)
 }
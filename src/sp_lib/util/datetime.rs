@@ -9,6 +9,11 @@ pub fn date2timestamp(date: &SPDate) -> i64 {
     date.and_hms_opt(0, 0, 0).unwrap_or(NaiveDateTime::MIN).timestamp()
 }
 
+#[inline(always)]
+pub fn timestamp2date(ts: i64) -> SPDate {
+    NaiveDateTime::from_timestamp_opt(ts, 0).unwrap_or(NaiveDateTime::MIN).date()
+}
+
 #[inline(always)]
 pub fn make_date(year: i32, month: u32, day: u32) -> SPDate {
     NaiveDate::from_ymd_opt(year, month, day).unwrap_or(NaiveDate::MIN)
@@ -29,6 +34,16 @@ pub fn date_plus_days(date: &SPDate, days: i64) -> SPDate {
     *date + Duration::days(days)
 }
 
+// Same day of the following year, or March 1 for February 29.
+pub fn date_plus_year(date: &SPDate) -> SPDate {
+    date.with_year(date.year() + 1).unwrap_or_else(|| make_date(date.year() + 1, 3, 1))
+}
+
+#[inline(always)]
+pub fn date_year(date: &SPDate) -> i32 {
+    date.year()
+}
+
 #[inline(always)]
 pub fn earliest_date() -> SPDate {
     make_date(1970, 1, 1)
@@ -61,6 +76,46 @@ pub fn count_days(from_date: &SPDate, to_date: &SPDate) -> i64 {
     to_date.signed_duration_since(*from_date).num_days()
 }
 
+// --------------------------------------------------------------------------------
+// Period
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Period {
+    Daily,
+    Weekly,
+    Monthly,
+    Quarterly
+}
+
+pub fn period2str(period: Period) -> &'static str {
+    match period {
+        Period::Daily => "daily",
+        Period::Weekly => "weekly",
+        Period::Monthly => "monthly",
+        Period::Quarterly => "quarterly"
+    }
+}
+
+pub fn str2period(period_str: &str) -> Result<Period, Error> {
+    match period_str.to_lowercase().as_str() {
+        "daily" => Ok(Period::Daily),
+        "weekly" => Ok(Period::Weekly),
+        "monthly" => Ok(Period::Monthly),
+        "quarterly" => Ok(Period::Quarterly),
+        _ => Err(format!("Unknown period '{}'", period_str).into())
+    }
+}
+
+// First date of the period containing date, weeks start on Monday.
+pub fn period_start(date: &SPDate, period: Period) -> SPDate {
+    match period {
+        Period::Daily => *date,
+        Period::Weekly => *date - Duration::days(date.weekday().num_days_from_monday() as i64),
+        Period::Monthly => make_date(date.year(), date.month(), 1),
+        Period::Quarterly => make_date(date.year(), (date.month() - 1) / 3 * 3 + 1, 1)
+    }
+}
+
 pub fn check_dup_or_back_gap(old_date: &SPDate, new_date: &SPDate) -> Result<(), Error> {
     match new_date.cmp(old_date) {
         Ordering::Equal => Err(format!("Duplicate date {}", new_date.format("%Y-%m-%d")).into()),
@@ -96,6 +151,12 @@ mod tests {
         assert_eq!(date2timestamp(&date), 1613520000);
     }
 
+    #[test]
+    fn test_timestamp2date() {
+        assert_eq!(timestamp2date(1613520000), make_date(2021, 2, 17));
+        assert_eq!(timestamp2date(1613572200), make_date(2021, 2, 17));
+    }
+
     #[test]
     fn test_today_plus_delta() {
         let today = today();
@@ -112,6 +173,14 @@ mod tests {
         assert_eq!(date_plus_days(&dt, -1), dt + Duration::days(-1));
     }
 
+    #[test]
+    fn test_date_plus_year() {
+        assert_eq!(date_plus_year(&make_date(2021, 2, 17)), make_date(2022, 2, 17));
+        assert_eq!(date_plus_year(&make_date(2020, 2, 29)), make_date(2021, 3, 1));
+        assert_eq!(date_plus_year(&make_date(2020, 12, 31)), make_date(2021, 12, 31));
+        assert_eq!(date_year(&make_date(2020, 12, 31)), 2020);
+    }
+
     #[test]
     fn test_earliest_date() {
         let earliest = earliest_date();
@@ -180,6 +249,28 @@ mod tests {
         assert!(!is_weekend(&mon));
     }
 
+    #[test]
+    fn test_period() {
+        for period in [Period::Daily, Period::Weekly, Period::Monthly, Period::Quarterly] {
+            assert_eq!(str2period(period2str(period)).unwrap(), period);
+        }
+        assert_eq!(str2period("Weekly").unwrap(), Period::Weekly);
+        assert_eq!(format!("{}", str2period("hourly").unwrap_err()), "Unknown period 'hourly'");
+    }
+
+    #[test]
+    fn test_period_start() {
+        let wed = make_date(2021, 3, 17);
+        assert_eq!(period_start(&wed, Period::Daily), wed);
+        assert_eq!(period_start(&wed, Period::Weekly), make_date(2021, 3, 15));
+        assert_eq!(period_start(&make_date(2021, 3, 15), Period::Weekly), make_date(2021, 3, 15));
+        assert_eq!(period_start(&make_date(2021, 3, 21), Period::Weekly), make_date(2021, 3, 15));
+        assert_eq!(period_start(&wed, Period::Monthly), make_date(2021, 3, 1));
+        assert_eq!(period_start(&wed, Period::Quarterly), make_date(2021, 1, 1));
+        assert_eq!(period_start(&make_date(2021, 4, 1), Period::Quarterly), make_date(2021, 4, 1));
+        assert_eq!(period_start(&make_date(2021, 12, 31), Period::Quarterly), make_date(2021, 10, 1));
+    }
+
     #[test]
     fn test_count_days() {
         let dt1 = make_date(2021, 3, 19);
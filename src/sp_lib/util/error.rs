@@ -1,5 +1,6 @@
 use std::{env, fmt, io, num, str};
 use curl;
+use serde_json;
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Error {
@@ -74,3 +75,9 @@ impl From<curl::Error> for Error {
         Error::new(format!("{item}"))
     }
 }
+
+impl From<serde_json::Error> for Error {
+    fn from(item: serde_json::Error) -> Self {
+        Error::new(format!("{item}"))
+    }
+}
@@ -46,6 +46,13 @@ pub fn calc_daily(price: PriceType, days: i64) -> PriceType {
     }
 }
 
+// Format share quantity with up to 4 decimal places, dropping trailing zeros.
+pub fn quantity_format(qty: PriceType) -> String {
+    let txt = format!("{:.4}", qty);
+    let txt = txt.trim_end_matches('0').trim_end_matches('.');
+    if txt == "-0" { String::from("0") } else { String::from(txt) }
+}
+
 // --------------------------------------------------------------------------------
 // Unit Tests
 
@@ -115,4 +122,14 @@ mod tests {
         assert!(price_eql(calc_daily(price, days2), 562.5));
         assert!(price_eql(calc_daily(price, days5), 225.0));
     }
+
+    #[test]
+    fn test_quantity_format() {
+        assert_eq!(quantity_format(100.0), "100");
+        assert_eq!(quantity_format(10.5), "10.5");
+        assert_eq!(quantity_format(0.1 + 0.2), "0.3");
+        assert_eq!(quantity_format(1.23456), "1.2346");
+        assert_eq!(quantity_format(0.0), "0");
+        assert_eq!(quantity_format(-0.00001), "0");
+    }
 }
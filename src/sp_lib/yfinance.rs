@@ -1,2 +1,4 @@
 pub mod types;
+pub mod http;
 pub mod query;
+pub mod chart;
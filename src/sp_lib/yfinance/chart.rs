@@ -0,0 +1,240 @@
+use serde_json::Value;
+
+use crate::datastore::history::{History, HistoryEntry};
+use crate::datastore::dividends::{Dividends, DividendEntry};
+use crate::datastore::splits::{Splits, SplitEntry};
+use crate::util::datetime;
+use crate::util::datetime::SPDate;
+use crate::util::error::Error;
+
+// --------------------------------------------------------------------------------
+// Chart
+//
+// Parsed Yahoo v8 chart JSON response. Dates are adjusted by the exchange gmtoffset.
+
+pub struct Chart {
+    history: History,
+    dividends: Dividends,
+    splits: Splits
+}
+
+impl Chart {
+    pub fn new(symbol: &str) -> Self {
+        Chart {
+            history: History::new(symbol),
+            dividends: Dividends::new(symbol),
+            splits: Splits::new(symbol)
+        }
+    }
+
+    pub fn parse_json(symbol: &str, json: &str) -> Result<Self, Error> {
+        let root: Value = serde_json::from_str(json)?;
+        let chart = get_field(&root, "chart")?;
+
+        let error = get_field(chart, "error")?;
+        if !error.is_null() {
+            let desc = error.get("description").and_then(|d| d.as_str()).unwrap_or("unknown error");
+            return Err(format!("Chart: Query error '{}'", desc).into());
+        }
+
+        let result = get_field(chart, "result")?
+            .get(0)
+            .ok_or_else(|| Error::from("Chart: Empty result"))?;
+
+        let gmtoffset = result
+            .get("meta")
+            .and_then(|meta| meta.get("gmtoffset"))
+            .and_then(|off| off.as_i64())
+            .unwrap_or(0);
+
+        let mut cht = Chart::new(symbol);
+        cht.parse_history(result, gmtoffset)?;
+        if let Some(events) = result.get("events") {
+            cht.parse_dividends(events, gmtoffset)?;
+            cht.parse_splits(events, gmtoffset)?;
+        }
+        Ok(cht)
+    }
+
+    #[inline(always)]
+    pub fn history(&self) -> &History {
+        &self.history
+    }
+
+    #[inline(always)]
+    pub fn dividends(&self) -> &Dividends {
+        &self.dividends
+    }
+
+    #[inline(always)]
+    pub fn splits(&self) -> &Splits {
+        &self.splits
+    }
+
+    fn parse_history(&mut self, result: &Value, gmtoffset: i64) -> Result<(), Error> {
+        let timestamps = match result.get("timestamp") {
+            Some(ts) => get_array(ts, "timestamp")?,
+            None => return Ok(()) // No trading days in requested range
+        };
+
+        let indicators = get_field(result, "indicators")?;
+        let quote = get_field(indicators, "quote")?
+            .get(0)
+            .ok_or_else(|| Error::from("Chart: Empty quote"))?;
+
+        let open = get_array(get_field(quote, "open")?, "open")?;
+        let high = get_array(get_field(quote, "high")?, "high")?;
+        let low = get_array(get_field(quote, "low")?, "low")?;
+        let close = get_array(get_field(quote, "close")?, "close")?;
+        let volume = get_array(get_field(quote, "volume")?, "volume")?;
+        let adj_close = match indicators.get("adjclose").and_then(|adj| adj.get(0)) {
+            Some(adj) => get_array(get_field(adj, "adjclose")?, "adjclose")?,
+            None => close
+        };
+
+        let size = timestamps.len();
+        for (name, arr) in [("open", open), ("high", high), ("low", low), ("close", close), ("volume", volume), ("adjclose", adj_close)] {
+            if arr.len() != size {
+                return Err(format!("Chart: Invalid {} length={} expected={}", name, arr.len(), size).into());
+            }
+        }
+
+        for i in 0..size {
+            // Yahoo reports null quotes for days without trades; skip them.
+            if let (Some(o), Some(h), Some(l), Some(c), Some(a), Some(v)) =
+                (open[i].as_f64(), high[i].as_f64(), low[i].as_f64(), close[i].as_f64(), adj_close[i].as_f64(), volume[i].as_u64()) {
+                let date = to_date(get_i64(&timestamps[i], "timestamp")?, gmtoffset);
+                self.history.add_entry(HistoryEntry::new(date, o, h, l, c, a, v));
+            }
+        }
+        Ok(())
+    }
+
+    fn parse_dividends(&mut self, events: &Value, gmtoffset: i64) -> Result<(), Error> {
+        let mut entries: Vec<DividendEntry> = Vec::new();
+        if let Some(Value::Object(divs)) = events.get("dividends") {
+            for div in divs.values() {
+                let date = to_date(get_i64(get_field(div, "date")?, "date")?, gmtoffset);
+                let amount = get_f64(get_field(div, "amount")?, "amount")?;
+                entries.push(DividendEntry::new(date, amount));
+            }
+        }
+
+        entries.sort_by_key(|entry| entry.date);
+        for entry in entries {
+            self.dividends.add_entry(entry);
+        }
+        Ok(())
+    }
+
+    fn parse_splits(&mut self, events: &Value, gmtoffset: i64) -> Result<(), Error> {
+        let mut entries: Vec<SplitEntry> = Vec::new();
+        if let Some(Value::Object(splts)) = events.get("splits") {
+            for splt in splts.values() {
+                let date = to_date(get_i64(get_field(splt, "date")?, "date")?, gmtoffset);
+                let numerator = get_f64(get_field(splt, "numerator")?, "numerator")?;
+                let denominator = get_f64(get_field(splt, "denominator")?, "denominator")?;
+                entries.push(SplitEntry::new(date, format!("{}:{}", numerator, denominator)));
+            }
+        }
+
+        entries.sort_by_key(|entry| entry.date);
+        for entry in entries {
+            self.splits.add_entry(entry);
+        }
+        Ok(())
+    }
+}
+
+// --------------------------------------------------------------------------------
+// Private Helpers
+
+#[inline(always)]
+fn to_date(timestamp: i64, gmtoffset: i64) -> SPDate {
+    datetime::timestamp2date(timestamp + gmtoffset)
+}
+
+fn get_field<'a>(value: &'a Value, name: &str) -> Result<&'a Value, Error> {
+    value.get(name).ok_or_else(|| format!("Chart: Missing field '{}'", name).into())
+}
+
+fn get_array<'a>(value: &'a Value, name: &str) -> Result<&'a Vec<Value>, Error> {
+    value.as_array().ok_or_else(|| format!("Chart: Field '{}' is not an array", name).into())
+}
+
+fn get_i64(value: &Value, name: &str) -> Result<i64, Error> {
+    value.as_i64().ok_or_else(|| format!("Chart: Field '{}' is not an integer", name).into())
+}
+
+fn get_f64(value: &Value, name: &str) -> Result<f64, Error> {
+    value.as_f64().ok_or_else(|| format!("Chart: Field '{}' is not a number", name).into())
+}
+
+// --------------------------------------------------------------------------------
+// Unit Tests
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_chart_parse_json() {
+        let json = r#"{"chart":{"result":[{
+            "meta":{"symbol":"AAPL","gmtoffset":-18000},
+            "timestamp":[1613572200,1613658600,1613745000],
+            "events":{
+                "dividends":{"1613658600":{"amount":0.205,"date":1613658600}},
+                "splits":{"1613572200":{"date":1613572200,"numerator":4,"denominator":1,"splitRatio":"4:1"}}},
+            "indicators":{
+                "quote":[{"open":[10.0,null,12.0],"high":[11.0,null,13.0],"low":[9.0,null,11.0],"close":[10.5,null,12.5],"volume":[1000,null,1200]}],
+                "adjclose":[{"adjclose":[10.25,null,12.25]}]}}],
+            "error":null}}"#;
+
+        let chart = Chart::parse_json("AAPL", json).unwrap();
+
+        let hist = chart.history();
+        assert_eq!(hist.symbol(), "AAPL");
+        assert_eq!(hist.count(), 2);
+        let entry = &hist.entries()[0];
+        assert_eq!(entry.date, datetime::make_date(2021, 2, 17));
+        assert_eq!((entry.open, entry.high, entry.low, entry.close, entry.adj_close, entry.volume),
+                   (10.0, 11.0, 9.0, 10.5, 10.25, 1000));
+        assert_eq!(hist.entries()[1].date, datetime::make_date(2021, 2, 19));
+
+        let div = chart.dividends();
+        assert_eq!(div.count(), 1);
+        assert_eq!(div.entries()[0].date, datetime::make_date(2021, 2, 18));
+        assert_eq!(div.entries()[0].price, 0.205);
+
+        let splt = chart.splits();
+        assert_eq!(splt.count(), 1);
+        assert_eq!(splt.entries()[0].date, datetime::make_date(2021, 2, 17));
+        assert_eq!(splt.entries()[0].split, "4:1");
+    }
+
+    #[test]
+    fn test_chart_parse_json_no_data() {
+        let json = r#"{"chart":{"result":[{"meta":{"symbol":"AAPL","gmtoffset":-18000},"indicators":{"quote":[{}]}}],"error":null}}"#;
+        let chart = Chart::parse_json("AAPL", json).unwrap();
+        assert_eq!(chart.history().count(), 0);
+        assert_eq!(chart.dividends().count(), 0);
+        assert_eq!(chart.splits().count(), 0);
+    }
+
+    #[test]
+    fn test_chart_parse_json_errors() {
+        let json = r#"{"chart":{"result":null,"error":{"code":"Not Found","description":"No data found, symbol may be delisted"}}}"#;
+        assert_eq!(format!("{}", Chart::parse_json("FOO", json).err().unwrap()),
+                   "Chart: Query error 'No data found, symbol may be delisted'");
+
+        let json = r#"{"chart":{"result":[{"timestamp":[1613572200],"indicators":{"quote":[{"open":[],"high":[],"low":[],"close":[],"volume":[]}]}}],"error":null}}"#;
+        assert_eq!(format!("{}", Chart::parse_json("AAPL", json).err().unwrap()),
+                   "Chart: Invalid open length=0 expected=1");
+
+        let json = r#"{"quote":{}}"#;
+        assert_eq!(format!("{}", Chart::parse_json("AAPL", json).err().unwrap()),
+                   "Chart: Missing field 'chart'");
+
+        assert!(Chart::parse_json("AAPL", "not json").is_err());
+    }
+}
@@ -0,0 +1,312 @@
+use std::collections::HashMap;
+use std::str;
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use curl::easy;
+
+use crate::util::error::Error;
+
+// --------------------------------------------------------------------------------
+// HttpConfig
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HttpConfig {
+    connect_timeout: u64, // Seconds
+    timeout: u64,         // Seconds
+    retries: u32,
+    backoff: u64,         // Milliseconds, doubled on every retry
+    rate_limit: u64,      // Milliseconds between requests to the same host
+    proxy: String,
+    user_agent: String
+}
+
+impl HttpConfig {
+    pub fn new() -> Self {
+        HttpConfig {
+            connect_timeout: 10,
+            timeout: 30,
+            retries: 3,
+            backoff: 500,
+            rate_limit: 250,
+            proxy: String::new(),
+            user_agent: String::new()
+        }
+    }
+
+    pub fn with_connect_timeout(mut self, secs: u64) -> Self {
+        self.connect_timeout = secs;
+        self
+    }
+
+    pub fn with_timeout(mut self, secs: u64) -> Self {
+        self.timeout = secs;
+        self
+    }
+
+    pub fn with_retries(mut self, retries: u32) -> Self {
+        self.retries = retries;
+        self
+    }
+
+    pub fn with_backoff(mut self, millis: u64) -> Self {
+        self.backoff = millis;
+        self
+    }
+
+    pub fn with_rate_limit(mut self, millis: u64) -> Self {
+        self.rate_limit = millis;
+        self
+    }
+
+    pub fn with_proxy(mut self, proxy: &str) -> Self {
+        self.proxy = String::from(proxy);
+        self
+    }
+
+    pub fn with_user_agent(mut self, user_agent: &str) -> Self {
+        self.user_agent = String::from(user_agent);
+        self
+    }
+
+    #[inline(always)] pub fn connect_timeout(&self) -> u64 { self.connect_timeout }
+    #[inline(always)] pub fn timeout(&self) -> u64 { self.timeout }
+    #[inline(always)] pub fn retries(&self) -> u32 { self.retries }
+    #[inline(always)] pub fn backoff(&self) -> u64 { self.backoff }
+    #[inline(always)] pub fn rate_limit(&self) -> u64 { self.rate_limit }
+    #[inline(always)] pub fn proxy(&self) -> &str { &self.proxy }
+    #[inline(always)] pub fn user_agent(&self) -> &str { &self.user_agent }
+}
+
+impl Default for HttpConfig {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// --------------------------------------------------------------------------------
+// HTTP Get
+
+pub fn get(url: &str, config: &HttpConfig) -> Result<String, Error> {
+    let mut attempt: u32 = 0;
+    loop {
+        wait_rate_limit(url_host(url), config.rate_limit());
+        match request(url, config) {
+            Ok(body) => return Ok(body),
+            Err(failure) => {
+                if !failure.retry || attempt >= config.retries() {
+                    return Err(failure.error);
+                }
+                thread::sleep(backoff_delay(config.backoff(), attempt, jitter_seed()));
+                attempt += 1;
+            }
+        }
+    }
+}
+
+// --------------------------------------------------------------------------------
+// Private Helpers
+
+struct Failure {
+    error: Error,
+    retry: bool
+}
+
+impl Failure {
+    fn new(error: Error, retry: bool) -> Self {
+        Failure { error, retry }
+    }
+}
+
+impl From<curl::Error> for Failure {
+    fn from(item: curl::Error) -> Self {
+        let retry =
+            item.is_operation_timedout() ||
+            item.is_couldnt_connect() ||
+            item.is_couldnt_resolve_host() ||
+            item.is_couldnt_resolve_proxy() ||
+            item.is_send_error() ||
+            item.is_recv_error() ||
+            item.is_got_nothing();
+        Failure::new(item.into(), retry)
+    }
+}
+
+fn request(url: &str, config: &HttpConfig) -> Result<String, Failure> {
+    let mut handle = easy::Easy::new();
+
+    handle.url(url)?;
+    handle.follow_location(true)?;
+    handle.connect_timeout(Duration::from_secs(config.connect_timeout()))?;
+    handle.timeout(Duration::from_secs(config.timeout()))?;
+    if !config.proxy().is_empty() {
+        handle.proxy(config.proxy())?;
+    }
+    if !config.user_agent().is_empty() {
+        handle.useragent(config.user_agent())?;
+    }
+
+    let mut data: Vec<u8> = Vec::new();
+    {
+        let mut transfer = handle.transfer();
+        transfer.write_function(|new_data| {
+            data.extend_from_slice(new_data);
+            Ok(new_data.len())
+        })?;
+        transfer.perform()?;
+    }
+
+    let code = handle.response_code()?;
+    if !(200..300).contains(&code) {
+        return Err(Failure::new(format!("HTTP status {} for '{}'", code, url).into(), is_retry_status(code)));
+    }
+
+    match str::from_utf8(&data) {
+        Ok(txt) => Ok(String::from(txt)),
+        Err(e) => Err(Failure::new(format!("Invalid UTF-8 response for '{}' - {}", url, e).into(), false))
+    }
+}
+
+#[inline(always)]
+fn is_retry_status(code: u32) -> bool {
+    code == 429 || code >= 500
+}
+
+fn url_host(url: &str) -> &str {
+    let rest = match url.find("://") {
+        Some(pos) => &url[pos + 3..],
+        None => url
+    };
+    match rest.find(['/', '?']) {
+        Some(pos) => &rest[..pos],
+        None => rest
+    }
+}
+
+fn backoff_delay(base: u64, attempt: u32, seed: u64) -> Duration {
+    let delay = base.saturating_mul(1 << attempt.min(16));
+    let jitter = seed % (delay / 2 + 1);
+    Duration::from_millis(delay + jitter)
+}
+
+fn jitter_seed() -> u64 {
+    match SystemTime::now().duration_since(UNIX_EPOCH) {
+        Ok(dur) => dur.subsec_nanos() as u64,
+        Err(_) => 0
+    }
+}
+
+// Last reserved request time per host
+static HOST_REQUESTS: Mutex<Option<HashMap<String, Instant>>> = Mutex::new(None);
+
+fn wait_rate_limit(host: &str, rate_limit: u64) {
+    if rate_limit == 0 {
+        return;
+    }
+
+    let interval = Duration::from_millis(rate_limit);
+    let now = Instant::now();
+    let wait = {
+        let mut guard = HOST_REQUESTS.lock().unwrap_or_else(|e| e.into_inner());
+        let requests = guard.get_or_insert_with(HashMap::new);
+        let next = match requests.get(host) {
+            Some(last) if *last + interval > now => *last + interval,
+            _ => now
+        };
+        requests.insert(String::from(host), next);
+        next - now
+    };
+
+    if wait > Duration::from_millis(0) {
+        thread::sleep(wait);
+    }
+}
+
+// --------------------------------------------------------------------------------
+// Unit Tests
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_http_config() {
+        let config = HttpConfig::new();
+        assert_eq!(config.connect_timeout(), 10);
+        assert_eq!(config.timeout(), 30);
+        assert_eq!(config.retries(), 3);
+        assert_eq!(config.backoff(), 500);
+        assert_eq!(config.rate_limit(), 250);
+        assert_eq!(config.proxy(), "");
+        assert_eq!(config.user_agent(), "");
+
+        let config = HttpConfig::new()
+            .with_connect_timeout(5)
+            .with_timeout(20)
+            .with_retries(1)
+            .with_backoff(100)
+            .with_rate_limit(0)
+            .with_proxy("http://proxy.local:8080")
+            .with_user_agent("Mozilla/5.0");
+        assert_eq!(config.connect_timeout(), 5);
+        assert_eq!(config.timeout(), 20);
+        assert_eq!(config.retries(), 1);
+        assert_eq!(config.backoff(), 100);
+        assert_eq!(config.rate_limit(), 0);
+        assert_eq!(config.proxy(), "http://proxy.local:8080");
+        assert_eq!(config.user_agent(), "Mozilla/5.0");
+    }
+
+    #[test]
+    fn test_url_host() {
+        assert_eq!(url_host("https://query1.finance.yahoo.com/v8/finance/chart/AAPL?period1=0"), "query1.finance.yahoo.com");
+        assert_eq!(url_host("http://localhost:8080?q=1"), "localhost:8080");
+        assert_eq!(url_host("example.com/path"), "example.com");
+    }
+
+    #[test]
+    fn test_retry_status() {
+        assert!(is_retry_status(429));
+        assert!(is_retry_status(500));
+        assert!(is_retry_status(503));
+        assert!(!is_retry_status(401));
+        assert!(!is_retry_status(404));
+    }
+
+    #[test]
+    fn test_backoff_delay() {
+        assert_eq!(backoff_delay(100, 0, 0), Duration::from_millis(100));
+        assert_eq!(backoff_delay(100, 1, 0), Duration::from_millis(200));
+        assert_eq!(backoff_delay(100, 2, 0), Duration::from_millis(400));
+        assert_eq!(backoff_delay(100, 2, 7), Duration::from_millis(407));
+        assert_eq!(backoff_delay(100, 2, 201), Duration::from_millis(400));
+        assert_eq!(backoff_delay(0, 3, 12345), Duration::from_millis(0));
+
+        for seed in 0..1000 {
+            let delay = backoff_delay(100, 1, seed);
+            assert!(delay >= Duration::from_millis(200) && delay <= Duration::from_millis(300));
+        }
+    }
+
+    #[test]
+    fn test_rate_limit() {
+        let host = "test_rate_limit.local";
+        let start = Instant::now();
+        wait_rate_limit(host, 50);
+        wait_rate_limit(host, 50);
+        wait_rate_limit(host, 50);
+        assert!(start.elapsed() >= Duration::from_millis(100));
+
+        let start = Instant::now();
+        wait_rate_limit("test_rate_limit_none.local", 0);
+        wait_rate_limit("test_rate_limit_none.local", 0);
+        assert!(start.elapsed() < Duration::from_millis(50));
+    }
+
+    #[test]
+    fn test_get_unsupported_scheme() {
+        // Unsupported scheme fails in curl without network access
+        let config = HttpConfig::new().with_retries(0).with_rate_limit(0);
+        assert!(get("foo://localhost/bar", &config).is_err());
+    }
+}
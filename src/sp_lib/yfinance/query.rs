@@ -1,7 +1,5 @@
-use std::str;
-use curl;
-use curl::easy;
-
+use crate::yfinance::http;
+use crate::yfinance::http::HttpConfig;
 use crate::yfinance::types;
 use crate::yfinance::types::{Interval, Events};
 use crate::util::datetime;
@@ -52,28 +50,90 @@ impl HistoryQuery {
     }
 
     pub fn execute(self: &mut HistoryQuery) -> Result<(), Error> {
-        self.result.clear();
-        url_request(&self.url(), &mut self.result)?;
+        self.execute_with(&HttpConfig::new())
+    }
+
+    pub fn execute_with(self: &mut HistoryQuery, config: &HttpConfig) -> Result<(), Error> {
+        self.result = http::get(&self.url(), config)?;
         Ok(())
     }
 }
 
 // --------------------------------------------------------------------------------
-// Private Helpers
+// ChartQuery
+
+pub struct ChartQuery {
+    symbol: String,
+    from_date: SPDate,
+    to_date: SPDate,
+    interval: Interval,
+    pub result: String
+}
+
+impl ChartQuery {
+    pub fn new(symbol: String,
+               from_date: SPDate,
+               to_date: SPDate,
+               interval: Interval) -> ChartQuery {
+        ChartQuery {
+            symbol,
+            from_date,
+            to_date,
+            interval,
+            result: String::new()
+        }
+    }
 
-fn url_request(url: &str, result: &mut String) -> Result<(), Error> {
-    let mut handle = easy::Easy::new();
+    pub fn url(self: &ChartQuery) -> String {
+        let base_url = "https://query1.finance.yahoo.com/v8/finance/chart";
+        let period1 = datetime::date2timestamp(&self.from_date);
+        let period2 = datetime::date2timestamp(&self.to_date);
+        let int_str = types::interval2str(self.interval);
+        format!("{}/{}?period1={}&period2={}&interval={}&events=div%7Csplit&includeAdjustedClose=true",
+                base_url,
+                self.symbol,
+                period1,
+                period2,
+                int_str)
+    }
 
-    handle.url(url)?;
+    pub fn execute(self: &mut ChartQuery) -> Result<(), Error> {
+        self.execute_with(&HttpConfig::new())
+    }
 
-    let mut transfer = handle.transfer();
-    transfer.write_function(|new_data| {
-        let txt = str::from_utf8(new_data).unwrap_or("");
-        result.push_str(txt);
-        Ok(txt.len())
-    })?;
+    pub fn execute_with(self: &mut ChartQuery, config: &HttpConfig) -> Result<(), Error> {
+        self.result = http::get(&self.url(), config)?;
+        Ok(())
+    }
+}
 
-    transfer.perform()?;
+// --------------------------------------------------------------------------------
+// Unit Tests
 
-    Ok(())
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_history_query_url() {
+        let query = HistoryQuery::new(String::from("AAPL"),
+                                      datetime::make_date(2021, 2, 11),
+                                      datetime::make_date(2021, 2, 13),
+                                      Interval::Daily,
+                                      Events::Dividend);
+        assert_eq!(query.url(),
+                   "https://query1.finance.yahoo.com/v7/finance/download/AAPL?\
+                    period1=1613001600&period2=1613174400&interval=1d&events=div&includeAdjustedClose=true");
+    }
+
+    #[test]
+    fn test_chart_query_url() {
+        let query = ChartQuery::new(String::from("AAPL"),
+                                    datetime::make_date(2021, 2, 11),
+                                    datetime::make_date(2021, 2, 13),
+                                    Interval::Daily);
+        assert_eq!(query.url(),
+                   "https://query1.finance.yahoo.com/v8/finance/chart/AAPL?\
+                    period1=1613001600&period2=1613174400&interval=1d&events=div%7Csplit&includeAdjustedClose=true");
+    }
 }
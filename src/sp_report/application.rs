@@ -1,17 +1,25 @@
 use crate::arguments::Arguments;
+use std::collections::HashMap;
+
 use sp_lib::datastore::datastore;
-use sp_lib::portfolio::{algorithms, extra_sort_ftns, stocks_config, stocks_update};
+use sp_lib::portfolio::{algorithms, cash_flow, currency, extra_sort_ftns, stocks_config, stocks_update};
+use sp_lib::portfolio::closed_position::ClosedPositionList;
+use sp_lib::portfolio::stock::StockList;
 use sp_lib::report::report_params::ReportParams;
 use sp_lib::report::report_type;
 use sp_lib::report::report_type::ReportType;
 use sp_lib::report::reports;
 use sp_lib::util::common_app;
+use sp_lib::util::datetime::Period;
 use sp_lib::util::error::Error;
 
 pub struct Application {
     args: Arguments,
     rtype: ReportType,
     config: stocks_config::StocksConfig,
+    currencies: HashMap<String, String>,
+    all_stocks: StockList,
+    all_positions: ClosedPositionList,
     ds: datastore::DataStore
 }
 
@@ -24,6 +32,9 @@ impl common_app::AppTrait for Application {
             args,
             rtype: ReportType::Value,
             config,
+            currencies: HashMap::new(),
+            all_stocks: StockList::new(),
+            all_positions: ClosedPositionList::new(),
             ds
         }
     }
@@ -64,12 +75,33 @@ impl Application {
     }
 
     fn update(self: &mut Application) -> Result<(), Error> {
+        // Dividends are reinvested in the shares held on their dates, then all lots are split adjusted
+        stocks_update::reinvest_dividends_from_ds(self.config.stocks_mut(), &self.ds)?;
+        stocks_update::split_adjust_from_ds(self.config.stocks_mut(), &self.ds)?;
+
         let count = stocks_update::update_stocks_from_ds(self.config.stocks_mut(), &self.ds)?;
 
         if count != self.config.stocks().len() {
             return Err(format!("update stocks failed; updated={} expected={}", count, self.config.stocks().len()).into());
         }
 
+        // Cash dividends of open lots are added to the holding cash flows
+        let div_flows = cash_flow::dividend_cash_flows_from_ds(self.config.stocks(), &self.ds)?;
+        self.config.holding_cash_flows_mut().extend(div_flows);
+        cash_flow::sort_cash_flows(self.config.holding_cash_flows_mut());
+
+        // Foreign currency prices are converted to the base currency
+        let base = String::from(self.config.base_currency());
+        self.currencies = currency::symbol_currencies(self.config.stocks(), self.config.closed_positions(), &base);
+        currency::convert_stocks_from_ds(self.config.stocks_mut(), &self.ds, &base)?;
+        currency::convert_positions_from_ds(self.config.closed_positions_mut(), &self.ds, &base)?;
+        currency::convert_cash_flows_from_ds(self.config.cash_flows_mut(), &self.ds, &base)?;
+        currency::convert_cash_flows_from_ds(self.config.holding_cash_flows_mut(), &self.ds, &base)?;
+
+        // Lots before filtering, for reports that look at the whole portfolio such as wash sales
+        self.all_stocks = self.config.stocks().clone();
+        self.all_positions = self.config.closed_positions().clone();
+
         Ok(())
     }
 
@@ -96,15 +128,31 @@ impl Application {
     fn report(self: &Application) {
         reports::print_report(
             ReportParams::new(self.rtype, &self.config)
+                .with_all_lots(&self.all_stocks, &self.all_positions)
                 .show_groupby(self.args.show_groupby())
                 .with_datastore(&self.ds)
+                .with_date_range(self.args.from(), self.args.to())
+                .with_period(self.args.period().unwrap_or(Period::Daily))
+                .with_benchmark(self.args.bench().map(String::as_str))
+                .with_risk_free_rate(self.args.rf_rate().unwrap_or(0.0))
+                .with_loss_thresholds(self.args.min_loss().unwrap_or(0.0), self.args.min_pct_loss().unwrap_or(0.0))
+                .with_cash_only(self.args.cash_only())
+                .with_currencies(&self.currencies)
         );
     }
 
     fn export(self: &Application) -> Result<(), Error> {
         if let Some(export_file) = self.args.export_file() {
             let report_params = ReportParams::new(self.rtype, &self.config)
-                .with_datastore(&self.ds);
+                .with_all_lots(&self.all_stocks, &self.all_positions)
+                .with_datastore(&self.ds)
+                .with_date_range(self.args.from(), self.args.to())
+                .with_period(self.args.period().unwrap_or(Period::Daily))
+                .with_benchmark(self.args.bench().map(String::as_str))
+                .with_risk_free_rate(self.args.rf_rate().unwrap_or(0.0))
+                .with_loss_thresholds(self.args.min_loss().unwrap_or(0.0), self.args.min_pct_loss().unwrap_or(0.0))
+                .with_cash_only(self.args.cash_only())
+                .with_currencies(&self.currencies);
             reports::export_report(report_params, export_file)?;
         }
         Ok(())
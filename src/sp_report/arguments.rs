@@ -1,7 +1,7 @@
 extern crate clap;
 
 use clap::{App, Arg};
-use sp_lib::util::common_args;
+use sp_lib::util::{common_args, datetime};
 
 pub struct Arguments {
     config_file: String,
@@ -10,9 +10,17 @@ pub struct Arguments {
     include: Option<String>,
     exclude: Option<String>,
     export_file: Option<String>,
+    from: Option<datetime::SPDate>,
+    to: Option<datetime::SPDate>,
+    period: Option<datetime::Period>,
+    bench: Option<String>,
+    rf_rate: Option<f64>,
+    min_loss: Option<f64>,
+    min_pct_loss: Option<f64>,
     show_groupby: bool,
     desc: bool,
-    match_symbols: bool
+    match_symbols: bool,
+    cash_only: bool
 }
 
 impl Arguments {
@@ -21,12 +29,35 @@ impl Arguments {
         let parsed_args = App::new("Stock Portfolio Report")
             .version(common_args::app_version())
             .about("Generate portfolio reports. Supported reports include gains & losses, \
-                    top/bottom performers, volatility, day change, closed positions, dividends \
-                    and summary.")
+                    top/bottom performers, volatility, day change, closed positions, dividends, \
+                    summary, performance, value history, benchmark comparison, risk, tax lots, wash sales, tax-loss harvesting and rebalancing.")
 
             // Options
             .arg(common_args::stocks_config())
-            .arg(common_args::export_file(Some("Export gains and losses table to a csv file")))
+            .arg(common_args::export_file(Some("Export gains and losses table to a csv file. \
+                                                For tax report, export realized lots as a Form 8949 worksheet")))
+            .arg(common_args::from_date(false, Some("Performance start date YYYY-MM-DD (default: first buy date). \
+                                                   For tax report, first date sold of realized lots")))
+            .arg(common_args::to_date(false, Some("Performance end date YYYY-MM-DD (default: today). \
+                                                 For tax report, last date sold of realized lots")))
+            .arg(common_args::period(Some("Value history sampling period, one of daily, weekly, monthly, quarterly (default: daily)")))
+            .arg(Arg::with_name("bench")
+                 .short("b")
+                 .long("bench")
+                 .help("Benchmark symbol in the datastore, used with bench and risk reports (e.g. SPY)")
+                 .takes_value(true))
+            .arg(Arg::with_name("rf_rate")
+                 .long("rfrate")
+                 .help("Annual risk-free rate in percent, used with risk report (default: 0)")
+                 .takes_value(true))
+            .arg(Arg::with_name("min_loss")
+                 .long("minloss")
+                 .help("Minimum lot loss, used with harvest report (default: 0)")
+                 .takes_value(true))
+            .arg(Arg::with_name("min_pct_loss")
+                 .long("minpct")
+                 .help("Minimum lot loss in percent of base value, used with harvest report (default: 0)")
+                 .takes_value(true))
             .arg(Arg::with_name("report_type")
                  .short("p")
                  .long("type")
@@ -37,7 +68,15 @@ impl Arguments {
                         daych  : Stocks day change\n\
                         closed : Closed positions value\n\
                         divid  : Stoks dividend\n\
-                        sum    : Stocks summary")
+                        sum    : Stocks summary\n\
+                        perf   : Time-weighted and money-weighted returns\n\
+                        hist   : Portfolio value history\n\
+                        bench  : Benchmark comparison\n\
+                        risk   : Beta, Sharpe, Sortino and drawdown\n\
+                        tax    : Short and long term realized and unrealized gains by tax year\n\
+                        wash   : Wash sales of closed losses and their replacement lots\n\
+                        harvest: Tax-loss harvesting candidates\n\
+                        rebal  : Drift from target allocations and rebalancing trades")
                  .takes_value(true))
             .arg(Arg::with_name("order_by")
                  .short("o")
@@ -54,7 +93,8 @@ impl Arguments {
                         volume : day volume         | change  : day change\n\
                         pctchg : day percent change | valchg  : day value change\n\
                         low    : day low price      | high    : day high price\n\
-                        laddt  : Latest div date    | divret  : Cumulative dividend return")
+                        laddt  : Latest div date    | divret  : Cumulative dividend return\n\
+                        account: account name")
                  .takes_value(true))
             .arg(Arg::with_name("include")
                  .short("i")
@@ -81,6 +121,9 @@ impl Arguments {
                  .short("m")
                  .long("match-symbols")
                  .help("Match closed positions to configured stock symbols post filtering and ordering"))
+            .arg(Arg::with_name("cash_only")
+                 .long("cash-only")
+                 .help("For rebal report, rebalance by buying with the cash balance only, without sales"))
             .get_matches();
 
         let config_file = common_args::parsed_stocks_config(&parsed_args);
@@ -89,9 +132,17 @@ impl Arguments {
         let include = parsed_args.value_of("include").map(String::from);
         let exclude = parsed_args.value_of("exclude").map(String::from);
         let export_file = common_args::parsed_export_file(&parsed_args);
+        let from = common_args::parsed_from_date(&parsed_args);
+        let to = common_args::parsed_to_date(&parsed_args);
+        let period = common_args::parsed_period(&parsed_args);
+        let bench = parsed_args.value_of("bench").map(String::from);
+        let rf_rate = parsed_args.value_of("rf_rate").map(|rate| rate.parse::<f64>().expect("Invalid risk-free rate"));
+        let min_loss = parsed_args.value_of("min_loss").map(|loss| loss.parse::<f64>().expect("Invalid minimum loss"));
+        let min_pct_loss = parsed_args.value_of("min_pct_loss").map(|pct| pct.parse::<f64>().expect("Invalid minimum loss percent"));
         let show_groupby = parsed_args.is_present("show_groupby");
         let desc = parsed_args.is_present("desc");
         let match_symbols = parsed_args.is_present("match_symbols");
+        let cash_only = parsed_args.is_present("cash_only");
 
         Arguments {
             config_file,
@@ -100,9 +151,17 @@ impl Arguments {
             include,
             exclude,
             export_file,
+            from,
+            to,
+            period,
+            bench,
+            rf_rate,
+            min_loss,
+            min_pct_loss,
             show_groupby,
             desc,
-            match_symbols
+            match_symbols,
+            cash_only
         }
     }
 
@@ -136,6 +195,41 @@ impl Arguments {
         self.export_file.as_ref()
     }
 
+    #[inline(always)]
+    pub fn from(&self) -> Option<datetime::SPDate> {
+        self.from
+    }
+
+    #[inline(always)]
+    pub fn to(&self) -> Option<datetime::SPDate> {
+        self.to
+    }
+
+    #[inline(always)]
+    pub fn period(&self) -> Option<datetime::Period> {
+        self.period
+    }
+
+    #[inline(always)]
+    pub fn bench(&self) -> Option<&String> {
+        self.bench.as_ref()
+    }
+
+    #[inline(always)]
+    pub fn rf_rate(&self) -> Option<f64> {
+        self.rf_rate
+    }
+
+    #[inline(always)]
+    pub fn min_loss(&self) -> Option<f64> {
+        self.min_loss
+    }
+
+    #[inline(always)]
+    pub fn min_pct_loss(&self) -> Option<f64> {
+        self.min_pct_loss
+    }
+
     #[inline(always)]
     pub fn show_groupby(&self) -> bool {
         self.show_groupby
@@ -150,6 +244,11 @@ impl Arguments {
     pub fn match_symbols(&self) -> bool {
         self.match_symbols
     }
+
+    #[inline(always)]
+    pub fn cash_only(&self) -> bool {
+        self.cash_only
+    }
 }
 
 impl Default for Arguments {
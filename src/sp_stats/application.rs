@@ -1,10 +1,12 @@
 use crate::arguments::Arguments;
 use sp_lib::datastore::{datastore, dividends, history};
-use sp_lib::stats::{description, hist_desc, hist_ftns};
+use sp_lib::stats::{description, hist_desc, hist_ftns, risk_ftns};
 use sp_lib::portfolio::stocks_config;
 use sp_lib::util::{common_app, datetime, misc};
 use sp_lib::util::error::Error;
 use std::collections::HashSet;
+use std::fs::File;
+use std::io::prelude::*;
 
 const DESC: &str = "desc";
 const DIVDESC: &str = "divdesc";
@@ -17,6 +19,15 @@ const ROC: &str = "roc";
 const PCTCH: &str = "pctch";
 const MVOLAT: &str = "mvolat";
 const RSI: &str = "rsi";
+const EMA: &str = "ema";
+const MACD: &str = "macd";
+const BOLL: &str = "boll";
+const ATR: &str = "atr";
+const STOCH: &str = "stoch";
+const OBV: &str = "obv";
+const CHAN: &str = "chan";
+const CORR: &str = "corr";
+const COV: &str = "cov";
 
 pub struct Application {
     args: Arguments,
@@ -48,6 +59,12 @@ impl common_app::AppTrait for Application {
             return Err(format!("Field {} is not allowed", self.args.field()).into());
         }
 
+        match self.args.calculate().as_str() {
+            CORR | COV => return self.calc_matrix(),
+            calc if self.args.symbols().len() > 1 => return Err(format!("Calculation {} supports a single symbol", calc).into()),
+            _ => {}
+        }
+
         self.load_data()?;
         self.print_date_and_symbol();
 
@@ -63,6 +80,13 @@ impl common_app::AppTrait for Application {
             PCTCH => self.calc_pctch()?,
             MVOLAT => self.calc_mvolat()?,
             RSI => self.calc_rsi()?,
+            EMA => self.calc_ema()?,
+            MACD => self.calc_macd()?,
+            BOLL => self.calc_boll()?,
+            ATR => self.calc_atr()?,
+            STOCH => self.calc_stoch()?,
+            OBV => self.calc_obv()?,
+            CHAN => self.calc_chan()?,
             _ => return Err(format!("Invalid calculate option - '{}'", self.args.calculate()).into())
         };
 
@@ -89,6 +113,30 @@ impl Application {
         println!("  from: {}", first_date.format("%Y-%m-%d"));
         println!("    to: {}", last_date.format("%Y-%m-%d"));
         println!("symbol: {}", self.args.symbol());
+        if let Some(period) = self.args.period() {
+            println!("period: {}", datetime::period2str(period));
+        }
+    }
+
+    // Unit of the calculation window, one bar of the sampling period.
+    fn window_unit(&self) -> &'static str {
+        match self.args.period().unwrap_or(datetime::Period::Daily) {
+            datetime::Period::Daily => "day",
+            datetime::Period::Weekly => "week",
+            datetime::Period::Monthly => "month",
+            datetime::Period::Quarterly => "quarter"
+        }
+    }
+
+    fn select_hist(&self, symbol: &str) -> Result<history::History, Error> {
+        let hist = match self.args.from() {
+            Some(from) => history::History::ds_select_if(&self.ds, symbol, |entry| entry.date >= from)?,
+            None => history::History::ds_select_all(&self.ds, symbol)?
+        };
+        match self.args.period() {
+            Some(period) => Ok(hist.resample(period)),
+            None => Ok(hist)
+        }
     }
 
     fn load_data(&mut self) -> Result<(), Error> {
@@ -101,10 +149,7 @@ impl Application {
                 };
             }
         } else if self.ds.symbol_exists(history::tag(), symbol) {
-            self.hist = match self.args.from() {
-                Some(from) => history::History::ds_select_if(&self.ds, symbol, |entry| entry.date >= from)?,
-                None => history::History::ds_select_all(&self.ds, symbol)?
-            };
+            self.hist = self.select_hist(symbol)?;
         }
         Ok(())
     }
@@ -187,13 +232,57 @@ impl Application {
         if show_field {
             println!(" field: {}", self.args.field());
         }
-        println!("window: {}", misc::count_format(self.args.window(), "day"));
+        println!("window: {}", misc::count_format(self.args.window(), self.window_unit()));
         println!("{:>6}: ", name);
         for (date, price) in dps.iter() {
             println!("{} {:.4}", date.format("%Y-%m-%d"), price);
         }
     }
 
+    pub fn print_dp_pair_list(&self, dps: &hist_ftns::DatePricePairList, name: &str, columns: [&str; 2]) {
+        println!("window: {}", misc::count_format(self.args.window(), self.window_unit()));
+        println!("{:>6}: {:>12} {:>12}", name, columns[0], columns[1]);
+        for (date, first, second) in dps.iter() {
+            println!("{} {:12.4} {:12.4}", date.format("%Y-%m-%d"), first, second);
+        }
+    }
+
+    pub fn print_dp_triple_list(&self, dps: &hist_ftns::DatePriceTripleList, name: &str, columns: [&str; 3]) {
+        println!(" field: {}", self.args.field());
+        println!("{:>6}: {:>12} {:>12} {:>12}", name, columns[0], columns[1], columns[2]);
+        for (date, first, second, third) in dps.iter() {
+            println!("{} {:12.4} {:12.4} {:12.4}", date.format("%Y-%m-%d"), first, second, third);
+        }
+    }
+
+    // Calculation parameters, or the defaults when not given.
+    fn params(&self, defaults: &[f64]) -> Result<Vec<f64>, Error> {
+        let params = match self.args.params() {
+            Some(params) => params,
+            None => return Ok(defaults.to_vec())
+        };
+
+        let values: Vec<f64> = params
+            .split(',')
+            .map(|param| param.trim().parse::<f64>())
+            .collect::<Result<Vec<f64>, _>>()
+            .map_err(|_| format!("Invalid parameters '{}'", params))?;
+        if values.len() != defaults.len() {
+            return Err(format!("Invalid parameters '{}', expected {} comma separated values", params, defaults.len()).into());
+        }
+        Ok(values)
+    }
+
+    // Calculation parameters that must be a number of days.
+    fn day_params(&self, defaults: &[usize]) -> Result<Vec<usize>, Error> {
+        let defaults: Vec<f64> = defaults.iter().map(|days| *days as f64).collect();
+        let values = self.params(&defaults)?;
+        if values.iter().any(|days| *days < 1.0 || days.fract() != 0.0) {
+            return Err(format!("Invalid parameters '{}', days must be whole numbers of at least 1", self.args.params().unwrap()).into());
+        }
+        Ok(values.iter().map(|days| *days as usize).collect())
+    }
+
     fn check_window(&self, min_window: usize) -> Result<(), Error> {
         if self.args.window() < min_window {
             Err(format!("Window size {} less than required size {}", self.args.window(), min_window).into())
@@ -242,4 +331,121 @@ impl Application {
         self.print_dp_list(&rsi, RSI, false);
         Ok(())
     }
+
+    fn calc_ema(&self) -> Result<(), Error> {
+        self.check_window(1)?;
+        let ema = hist_ftns::hist_field_ema(&self.hist, self.args.field(), self.args.window())?;
+        self.print_dp_list(&ema, EMA, true);
+        Ok(())
+    }
+
+    fn calc_macd(&self) -> Result<(), Error> {
+        let params = self.day_params(&[12, 26, 9])?;
+        let macd = hist_ftns::hist_field_macd(&self.hist, self.args.field(), params[0], params[1], params[2])?;
+        println!("params: fast={} slow={} signal={}", params[0], params[1], params[2]);
+        self.print_dp_triple_list(&macd, MACD, ["macd", "signal", "histogram"]);
+        Ok(())
+    }
+
+    fn calc_boll(&self) -> Result<(), Error> {
+        self.check_window(2)?;
+        let params = self.params(&[2.0])?;
+        let boll = hist_ftns::hist_field_bollinger(&self.hist, self.args.field(), self.args.window(), params[0])?;
+        println!("window: {}", misc::count_format(self.args.window(), self.window_unit()));
+        println!("params: mult={}", params[0]);
+        self.print_dp_triple_list(&boll, BOLL, ["lower", "middle", "upper"]);
+        Ok(())
+    }
+
+    fn calc_atr(&self) -> Result<(), Error> {
+        self.check_window(1)?;
+        let atr = hist_ftns::hist_atr(&self.hist, self.args.window())?;
+        self.print_dp_list(&atr, ATR, false);
+        Ok(())
+    }
+
+    fn calc_stoch(&self) -> Result<(), Error> {
+        self.check_window(1)?;
+        let params = self.day_params(&[3])?;
+        let stoch = hist_ftns::hist_stochastic(&self.hist, self.args.window(), params[0])?;
+        println!("params: smooth={}", params[0]);
+        self.print_dp_pair_list(&stoch, STOCH, ["%K", "%D"]);
+        Ok(())
+    }
+
+    fn calc_obv(&self) -> Result<(), Error> {
+        let obv = hist_ftns::hist_field_obv(&self.hist, self.args.field())?;
+        println!(" field: {}", self.args.field());
+        println!("{:>6}: ", OBV);
+        for (date, value) in obv.iter() {
+            println!("{} {:.0}", date.format("%Y-%m-%d"), value);
+        }
+        Ok(())
+    }
+
+    fn calc_chan(&self) -> Result<(), Error> {
+        self.check_window(1)?;
+        let chan = hist_ftns::hist_channel(&self.hist, self.args.window())?;
+        self.print_dp_pair_list(&chan, CHAN, ["low", "high"]);
+        Ok(())
+    }
+
+    fn calc_matrix(&self) -> Result<(), Error> {
+        let symbols = self.args.symbols();
+        if symbols.len() < 2 {
+            return Err(format!("Calculation {} requires at least two symbols", self.args.calculate()).into());
+        }
+
+        let mut prices: Vec<hist_ftns::DatePriceList> = Vec::with_capacity(symbols.len());
+        for symbol in symbols.iter() {
+            if !self.ds.symbol_exists(history::tag(), symbol) {
+                return Err(format!("Symbol {} history does not exist", symbol).into());
+            }
+            let hist = self.select_hist(symbol)?;
+            prices.push(hist_ftns::hist_field_prices(&hist, self.args.field()));
+        }
+
+        // Dates missing from any symbol are dropped
+        let (dates, _) = risk_ftns::align_series(&prices);
+        let returns = risk_ftns::aligned_returns(&prices)?;
+        let matrix = if self.args.calculate() == CORR {
+            risk_ftns::correlation_matrix(&returns)?
+        } else {
+            risk_ftns::covariance_matrix(&returns)?
+        };
+
+        let (first_date, last_date) = Application::date_range(&dates, |date| *date);
+        println!("   from: {}", first_date.format("%Y-%m-%d"));
+        println!("     to: {}", last_date.format("%Y-%m-%d"));
+        println!("symbols: {}", symbols.join(","));
+        println!("  field: {}", self.args.field());
+        if let Some(period) = self.args.period() {
+            println!(" period: {}", datetime::period2str(period));
+        }
+        println!("   days: {}", returns.first().map_or(0, |rets| rets.len()));
+        println!("{:>7}:", self.args.calculate());
+
+        print!("{:8}", "");
+        for symbol in symbols.iter() {
+            print!(" {:>10}", symbol);
+        }
+        println!();
+        for (symbol, row) in symbols.iter().zip(matrix.iter()) {
+            print!("{:8}", symbol);
+            for value in row.iter() {
+                print!(" {:10.6}", value);
+            }
+            println!();
+        }
+
+        if let Some(export_file) = self.args.export_file() {
+            let mut file = File::create(export_file)?;
+            writeln!(file, "Symbol,{}", symbols.join(","))?;
+            for (symbol, row) in symbols.iter().zip(matrix.iter()) {
+                let values: Vec<String> = row.iter().map(|value| format!("{:.6}", value)).collect();
+                writeln!(file, "{},{}", symbol, values.join(","))?;
+            }
+        }
+        Ok(())
+    }
 }
@@ -9,7 +9,10 @@ pub struct Arguments {
     symbol: String,
     field: String,
     window: usize,
-    from: Option<datetime::SPDate>
+    params: Option<String>,
+    from: Option<datetime::SPDate>,
+    period: Option<datetime::Period>,
+    export_file: Option<String>
 }
 
 impl Arguments {
@@ -17,16 +20,20 @@ impl Arguments {
         #[rustfmt::skip]
         let parsed_args = App::new("Stock Portfolio Stats Tool")
             .version(common_args::app_version())
-            .about("Stats tool - describe and calculate")
+            .about("Stats tool - describe and calculate, including correlation and covariance across symbols")
 
             // Options
             .arg(common_args::stocks_config())
             .arg(common_args::from_date(false, Some("Start from date YYYY-MM-DD")))
-            .arg(common_args::symbol(true, None))
+            .arg(common_args::symbol(true, Some("Stock symbol, or comma separated symbols with corr and cov calculations")))
+            .arg(common_args::period(Some("Resample history into bars of period, one of daily, weekly, monthly, quarterly (default: daily)\n\
+                                           Window and calculation parameters count bars of that period")))
+            .arg(common_args::export_file(Some("Export correlation or covariance matrix to a csv file")))
             .arg(Arg::with_name("calculate")
                  .short("c")
                  .long("calc")
-                 .help("Calculate stats, one of desc, divdesc, sa, vwap, volat, sma, mvwap, roc, pctch, mvolat, rsi.\n\
+                 .help("Calculate stats, one of desc, divdesc, sa, vwap, volat, sma, mvwap, roc, pctch, mvolat, rsi,\n\
+                        ema, macd, boll, atr, stoch, obv, chan, corr, cov.\n\
                         desc    : describe history\n\
                         divdesc : describe dividends\n\
                         sa      : calculate simple average price\n\
@@ -37,21 +44,38 @@ impl Arguments {
                         roc     : calculate rate of change\n\
                         pctch   : calculate percent change relative to from date\n\
                         mvolat  : calculate moving volatility\n\
-                        rsi     : Calculate Relative Strength Index")
+                        rsi     : Calculate Relative Strength Index\n\
+                        ema     : calculate exponential moving average price\n\
+                        macd    : calculate MACD line, signal and histogram\n\
+                        boll    : calculate Bollinger Bands\n\
+                        atr     : calculate Average True Range\n\
+                        stoch   : calculate stochastic oscillator %K and %D\n\
+                        obv     : calculate On-Balance Volume\n\
+                        chan    : calculate rolling low and high channel\n\
+                        corr    : calculate daily return correlation matrix of symbols\n\
+                        cov     : calculate daily return covariance matrix of symbols")
                  .required(true)
                  .takes_value(true))
             .arg(Arg::with_name("window")
                  .short("w")
                  .long("window")
-                 .help("Number of days, required with sma, mvwap, roc, mvolat and rsi calculations\n\
-                        Required minimum: sma=1, mvwap=1, roc=2, mvolat=1, rsi=2")
+                 .help("Number of days, required with sma, mvwap, roc, mvolat, rsi, ema, boll, atr, stoch and chan calculations\n\
+                        Required minimum: sma=1, mvwap=1, roc=2, mvolat=1, rsi=2, ema=1, boll=2, atr=1, stoch=1, chan=1")
+                 .takes_value(true))
+            .arg(Arg::with_name("params")
+                 .short("a")
+                 .long("params")
+                 .help("Comma separated calculation parameters\n\
+                        macd  : fast days, slow days, signal days (default: 12,26,9)\n\
+                        boll  : standard deviation multiplier (default: 2)\n\
+                        stoch : %D smoothing days (default: 3)")
                  .takes_value(true))
             .arg(Arg::with_name("field")
                  .short("i")
                  .long("field")
                  .help("Symbol history field to use in calculation.\n\
                         One of open, high, low, close, adj_close. Default adj_close.\n\
-                        Applies to sa, vwap, volat, sma, mvwap, roc, pctch and mvolat")
+                        Applies to sa, vwap, volat, sma, mvwap, roc, pctch, mvolat, ema, macd, boll, obv, corr and cov")
                  .takes_value(true))
             .get_matches();
 
@@ -64,7 +88,10 @@ impl Arguments {
                 Some(win) => win.parse::<usize>().expect("Invalid calculation window"),
                 None => 0
             },
-            from: common_args::parsed_from_date(&parsed_args)
+            params: parsed_args.value_of("params").map(String::from),
+            from: common_args::parsed_from_date(&parsed_args),
+            period: common_args::parsed_period(&parsed_args),
+            export_file: common_args::parsed_export_file(&parsed_args)
         }
     }
 
@@ -83,6 +110,11 @@ impl Arguments {
         &self.symbol
     }
 
+    // Comma separated symbols
+    pub fn symbols(&self) -> Vec<&str> {
+        self.symbol.split(',').map(|sym| sym.trim()).filter(|sym| !sym.is_empty()).collect()
+    }
+
     #[inline(always)]
     pub fn field(&self) -> &String {
         &self.field
@@ -93,10 +125,25 @@ impl Arguments {
         self.window
     }
 
+    #[inline(always)]
+    pub fn params(&self) -> Option<&String> {
+        self.params.as_ref()
+    }
+
     #[inline(always)]
     pub fn from(&self) -> Option<datetime::SPDate> {
         self.from
     }
+
+    #[inline(always)]
+    pub fn period(&self) -> Option<datetime::Period> {
+        self.period
+    }
+
+    #[inline(always)]
+    pub fn export_file(&self) -> Option<&String> {
+        self.export_file.as_ref()
+    }
 }
 
 impl Default for Arguments {
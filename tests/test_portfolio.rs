@@ -6,13 +6,27 @@ use sp_lib::util::datetime::*;
 use sp_lib::util::fixed_price::*;
 use sp_lib::util::temp_file;
 use sp_lib::util::price_type::price_eql;
-use sp_lib::portfolio::closed_position::ClosedPosition;
+use sp_lib::util::error::Error;
+use sp_lib::datastore::datastore::DataStore;
+use sp_lib::datastore::dividends::{self, Dividends, DividendEntry};
+use sp_lib::datastore::fx_rates::{self, FxRates};
+use sp_lib::datastore::history::{self, History, HistoryEntry};
+use sp_lib::datastore::splits::{self, Splits, SplitEntry};
+use sp_lib::portfolio::cash_flow::{self, CashFlowType};
+use sp_lib::portfolio::closed_position::{ClosedPosition, ClosedPositionList};
+use sp_lib::portfolio::currency;
+use sp_lib::portfolio::lot_method::LotMethod;
+use sp_lib::portfolio::performance::{self, MarketHistory};
 use sp_lib::portfolio::stock_type::*;
 use sp_lib::portfolio::stock::*;
 use sp_lib::portfolio::algorithms::*;
 use sp_lib::portfolio::stocks_update::*;
 use sp_lib::portfolio::stocks_config::*;
 use sp_lib::portfolio::stocks_reader::*;
+use sp_lib::provider::provider_trait::MarketDataProvider;
+use sp_lib::provider::provider_type::ProviderType;
+use sp_lib::provider::providers;
+use sp_lib::yfinance::http::HttpConfig;
 use sp_lib::report::report_params::ReportParams;
 use sp_lib::report::report_type::ReportType;
 use sp_lib::report::reports;
@@ -20,8 +34,8 @@ use sp_lib::report::reports;
 #[test]
 fn test_stock_list() {
     let mut list = StockList::new();
-    list.push(make_stock("AAPL", StockType::Cash, today_plus_days(-3), 100, 120.25, 125.25));
-    list.push(make_stock("DELL", StockType::Cash, today_plus_days(-2), 100, 79.21, 79.71));
+    list.push(make_stock("AAPL", StockType::Cash, today_plus_days(-3), 100.0, 120.25, 125.25));
+    list.push(make_stock("DELL", StockType::Cash, today_plus_days(-2), 100.0, 79.21, 79.71));
     assert_eq!(list.len(), 2);
     assert!(price_eql(net_notional(&list), 550.0));
     assert!(price_eql(latest_notional(&list), 20496.0));
@@ -40,13 +54,13 @@ fn test_stock_list() {
     assert!(price_eql(pct_chg, 2.757445));
     assert!(price_eql(pct_chg_wd, 3.312694));
 
-    let total_size: u32 = list.iter().map(|stock| stock.quantity).sum();
-    assert_eq!(total_size, 200);
+    let total_size: f64 = list.iter().map(|stock| stock.quantity).sum();
+    assert_eq!(total_size, 200.0);
 }
 
 #[test]
 fn test_stock_aggregate() {
-    fn test(groupby: &HashMap<String, (u32, Price, Price)>, symbol: &str, size: u32, bprice: Price, cprice: Price) {
+    fn test(groupby: &HashMap<String, (Quantity, Price, Price)>, symbol: &str, size: Quantity, bprice: Price, cprice: Price) {
         let size_prices = groupby.get(symbol).unwrap();
         assert_eq!(size_prices.0, size);
         assert!(price_eql(size_prices.1, bprice));
@@ -54,19 +68,44 @@ fn test_stock_aggregate() {
     }
 
     let mut list = StockList::new();
-    list.push(make_stock("AAPL", StockType::Cash, today_plus_days(-3), 100, 120.25, 125.25));
-    list.push(make_stock("DELL", StockType::Cash, today_plus_days(-2), 100, 79.21, 79.71));
-    list.push(make_stock("AAPL", StockType::Cash, today_plus_days(-2), 100, 122.0, 125.25));
+    list.push(make_stock("AAPL", StockType::Cash, today_plus_days(-3), 100.0, 120.25, 125.25));
+    list.push(make_stock("DELL", StockType::Cash, today_plus_days(-2), 100.0, 79.21, 79.71));
+    list.push(make_stock("AAPL", StockType::Cash, today_plus_days(-2), 100.0, 122.0, 125.25));
 
     let gby = stock_aggregate(&list);
     assert_eq!(gby.len(), 2);
-    test(&gby, "AAPL", 200, 24225.0, 25050.0);
-    test(&gby, "DELL", 100, 7921.0, 7971.0);
+    test(&gby, "AAPL", 200.0, 24225.0, 25050.0);
+    test(&gby, "DELL", 100.0, 7921.0, 7971.0);
+}
+
+#[test]
+fn test_account_aggregate() {
+    let mut list: StockList = vec![make_stock("AAPL", StockType::Cash, today_plus_days(-3), 100.0, 120.25, 125.25),
+                                   make_stock("AAPL", StockType::Cash, today_plus_days(-2), 100.0, 122.25, 125.25),
+                                   make_stock("DELL", StockType::Cash, today_plus_days(-2), 100.0, 79.21, 79.71)];
+    list[0].account = String::from("ira");
+    list[1].account = String::from("ira");
+    list[1].cum_dividend = 20.0;
+
+    let accounts = account_aggregate(&list);
+    assert_eq!(accounts.len(), 2);
+
+    let (lots, base_value, cur_value, cum_div) = accounts["ira"];
+    assert_eq!(lots, 2);
+    assert!(price_eql(base_value, 24250.0));
+    assert!(price_eql(cur_value, 25050.0));
+    assert!(price_eql(cum_div, 20.0));
+
+    let (lots, base_value, cur_value, cum_div) = accounts[""];
+    assert_eq!(lots, 1);
+    assert!(price_eql(base_value, 7921.0));
+    assert!(price_eql(cur_value, 7971.0));
+    assert!(price_eql(cum_div, 0.0));
 }
 
 #[test]
 fn test_dividend_aggregate() {
-    fn test(groupby: &HashMap<String, (u32, Price, Price)>, symbol: &str, size: u32, price: Price, base: Price) {
+    fn test(groupby: &HashMap<String, (Quantity, Price, Price)>, symbol: &str, size: Quantity, price: Price, base: Price) {
         let size_prices = groupby.get(symbol).unwrap();
         assert_eq!(size_prices.0, size);
         assert!(price_eql(size_prices.1, price));
@@ -74,9 +113,9 @@ fn test_dividend_aggregate() {
     }
 
     let mut list = StockList::new();
-    list.push(make_stock("AAPL", StockType::Cash, today_plus_days(-3), 100, 120.25, 125.25));
-    list.push(make_stock("DELL", StockType::Cash, today_plus_days(-2), 100, 79.21, 79.71));
-    list.push(make_stock("AAPL", StockType::Cash, today_plus_days(-2), 100, 122.0, 125.25));
+    list.push(make_stock("AAPL", StockType::Cash, today_plus_days(-3), 100.0, 120.25, 125.25));
+    list.push(make_stock("DELL", StockType::Cash, today_plus_days(-2), 100.0, 79.21, 79.71));
+    list.push(make_stock("AAPL", StockType::Cash, today_plus_days(-2), 100.0, 122.0, 125.25));
 
     list[0].cum_dividend = 10.0;
     list[1].cum_dividend = 20.15;
@@ -84,30 +123,30 @@ fn test_dividend_aggregate() {
 
     let gby = dividend_aggregate(&list);
     assert_eq!(gby.len(), 2);
-    test(&gby, "AAPL", 200, 25.25, 24225.00);
-    test(&gby, "DELL", 100, 20.15, 7921.00);
+    test(&gby, "AAPL", 200.0, 25.25, 24225.00);
+    test(&gby, "DELL", 100.0, 20.15, 7921.00);
 }
 
 #[test]
 fn test_stock_groupby() {
     let mut list = StockList::new();
-    list.push(make_stock("DELL", StockType::Cash, today_plus_days(-2), 100, 79.21,  79.71));
-    list.push(make_stock("AAPL", StockType::Cash, today_plus_days(-3), 200, 120.25, 125.25));
-    list.push(make_stock("ICLN", StockType::ETF,   today_plus_days(0),  400, 24.10,  24.12));
-    list.push(make_stock("AAPL", StockType::Cash, today_plus_days(0),  100, 125.50, 125.75));
+    list.push(make_stock("DELL", StockType::Cash, today_plus_days(-2), 100.0, 79.21,  79.71));
+    list.push(make_stock("AAPL", StockType::Cash, today_plus_days(-3), 200.0, 120.25, 125.25));
+    list.push(make_stock("ICLN", StockType::ETF,   today_plus_days(0),  400.0, 24.10,  24.12));
+    list.push(make_stock("AAPL", StockType::Cash, today_plus_days(0),  100.0, 125.50, 125.75));
 
-    let sym_sizes = stock_groupby(&list, |_| 0, |s, q| s.quantity + q);
+    let sym_sizes = stock_groupby(&list, |_| 0.0, |s, q| s.quantity + q);
     assert_eq!(sym_sizes.len(), 3);
-    assert_eq!(*sym_sizes.get("AAPL").unwrap(), 300);
-    assert_eq!(*sym_sizes.get("DELL").unwrap(), 100);
-    assert_eq!(*sym_sizes.get("ICLN").unwrap(), 400);
+    assert_eq!(*sym_sizes.get("AAPL").unwrap(), 300.0);
+    assert_eq!(*sym_sizes.get("DELL").unwrap(), 100.0);
+    assert_eq!(*sym_sizes.get("ICLN").unwrap(), 400.0);
 }
 
 #[test]
 fn test_stock_update_from_csv() {
     let csv = "Date,Open,High,Low,Close,Adj Close,Volume\n\
                2021-02-26,24.90,32.0,24.0,28.0,28.25,11000";
-    let mut stock = Stock::new(String::from("STCK"), StockType::Cash, make_date(2021, 2, 1), 100, 24.0);
+    let mut stock = Stock::new(String::from("STCK"), StockType::Cash, make_date(2021, 2, 1), 100.0, 24.0);
     assert!(update_stock_from_csv(&mut stock, &csv).unwrap());
     assert!(price_eql(stock.latest_price, 28.25));
     assert_eq!(stock.latest_date, make_date(2021, 2, 26));
@@ -119,7 +158,7 @@ fn test_stock_update_from_csv2() {
                2021-02-24,25.0,30.0,20.0,26.0,26.0,10000\n\
                2021-02-25,26.10,31.0,22.0,24.0,24.0,9000\n\
                2021-02-26,24.90,32.0,24.0,28.0,28.25,11000";
-    let mut stock = Stock::new(String::from("STCK"), StockType::Cash, make_date(2021, 2, 1), 100, 24.0);
+    let mut stock = Stock::new(String::from("STCK"), StockType::Cash, make_date(2021, 2, 1), 100.0, 24.0);
     assert!(update_stock_from_csv(&mut stock, &csv).unwrap());
     assert!(price_eql(stock.latest_price, 28.25));
     assert_eq!(stock.latest_date, make_date(2021, 2, 26));
@@ -131,7 +170,7 @@ fn test_stock_update_from_csv_zero_price() {
                2021-02-24,25.0,30.0,20.0,26.0,26.0,10000\n\
                2021-02-25,26.10,31.0,22.0,24.0,24.0,9000\n\
                2021-02-26,24.90,32.0,24.0,28.0,0.00,11000";
-    let mut stock = Stock::new(String::from("STCK"), StockType::Cash, make_date(2021, 2, 1), 100, 24.0);
+    let mut stock = Stock::new(String::from("STCK"), StockType::Cash, make_date(2021, 2, 1), 100.0, 24.0);
     assert!(!update_stock_from_csv(&mut stock, &csv).unwrap());
     assert!(price_eql(stock.latest_price, 0.00));
     assert_eq!(stock.latest_date, earliest_date());
@@ -140,7 +179,7 @@ fn test_stock_update_from_csv_zero_price() {
 #[test]
 fn test_stock_update_from_csv_no_data() {
     let csv = "Date,Open,High,Low,Close,Adj Close,Volume";
-    let mut stock = Stock::new(String::from("STCK"), StockType::Cash, make_date(2021, 2, 1), 100, 24.0);
+    let mut stock = Stock::new(String::from("STCK"), StockType::Cash, make_date(2021, 2, 1), 100.0, 24.0);
     assert!(!update_stock_from_csv(&mut stock, &csv).unwrap());
     assert!(price_eql(stock.latest_price, 0.00));
     assert_eq!(stock.latest_date, earliest_date());
@@ -150,7 +189,7 @@ fn test_stock_update_from_csv_no_data() {
 fn test_stock_update_from_csv_incomplete_data() {
     let csv = "Date,Open,High,Low,Close,Adj Close,Volume\n\
                2021-02-24,25.0,30.0";
-    let mut stock = Stock::new(String::from("STCK"), StockType::Cash, make_date(2021, 2, 1), 100, 24.0);
+    let mut stock = Stock::new(String::from("STCK"), StockType::Cash, make_date(2021, 2, 1), 100.0, 24.0);
     assert!(update_stock_from_csv(&mut stock, &csv).is_err());
     assert!(price_eql(stock.latest_price, 0.00));
     assert_eq!(stock.latest_date, earliest_date());
@@ -160,12 +199,12 @@ fn test_stock_update_from_csv_incomplete_data() {
 // #[test]
 // fn test_stocks_update() {
 //     let mut stocks = StockList::new();
-//     stocks.push(make_stock("DELL", StockType::Cash, today_plus_days(-2), 100, 52.21, 0.00));
-//     stocks.push(make_stock("AAPL", StockType::Cash, today_plus_days(-3), 200, 120.25, 0.00));
+//     stocks.push(make_stock("DELL", StockType::Cash, today_plus_days(-2), 100.0, 52.21, 0.00));
+//     stocks.push(make_stock("AAPL", StockType::Cash, today_plus_days(-3), 200.0, 120.25, 0.00));
 
 //     let dt = make_date(2022, 02, 17);
 
-//     let cnt = update_stocks(&mut stocks, Some(dt)).unwrap();
+//     let cnt = update_stocks(&mut stocks, &YahooProvider::new(HttpConfig::new()), Some(dt)).unwrap();
 //     assert_eq!(cnt, 2);
 //     assert_eq!(stocks[0].latest_date, dt);
 //     assert_eq!(stocks[1].latest_date, dt);
@@ -173,6 +212,42 @@ fn test_stock_update_from_csv_incomplete_data() {
 //     assert!((stocks[1].latest_price - 167.40).abs() < 0.5);
 // }
 
+#[test]
+fn test_stocks_update_from_provider() {
+    struct TestProvider {}
+
+    impl MarketDataProvider for TestProvider {
+        fn history(&self, symbol: &str, _from_date: &SPDate, to_date: &SPDate) -> Result<String, Error> {
+            let price = if symbol == "DELL" { 56.16 } else { 167.40 };
+            Ok(format!("Date,Open,High,Low,Close,Adj Close,Volume\n\
+                        {},{:.2},{:.2},{:.2},{:.2},{:.2},10000",
+                       date_plus_days(to_date, -1).format("%Y-%m-%d"),
+                       price, price, price, price, price))
+        }
+
+        fn dividends(&self, _symbol: &str, _from_date: &SPDate, _to_date: &SPDate) -> Result<String, Error> {
+            Ok(String::new())
+        }
+
+        fn splits(&self, _symbol: &str, _from_date: &SPDate, _to_date: &SPDate) -> Result<String, Error> {
+            Ok(String::new())
+        }
+    }
+
+    let mut stocks = StockList::new();
+    stocks.push(make_stock("DELL", StockType::Cash, today_plus_days(-2), 100.0, 52.21, 0.00));
+    stocks.push(make_stock("AAPL", StockType::Cash, today_plus_days(-3), 200.0, 120.25, 0.00));
+
+    let dt = make_date(2022, 2, 17);
+
+    let cnt = update_stocks(&mut stocks, &TestProvider{}, Some(dt)).unwrap();
+    assert_eq!(cnt, 2);
+    assert_eq!(stocks[0].latest_date, dt);
+    assert_eq!(stocks[1].latest_date, dt);
+    assert!(price_eql(stocks[0].latest_price, 56.16));
+    assert!(price_eql(stocks[1].latest_price, 167.40));
+}
+
 #[test]
 fn test_sort_stocks() {
     fn test_sort(stocks: &mut StockList, field: &str, desc: bool, first: &str, second: &str, third: &str) {
@@ -183,9 +258,9 @@ fn test_sort_stocks() {
     }
 
     let mut list = StockList::new();
-    list.push(make_stock("DELL", StockType::Cash, today_plus_days(-2), 100, 79.21, 79.71));
-    list.push(make_stock("AAPL", StockType::Cash, today_plus_days(-3), 200, 120.25, 125.25));
-    list.push(make_stock("ICLN", StockType::ETF, today_plus_days(-1), 300, 24.10, 24.12));
+    list.push(make_stock("DELL", StockType::Cash, today_plus_days(-2), 100.0, 79.21, 79.71));
+    list.push(make_stock("AAPL", StockType::Cash, today_plus_days(-3), 200.0, 120.25, 125.25));
+    list.push(make_stock("ICLN", StockType::ETF, today_plus_days(-1), 300.0, 24.10, 24.12));
     list[0].cum_dividend = 0.0;
     list[1].cum_dividend = 20.15;
     list[2].cum_dividend = 15.25;
@@ -240,6 +315,12 @@ fn test_sort_stocks() {
 
     test_sort(&mut list, "divret", desc, "ICLN", "AAPL", "DELL");
     test_sort(&mut list, "divret", asc, "DELL", "AAPL", "ICLN");
+
+    list[0].account = String::from("ira");
+    list[1].account = String::from("taxable");
+    list[2].account = String::from("brokerage");
+    test_sort(&mut list, "account", asc, "ICLN", "DELL", "AAPL");
+    test_sort(&mut list, "account", desc, "AAPL", "DELL", "ICLN");
 }
 
 #[test]
@@ -253,9 +334,9 @@ fn test_sort_stocks_by_extra_ftn() {
     }
 
     let mut list = StockList::new();
-    list.push(make_stock("DELL", StockType::Cash, today_plus_days(-2), 100, 79.21, 79.71));
-    list.push(make_stock("AAPL", StockType::Cash, today_plus_days(-3), 200, 120.25, 125.25));
-    list.push(make_stock("ICLN", StockType::ETF, today_plus_days(0), 300, 24.10, 24.12));
+    list.push(make_stock("DELL", StockType::Cash, today_plus_days(-2), 100.0, 79.21, 79.71));
+    list.push(make_stock("AAPL", StockType::Cash, today_plus_days(-3), 200.0, 120.25, 125.25));
+    list.push(make_stock("ICLN", StockType::ETF, today_plus_days(0), 300.0, 24.10, 24.12));
     list[0].cum_dividend = 0.0;
     list[1].cum_dividend = 20.15;
     list[2].cum_dividend = 15.25;
@@ -266,17 +347,17 @@ fn test_sort_stocks_by_extra_ftn() {
     test_sort(&mut list, |s| s.cum_dividend as f64, asc, "DELL", "ICLN", "AAPL");
     test_sort(&mut list, |s| s.cum_dividend as f64, desc, "AAPL", "ICLN", "DELL");
 
-    test_sort(&mut list, |s| (300 - s.quantity) as f64, asc, "ICLN", "AAPL", "DELL");
-    test_sort(&mut list, |s| (300 - s.quantity) as f64, desc, "DELL", "AAPL", "ICLN");
+    test_sort(&mut list, |s| 300.0 - s.quantity, asc, "ICLN", "AAPL", "DELL");
+    test_sort(&mut list, |s| 300.0 - s.quantity, desc, "DELL", "AAPL", "ICLN");
 }
 
 #[test]
 fn test_filter_stocks() {
     fn test_filter(expr: &str, keep: bool, symbols: &Vec<&str>) {
         let mut list = StockList::new();
-        list.push(make_stock("DELL", StockType::Cash, today_plus_days(-2), 100, 79.21, 79.71));
-        list.push(make_stock("AAPL", StockType::Cash, today_plus_days(-3), 200, 120.25, 125.25));
-        list.push(make_stock("ICLN", StockType::ETF, today_plus_days(0), 300, 24.10, 24.12));
+        list.push(make_stock("DELL", StockType::Cash, today_plus_days(-2), 100.0, 79.21, 79.71));
+        list.push(make_stock("AAPL", StockType::Cash, today_plus_days(-3), 200.0, 120.25, 125.25));
+        list.push(make_stock("ICLN", StockType::ETF, today_plus_days(0), 300.0, 24.10, 24.12));
 
         filter_stocks(&mut list, expr, keep).unwrap();
 
@@ -301,13 +382,36 @@ fn test_filter_stocks() {
     test_filter("MSFT", remove, &vec!["DELL", "AAPL", "ICLN"]);
 }
 
+#[test]
+fn test_filter_stocks_by_account() {
+    fn test_filter(expr: &str, keep: bool, symbols: &Vec<&str>) {
+        let mut list: StockList = vec![make_stock("DELL", StockType::Cash, today_plus_days(-2), 100.0, 79.21, 79.71),
+                                       make_stock("AAPL", StockType::Cash, today_plus_days(-3), 200.0, 120.25, 125.25),
+                                       make_stock("ICLN", StockType::ETF, today_plus_days(0), 300.0, 24.10, 24.12)];
+        list[0].account = String::from("ira");
+        list[1].account = String::from("taxable");
+
+        filter_stocks(&mut list, expr, keep).unwrap();
+
+        assert_eq!(list.iter().map(|s| s.symbol.as_str()).collect::<Vec<&str>>(), *symbols);
+    }
+
+    let keep = true;
+    let remove = false;
+
+    test_filter("account:ira", keep, &vec!["DELL"]);
+    test_filter("account:ira", remove, &vec!["AAPL", "ICLN"]);
+    test_filter("account:ira, taxable", keep, &vec!["DELL", "AAPL"]);
+    test_filter("account:401k", keep, &vec![]);
+}
+
 #[test]
 fn test_filter_stocks_by_expr() {
     fn test_filter_by(by_expr: &str, keep: bool, sz: usize, sym1: &str, sym2: &str, sym3: &str) {
         let mut list = StockList::new();
-        list.push(make_stock("DELL", StockType::Cash, today_plus_days(-2), 100, 79.21, 79.71));
-        list.push(make_stock("AAPL", StockType::Cash, today_plus_days(-3), 200, 120.25, 125.25));
-        list.push(make_stock("ICLN", StockType::ETF, today_plus_days(0), 300, 24.10, 24.12));
+        list.push(make_stock("DELL", StockType::Cash, today_plus_days(-2), 100.0, 79.21, 79.71));
+        list.push(make_stock("AAPL", StockType::Cash, today_plus_days(-3), 200.0, 120.25, 125.25));
+        list.push(make_stock("ICLN", StockType::ETF, today_plus_days(0), 300.0, 24.10, 24.12));
 
         filter_stocks(&mut list, by_expr, keep).unwrap();
         assert_eq!(list.len(), sz);
@@ -343,6 +447,65 @@ fn test_filter_stocks_by_expr() {
     test_filter_by("div = 0.00",       false, 0, "", "", "");
     test_filter_by("size >= 200",      false, 1, "DELL", "", "");
     test_filter_by("value <= 7500.00", false, 2, "DELL", "AAPL", "");
+
+    test_filter_by("symbol = AAPL",                     true, 1, "AAPL", "", "");
+    test_filter_by("symbol >= 'DELL'",                  true, 2, "DELL", "ICLN", "");
+    test_filter_by("type != etf",                       true, 2, "DELL", "AAPL", "");
+    test_filter_by("days > 2 or type = etf",            true, 2, "AAPL", "ICLN", "");
+    test_filter_by("days > 2 or type = etf and pct > 1", true, 1, "AAPL", "", "");
+    test_filter_by("(days > 2 or type = etf) and size >= 300", true, 1, "ICLN", "", "");
+    test_filter_by("not symbol = DELL and not (days=3)", true, 1, "ICLN", "", "");
+    test_filter_by("not not type==cash",                true, 2, "DELL", "AAPL", "");
+    test_filter_by("yrdiv = 0 and ladiv = 0 and dudiv = 0 and divret = 0", true, 3, "DELL", "AAPL", "ICLN");
+    test_filter_by("account = ''",                      true, 3, "DELL", "AAPL", "ICLN");
+    test_filter_by("laddt < 2000-01-01",                true, 3, "DELL", "AAPL", "ICLN");
+    test_filter_by("days > 2 or type = etf",            false, 1, "DELL", "", "");
+
+    let date_expr = format!("date >= {}", today_plus_days(-2).format("%Y-%m-%d"));
+    test_filter_by(&date_expr, true, 2, "DELL", "ICLN", "");
+    test_filter_by(&date_expr, false, 1, "AAPL", "", "");
+}
+
+#[test]
+fn test_filter_stocks_symbols_with_equals() {
+    fn test_filter(expr: &str, symbols: &[&str]) {
+        let mut list = StockList::new();
+        list.push(make_stock("EURUSD=X", StockType::Index, today_plus_days(-2), 100.0, 1.18, 1.19));
+        list.push(make_stock("DELL", StockType::Cash, today_plus_days(-3), 100.0, 79.21, 79.71));
+
+        filter_stocks(&mut list, expr, true).unwrap();
+        assert_eq!(list.iter().map(|s| s.symbol.as_str()).collect::<Vec<&str>>(), symbols);
+    }
+
+    test_filter("EURUSD=X", &["EURUSD=X"]);
+    test_filter("DELL,EURUSD=X", &["EURUSD=X", "DELL"]);
+    test_filter("symbol = EURUSD=X", &["EURUSD=X"]);
+    test_filter("symbol=EURUSD=X or symbol=DELL", &["EURUSD=X", "DELL"]);
+    test_filter("days=3", &["DELL"]);
+}
+
+#[test]
+fn test_filter_stocks_errors() {
+    fn check(expr: &str, err: &str) {
+        let mut list = StockList::new();
+        assert_eq!(format!("{}", filter_stocks(&mut list, expr, true).err().unwrap()), err);
+    }
+
+    check("days > 2 &", "Unexpected character '&' at position 10 in filter expression 'days > 2 &'");
+    check("symbol = 'AAPL", "Unterminated string at position 10 in filter expression 'symbol = 'AAPL'");
+    check("dayz > 2", "Unsupported field 'dayz' at position 1 in filter expression 'dayz > 2'");
+    check("days 2", "Expected operator, found '2' at position 6 in filter expression 'days 2'");
+    check("days >", "Expected value at end of filter expression 'days >'");
+    check("days > (", "Expected value, found '(' at position 8 in filter expression 'days > ('");
+    check("days > 2 and", "Expected field at end of filter expression 'days > 2 and'");
+    check("days > 2 and or pct < 1", "Expected field, found 'or' at position 14 in filter expression 'days > 2 and or pct < 1'");
+    check("(days > 2 or pct < 1", "Unclosed '(' at position 1 in filter expression '(days > 2 or pct < 1'");
+    check("(days > 2 pct < 1)", "Expected ')', found 'pct' at position 11 in filter expression '(days > 2 pct < 1)'");
+    check("days > 2)", "Unexpected ')' at position 9 in filter expression 'days > 2)'");
+    check("price > 1.2.3", "Invalid number '1.2.3' at position 9 in filter expression 'price > 1.2.3'");
+    check("type = bond", "Invalid stock type 'bond' at position 8 in filter expression 'type = bond'");
+    check("date < 2021-13-01", "Invalid date '2021-13-01' at position 8 in filter expression 'date < 2021-13-01'");
+    check("AAPL DELL", "Unsupported field 'AAPL' at position 1 in filter expression 'AAPL DELL'");
 }
 
 #[test]
@@ -356,7 +519,7 @@ fn test_match_list_to_symbols() {
                     StockType::Cash,
                     today_plus_days(-10),
                     today(),
-                    100,
+                    FixedPrice::from_unsigned(100),
                     FixedPrice::from_string("10.00"),
                     FixedPrice::from_string("15.00"),
                     FixedPrice::from_string("0.05"),
@@ -395,17 +558,17 @@ fn test_stock_base_dates() {
     }
 
     let mut list = StockList::new();
-    list.push(make_stock("DELL", StockType::Cash, today_plus_days(-2), 100, 79.21,  79.71));
-    list.push(make_stock("AAPL", StockType::Cash, today_plus_days(-3), 200, 120.25, 125.25));
-    list.push(make_stock("ICLN", StockType::ETF,   today_plus_days(0),  300, 24.10,  24.12));
-    list.push(make_stock("AAPL", StockType::Cash, today_plus_days(0),  100, 125.50, 125.75));
+    list.push(make_stock("DELL", StockType::Cash, today_plus_days(-2), 100.0, 79.21,  79.71));
+    list.push(make_stock("AAPL", StockType::Cash, today_plus_days(-3), 200.0, 120.25, 125.25));
+    list.push(make_stock("ICLN", StockType::ETF,   today_plus_days(0),  300.0, 24.10,  24.12));
+    list.push(make_stock("AAPL", StockType::Cash, today_plus_days(0),  100.0, 125.50, 125.75));
     test_dates(&list);
 
     let mut list = StockList::new();
-    list.push(make_stock("DELL", StockType::Cash, today_plus_days(-2), 100, 79.21,  79.71));
-    list.push(make_stock("AAPL", StockType::Cash, today_plus_days(0),  100, 125.50, 125.75));
-    list.push(make_stock("ICLN", StockType::ETF,   today_plus_days(0),  300, 24.10,  24.12));
-    list.push(make_stock("AAPL", StockType::Cash, today_plus_days(-3), 200, 120.25, 125.25));
+    list.push(make_stock("DELL", StockType::Cash, today_plus_days(-2), 100.0, 79.21,  79.71));
+    list.push(make_stock("AAPL", StockType::Cash, today_plus_days(0),  100.0, 125.50, 125.75));
+    list.push(make_stock("ICLN", StockType::ETF,   today_plus_days(0),  300.0, 24.10,  24.12));
+    list.push(make_stock("AAPL", StockType::Cash, today_plus_days(-3), 200.0, 120.25, 125.25));
     test_dates(&list);
 }
 
@@ -413,9 +576,9 @@ fn test_stock_base_dates() {
 fn test_value_export() {
     let mut cfg = StocksConfig::new();
     let stocks = cfg.stocks_mut();
-    stocks.push(make_stock("DELL", StockType::Cash, today_plus_days(-2), 100, 75.50, 80.0));
-    stocks.push(make_stock("AAPL", StockType::Cash, today_plus_days(-3), 100, 120.25, 125.25));
-    stocks.push(make_stock("ICLN", StockType::ETF, today_plus_days(0), 100, 24.10, 24.15));
+    stocks.push(make_stock("DELL", StockType::Cash, today_plus_days(-2), 100.0, 75.50, 80.0));
+    stocks.push(make_stock("AAPL", StockType::Cash, today_plus_days(-3), 100.0, 120.25, 125.25));
+    stocks.push(make_stock("ICLN", StockType::ETF, today_plus_days(0), 100.0, 24.10, 24.15));
 
     assert_eq!(cfg.ds_root(), "");
     assert_eq!(cfg.ds_name(), "");
@@ -443,6 +606,143 @@ fn test_value_export() {
     assert!(temp_file::remove_file(&temp_name));
 }
 
+#[test]
+fn test_tax_export() {
+    let mut cfg = StocksConfig::new();
+    let positions = cfg.closed_positions_mut();
+    positions.push(make_position("AAPL", StockType::Cash, make_date(2020, 9, 20), make_date(2021, 9, 21),
+                                 FixedPrice::from_string("10"), FixedPrice::from_string("115.00"), FixedPrice::from_string("150.00"),
+                                 FixedPrice::from_string("1.00"), FixedPrice::from_string("3.00")));
+    positions.push(make_position("DELL", StockType::Cash, make_date(2021, 2, 10), make_date(2021, 3, 1),
+                                 FixedPrice::from_string("5.5"), FixedPrice::from_string("80.00"), FixedPrice::from_string("75.00"),
+                                 FixedPrice::from_string("0.50"), FixedPrice::from_string("0.00")));
+    positions.push(make_position("ICLN", StockType::ETF, make_date(2019, 6, 3), make_date(2020, 12, 1),
+                                 FixedPrice::from_string("100"), FixedPrice::from_string("10.00"), FixedPrice::from_string("25.00"),
+                                 FP_0, FP_0));
+    positions.push(make_position("SPY", StockType::Index, make_date(2020, 1, 2), make_date(2021, 3, 1),
+                                 FixedPrice::from_string("1"), FixedPrice::from_string("300.00"), FixedPrice::from_string("380.00"),
+                                 FP_0, FP_0));
+
+    let temp_name = "sp_test_tax_export.csv";
+    let csv_filename = temp_file::make_path(temp_name);
+    let rparams = ReportParams::new(ReportType::Tax, &cfg)
+        .with_date_range(Some(make_date(2021, 1, 1)), Some(make_date(2021, 12, 31)));
+    reports::export_report(rparams, csv_filename.to_str().unwrap()).unwrap();
+
+    let csv_content = fs::read_to_string(&csv_filename).unwrap();
+    assert_eq!(csv_content,
+               "Description,Date Acquired,Date Sold,Proceeds,Cost Basis,Code,Adjustment,Gain or Loss,Term,Tax Year\n\
+                5.5 DELL,2021-02-10,2021-03-01,412.00,440.00,,0.00,-28.00,short,2021\n\
+                10 AAPL,2020-09-20,2021-09-21,1499.00,1150.00,,0.00,349.00,long,2021\n");
+
+    assert!(temp_file::remove_file(temp_name));
+}
+
+#[test]
+fn test_wash_export() {
+    let mut cfg = StocksConfig::new();
+    cfg.stocks_mut().push(make_stock("AAPL", StockType::Cash, make_date(2021, 3, 15), 4.0, 110.0, 115.0));
+    let positions = cfg.closed_positions_mut();
+    positions.push(make_position("AAPL", StockType::Cash, make_date(2021, 1, 4), make_date(2021, 3, 1),
+                                 FixedPrice::from_string("10"), FixedPrice::from_string("120.00"), FixedPrice::from_string("100.00"),
+                                 FP_0, FP_0));
+
+    let temp_name = "sp_test_wash_export.csv";
+    let csv_filename = temp_file::make_path(temp_name);
+    reports::export_report(ReportParams::new(ReportType::Wash, &cfg), csv_filename.to_str().unwrap()).unwrap();
+    assert_eq!(fs::read_to_string(&csv_filename).unwrap(),
+               "Symbol,Acquired,Sold,Repl Date,Repl,Size,Disallowed,Basis Adj\n\
+                AAPL,2021-01-04,2021-03-01,2021-03-15,open,4,80.00,20.00\n");
+
+    reports::export_report(ReportParams::new(ReportType::Tax, &cfg), csv_filename.to_str().unwrap()).unwrap();
+    assert_eq!(fs::read_to_string(&csv_filename).unwrap(),
+               "Description,Date Acquired,Date Sold,Proceeds,Cost Basis,Code,Adjustment,Gain or Loss,Term,Tax Year\n\
+                10 AAPL,2021-01-04,2021-03-01,1000.00,1200.00,W,80.00,-120.00,short,2021\n");
+
+    // Wash sales are found among all lots when the shown stocks are filtered
+    let all_stocks = cfg.stocks().clone();
+    let all_positions = cfg.closed_positions().clone();
+    filter_stocks(cfg.stocks_mut(), "AAPL", false).unwrap();
+    assert!(cfg.stocks().is_empty());
+
+    let rparams = ReportParams::new(ReportType::Tax, &cfg).with_all_lots(&all_stocks, &all_positions);
+    reports::export_report(rparams, csv_filename.to_str().unwrap()).unwrap();
+    assert_eq!(fs::read_to_string(&csv_filename).unwrap(),
+               "Description,Date Acquired,Date Sold,Proceeds,Cost Basis,Code,Adjustment,Gain or Loss,Term,Tax Year\n\
+                10 AAPL,2021-01-04,2021-03-01,1000.00,1200.00,W,80.00,-120.00,short,2021\n");
+
+    let rparams = ReportParams::new(ReportType::Wash, &cfg).with_all_lots(&all_stocks, &all_positions);
+    reports::export_report(rparams, csv_filename.to_str().unwrap()).unwrap();
+    assert_eq!(fs::read_to_string(&csv_filename).unwrap(), "Symbol,Acquired,Sold,Repl Date,Repl,Size,Disallowed,Basis Adj\n");
+
+    assert!(temp_file::remove_file(temp_name));
+}
+
+#[test]
+fn test_harvest_export() {
+    let mut cfg = StocksConfig::new();
+    let stocks = cfg.stocks_mut();
+    stocks.push(make_stock("AAPL", StockType::Cash, today_plus_days(-400), 10.0, 120.0, 100.0));
+    stocks.push(make_stock("AAPL", StockType::Cash, today_plus_days(-10), 2.0, 98.0, 100.0));
+    stocks.push(make_stock("DELL", StockType::Cash, today_plus_days(-20), 100.0, 80.0, 79.5));
+    stocks.push(make_stock("ICLN", StockType::ETF, today_plus_days(-20), 100.0, 24.0, 20.0));
+
+    let temp_name = "sp_test_harvest_export.csv";
+    let csv_filename = temp_file::make_path(temp_name);
+    let rparams = ReportParams::new(ReportType::Harvest, &cfg).with_loss_thresholds(100.0, 1.0);
+    reports::export_report(rparams, csv_filename.to_str().unwrap()).unwrap();
+
+    let today_str = today_plus_days(0).format("%Y-%m-%d");
+    let expected = format!("Symbol,Buy Date,Upd Date,Size,Base Value,Cur Value,Loss,Loss Pct,Term,Wash Buy\n\
+                            AAPL,{},{},10,1200.00,1000.00,200.00,16.67,long,{}\n\
+                            ICLN,{},{},100,2400.00,2000.00,400.00,16.67,short,\n",
+                           today_plus_days(-400).format("%Y-%m-%d"),
+                           today_str,
+                           today_plus_days(-10).format("%Y-%m-%d"),
+                           today_plus_days(-20).format("%Y-%m-%d"),
+                           today_str);
+    assert_eq!(fs::read_to_string(&csv_filename).unwrap(), expected);
+
+    assert!(temp_file::remove_file(temp_name));
+}
+
+#[test]
+fn test_rebal_export() {
+    let content: &str = "cash: 1000.00\n\
+                         stocks: csv{\n\
+                         symbol,type,date,quantity,base_price\n\
+                         AAPL,cash,2021-02-10,10,90.00\n\
+                         ICLN,etf,2021-02-10,100,24.00\n\
+                         }\n\
+                         targets: csv{\n\
+                         target,weight,tolerance\n\
+                         AAPL,50,2\n\
+                         type:etf,40,\n\
+                         }\n";
+
+    let mut cfg = StocksConfig::from_str(content).unwrap();
+    let date = today_plus_days(0);
+    cfg.stocks_mut()[0].set_latest_price(100.0, date);
+    cfg.stocks_mut()[1].set_latest_price(20.0, date);
+
+    let temp_name = "sp_test_rebal_export.csv";
+    let csv_filename = temp_file::make_path(temp_name);
+    let rparams = ReportParams::new(ReportType::Rebal, &cfg);
+    reports::export_report(rparams, csv_filename.to_str().unwrap()).unwrap();
+    assert_eq!(fs::read_to_string(&csv_filename).unwrap(),
+               "Symbol,Price,Shares,Trade Value\n\
+                AAPL,100.00,10,1000.00\n\
+                ICLN,20.00,-20,-400.00\n");
+
+    let rparams = ReportParams::new(ReportType::Rebal, &cfg).with_cash_only(true);
+    reports::export_report(rparams, csv_filename.to_str().unwrap()).unwrap();
+    assert_eq!(fs::read_to_string(&csv_filename).unwrap(),
+               "Symbol,Price,Shares,Trade Value\n\
+                AAPL,100.00,10,1000.00\n");
+
+    assert!(temp_file::remove_file(temp_name));
+}
+
 #[test]
 fn test_stock_reader() {
     let temp_name = "sp_test_stocks_file.csv";
@@ -462,14 +762,340 @@ fn test_stock_reader() {
                vec![StockType::Cash, StockType::Cash, StockType::Cash]);
     assert_eq!(list.iter().map(|s| s.date).collect::<Vec<SPDate>>(),
                vec![make_date(2020, 9, 20), make_date(2020, 11, 12), make_date(2021, 02, 10)]);
-    assert_eq!(list.iter().map(|s| s.quantity).collect::<Vec<u32>>(),
-               vec![100, 100, 100]);
+    assert_eq!(list.iter().map(|s| s.quantity).collect::<Vec<f64>>(),
+               vec![100.0, 100.0, 100.0]);
     assert_eq!(list.iter().map(|s| s.base_price).collect::<Vec<f64>>(),
                vec![115.0, 118.50, 75.50]);
 
     assert!(temp_file::remove_file(&temp_name));
 }
 
+#[test]
+fn test_stock_reader_fractional() {
+    let list = StocksReader::parse_content("symbol,type,date,quantity,base_price\n\
+                                            AAPL,cash,2020-09-20,10.5,115.00\n\
+                                            DELL,cash,2021-02-10,0.125,75.50\n").unwrap();
+    assert_eq!(list.iter().map(|s| s.quantity).collect::<Vec<f64>>(), vec![10.5, 0.125]);
+    assert!(price_eql(list[0].base_notional(), 1207.5));
+    assert!(price_eql(list[1].base_notional(), 9.4375));
+
+    let gby = stock_aggregate(&list);
+    assert!(price_eql(gby.get("AAPL").unwrap().0, 10.5));
+
+    for (qty, err) in [("-1", "StocksReader::parse_content - Invalid quantity '-1'"),
+                       ("ten", "StocksReader::parse_content - Invalid quantity 'invalid float literal'")] {
+        let content = format!("symbol,type,date,quantity,base_price\nAAPL,cash,2020-09-20,{},115.00\n", qty);
+        assert_eq!(format!("{}", StocksReader::parse_content(&content).err().unwrap()), err);
+    }
+}
+
+#[test]
+fn test_stock_reader_drip() {
+    let list = StocksReader::parse_content("symbol,type,date,quantity,base_price,drip\n\
+                                            AAPL,cash,2020-09-20,100,115.00,true\n\
+                                            DELL,cash,2021-02-10,100,75.50,\n\
+                                            MSFT,cash,2021-02-10,100,235.00,False\n").unwrap();
+    assert_eq!(list.iter().map(|s| s.drip).collect::<Vec<bool>>(), vec![true, false, false]);
+
+    let content = "symbol,type,date,quantity,base_price,drip\nAAPL,cash,2020-09-20,100,115.00,maybe\n";
+    assert_eq!(format!("{}", StocksReader::parse_content(content).err().unwrap()),
+               "StocksReader::parse_content - Invalid drip 'maybe'");
+}
+
+#[test]
+fn test_stock_reader_currency() {
+    let list = StocksReader::parse_content("symbol,type,date,quantity,base_price,drip,currency\n\
+                                            AAPL,cash,2020-09-20,100,115.00\n\
+                                            SAP,cash,2021-02-10,100,105.50,,eur\n\
+                                            TM,cash,2021-02-10,100,150.00,true,\n").unwrap();
+    assert_eq!(list.iter().map(|s| s.currency.as_str()).collect::<Vec<&str>>(), vec!["", "EUR", ""]);
+    assert!(list[2].drip);
+
+    let content = "symbol,type,date,quantity,base_price,drip,currency\nSAP,cash,2021-02-10,100,105.50,,euro\n";
+    assert_eq!(format!("{}", StocksReader::parse_content(content).err().unwrap()), "Invalid currency 'euro'");
+}
+
+#[test]
+fn test_stock_reader_account() {
+    let list = StocksReader::parse_content("symbol,type,date,quantity,base_price,drip,currency,account\n\
+                                            AAPL,cash,2020-09-20,100,115.00\n\
+                                            SAP,cash,2021-02-10,100,105.50,,eur,ira\n\
+                                            TM,cash,2021-02-10,100,150.00,,, taxable \n").unwrap();
+    assert_eq!(list.iter().map(|s| s.account.as_str()).collect::<Vec<&str>>(), vec!["", "ira", "taxable"]);
+    assert_eq!(list[1].currency, "EUR");
+
+    let content = "symbol,type,date,quantity,base_price,drip,currency,account\nSAP,cash,2021-02-10,100,105.50,,,ira,x\n";
+    assert!(StocksReader::parse_content(content).is_err());
+}
+
+#[test]
+fn test_currency_convert_stock() {
+    let fx = FxRates::parse_csv("EURUSD", "2021-01-04,1.20\n2021-02-17,1.21\n2021-05-19,1.22\n2021-06-30,1.19").unwrap();
+
+    let mut div = Dividends::new("SAP");
+    div.add_entry(DividendEntry::new(make_date(2020, 11, 18), 0.50));
+    div.add_entry(DividendEntry::new(make_date(2021, 2, 17), 1.00));
+    div.add_entry(DividendEntry::new(make_date(2021, 5, 19), 2.00));
+
+    let mut stock = Stock::new(String::from("SAP"), StockType::Cash, make_date(2021, 1, 5), 10.0, 100.0);
+    stock.currency = String::from("EUR");
+    stock.set_latest_price(110.0, make_date(2021, 7, 1));
+    stock.set_latest_dividend(2.0, make_date(2021, 5, 19));
+
+    currency::convert_stock(&mut stock, "USD", &fx, &div, &Splits::new("SAP")).unwrap();
+    assert!(price_eql(stock.base_price, 120.0));
+    assert!(price_eql(stock.latest_price, 130.9));
+    assert!(price_eql(stock.latest_div_price, 2.44));
+    assert!(price_eql(stock.cum_dividend, 36.5));
+    assert_eq!(stock.currency, "USD");
+
+    // Dividends of DRIP lots are not cash dividends
+    let mut stock = Stock::new(String::from("SAP"), StockType::Cash, make_date(2021, 1, 5), 10.0, 100.0);
+    stock.currency = String::from("EUR");
+    stock.drip = true;
+    stock.set_latest_price(110.0, make_date(2021, 7, 1));
+    currency::convert_stock(&mut stock, "USD", &fx, &div, &Splits::new("SAP")).unwrap();
+    assert_eq!(stock.cum_dividend, 0.0);
+
+    let mut stock = Stock::new(String::from("SAP"), StockType::Cash, make_date(2020, 12, 31), 10.0, 100.0);
+    stock.currency = String::from("EUR");
+    assert_eq!(format!("{}", currency::convert_stock(&mut stock, "USD", &fx, &div, &Splits::new("SAP")).err().unwrap()),
+               "No EURUSD fx rate on or before 2020-12-31");
+}
+
+#[test]
+fn test_currency_convert_position() {
+    let fx = FxRates::parse_csv("EURUSD", "2021-01-04,1.20\n2021-06-30,1.25").unwrap();
+    let mut pos = make_position("SAP", StockType::Cash, make_date(2021, 1, 5), make_date(2021, 7, 1),
+                                FixedPrice::from_unsigned(10), FixedPrice::from_string("100.00"), FixedPrice::from_string("110.00"),
+                                FixedPrice::from_string("2.00"), FixedPrice::from_string("8.00"));
+    pos.currency = String::from("EUR");
+
+    currency::convert_position(&mut pos, "USD", &fx).unwrap();
+    assert_eq!(pos.base_price, FixedPrice::from_string("120.00"));
+    assert_eq!(pos.exit_price, FixedPrice::from_string("137.50"));
+    assert_eq!(pos.exit_fee, FixedPrice::from_string("2.50"));
+    assert_eq!(pos.dividend, FixedPrice::from_string("10.00"));
+    assert_eq!(pos.net_notional(), FixedPrice::from_string("175.00"));
+    assert_eq!(pos.currency, "USD");
+}
+
+#[test]
+fn test_currency_value_series() {
+    let ds = make_datastore("sp_test_fx_value_ds", &[
+        (history::tag(), "SAP", "Date,Open,High,Low,Close,Adj Close,Volume\n\
+                                 2020-12-31,95.0,96.0,94.0,95.0,95.0,1000\n\
+                                 2021-01-04,99.0,101.0,98.0,100.0,100.0,1000\n\
+                                 2021-01-05,105.0,111.0,104.0,110.0,110.0,1000\n\
+                                 2021-01-06,115.0,121.0,114.0,120.0,120.0,1000\n"),
+        (fx_rates::tag(), "EURUSD", "Date,Rate\n\
+                                     2021-01-04,1.2\n\
+                                     2021-01-05,1.25\n")]);
+
+    let mut stocks: StockList = vec![Stock::new(String::from("SAP"), StockType::Cash, make_date(2021, 1, 4), 10.0, 100.0)];
+    stocks[0].currency = String::from("EUR");
+    let positions: ClosedPositionList = Vec::new();
+    assert_eq!(update_stocks_from_ds(&mut stocks, &ds).unwrap(), 1);
+
+    let currencies = currency::symbol_currencies(&stocks, &positions, "USD");
+    assert_eq!(currencies.get("SAP").map(String::as_str), Some("EUR"));
+    assert_eq!(currency::convert_stocks_from_ds(&mut stocks, &ds, "USD").unwrap(), 1);
+
+    // Closes are converted on their dates, the close before the first rate is dropped
+    let to = make_date(2021, 1, 6);
+    let mut mkt = MarketHistory::from_ds(&ds, &stocks, &positions, &to).unwrap();
+    assert_eq!(currency::convert_market_from_ds(&mut mkt, &currencies, &ds, "USD").unwrap(), 1);
+    assert_eq!(mkt.close("SAP", &make_date(2020, 12, 31)), None);
+
+    let series = performance::value_series(&stocks, &positions, 0.0, &Vec::new(), &mkt, &to);
+    assert_eq!(series.iter().map(|pt| pt.date).collect::<Vec<SPDate>>(),
+               vec![make_date(2021, 1, 4), make_date(2021, 1, 5), make_date(2021, 1, 6)]);
+    assert!(zip(series.iter().map(|pt| pt.value), [1200.0, 1375.0, 1500.0]).all(|(value, expected)| price_eql(value, expected)));
+    assert!(series.iter().all(|pt| price_eql(pt.cost, 1200.0)));
+
+    assert!(ds.delete().is_ok());
+}
+
+#[test]
+fn test_currency_base_dates() {
+    let mut stocks = StockList::new();
+    for (sym, date, ccy) in [("AAPL", make_date(2020, 1, 2), ""),
+                             ("SAP", make_date(2021, 2, 1), "EUR"),
+                             ("SIE", make_date(2021, 1, 4), "EUR"),
+                             ("SPY", make_date(2019, 1, 2), "USD")] {
+        let mut stock = Stock::new(String::from(sym), StockType::Cash, date, 1.0, 1.0);
+        stock.currency = String::from(ccy);
+        stocks.push(stock);
+    }
+    let mut pos = make_position("TM", StockType::Cash, make_date(2020, 6, 1), make_date(2021, 1, 4),
+                                FixedPrice::from_unsigned(1), FP_0, FP_0, FP_0, FP_0);
+    pos.currency = String::from("JPY");
+
+    let dates = currency::currency_base_dates(&stocks, &vec![pos], "USD");
+    assert_eq!(dates.len(), 2);
+    assert_eq!(dates["EUR"], make_date(2021, 1, 4));
+    assert_eq!(dates["JPY"], make_date(2020, 6, 1));
+
+    assert_eq!(currency::parse_currency(" gbp ").unwrap(), "GBP");
+    assert!(currency::parse_currency("GB").is_err());
+    assert!(currency::is_foreign("EUR", "USD"));
+    assert!(!currency::is_foreign("", "USD"));
+    assert!(!currency::is_foreign("USD", "USD"));
+}
+
+#[test]
+fn test_split_adjust() {
+    let mut splt = Splits::new("AAPL");
+    splt.add_entry(SplitEntry::new(make_date(2014, 6, 9), String::from("7:1")));
+    splt.add_entry(SplitEntry::new(make_date(2020, 8, 31), String::from("4:1")));
+
+    let mut stock = Stock::new(String::from("AAPL"), StockType::Cash, make_date(2020, 1, 2), 10.0, 300.0);
+    assert!(split_adjust(&mut stock, &splt).unwrap());
+    assert!(price_eql(stock.quantity, 40.0));
+    assert!(price_eql(stock.base_price, 75.0));
+
+    stock.set_latest_price(150.0, make_date(2021, 1, 4));
+    assert!(price_eql(stock.pct_change(), 100.0));
+
+    let mut stock = Stock::new(String::from("AAPL"), StockType::Cash, make_date(2010, 1, 4), 1.0, 210.0);
+    assert!(split_adjust(&mut stock, &splt).unwrap());
+    assert!(price_eql(stock.quantity, 28.0));
+    assert!(price_eql(stock.base_price, 7.5));
+
+    let mut stock = Stock::new(String::from("AAPL"), StockType::Cash, make_date(2020, 8, 31), 10.0, 130.0);
+    assert!(!split_adjust(&mut stock, &splt).unwrap());
+    assert!(price_eql(stock.quantity, 10.0));
+    assert!(price_eql(stock.base_price, 130.0));
+
+    splt.add_entry(SplitEntry::new(make_date(2021, 1, 4), String::from("bad")));
+    assert_eq!(format!("{}", split_adjust(&mut stock, &splt).err().unwrap()), "SplitEntry: Invalid split 'bad'");
+}
+
+#[test]
+fn test_reinvest_dividends() {
+    let mut stock = Stock::new(String::from("MSFT"), StockType::Cash, make_date(2021, 1, 4), 100.0, 200.0);
+    stock.drip = true;
+
+    let mut div = Dividends::new("MSFT");
+    div.add_entry(DividendEntry::new(make_date(2020, 11, 18), 0.56));
+    div.add_entry(DividendEntry::new(make_date(2021, 2, 17), 0.56));
+    div.add_entry(DividendEntry::new(make_date(2021, 5, 19), 0.56));
+
+    let mut hist = History::new("MSFT");
+    hist.add_entry(HistoryEntry::new(make_date(2021, 2, 17), 240.0, 245.0, 238.0, 244.0, 243.0, 1000));
+    hist.add_entry(HistoryEntry::new(make_date(2021, 5, 18), 246.0, 249.0, 244.0, 245.0, 244.5, 1000));
+
+    let lots = reinvest_dividends(&stock, &div, &Splits::new("MSFT"), &hist).unwrap();
+    assert_eq!(lots.len(), 2);
+
+    // 100 * 0.56 / 244.00 shares on 2021-02-17
+    assert_eq!(lots[0].symbol, "MSFT");
+    assert_eq!(lots[0].date, make_date(2021, 2, 17));
+    assert!(price_eql(lots[0].base_price, 244.0));
+    assert!(price_eql(lots[0].quantity, 0.229508));
+    assert!(lots[0].drip);
+
+    // Compounded (100 + 0.229508) * 0.56 / 245.00 shares on 2021-05-19 at the prior close
+    assert_eq!(lots[1].date, make_date(2021, 5, 19));
+    assert!(price_eql(lots[1].base_price, 245.0));
+    assert!(price_eql(lots[1].quantity, 0.229096));
+
+    let stock = Stock::new(String::from("MSFT"), StockType::Cash, make_date(2021, 1, 4), 100.0, 200.0);
+    let hist = History::new("MSFT");
+    assert_eq!(format!("{}", reinvest_dividends(&stock, &div, &Splits::new("MSFT"), &hist).err().unwrap()),
+               "Failed to find close for MSFT on 2021-02-17");
+}
+
+#[test]
+fn test_reinvest_dividends_from_ds() {
+    let ds = make_datastore("sp_test_drip_ds", &[
+        (history::tag(), "MSFT", "Date,Open,High,Low,Close,Adj Close,Volume\n\
+                                  2021-02-17,240.0,245.0,238.0,244.0,243.0,1000\n\
+                                  2021-05-18,246.0,249.0,244.0,245.0,244.5,1000\n\
+                                  2021-06-30,249.0,251.0,248.0,250.0,250.0,1000\n"),
+        (dividends::tag(), "MSFT", "Date,Dividends\n\
+                                    2021-02-17,0.56\n\
+                                    2021-05-19,0.56\n")]);
+
+    let mut stocks: StockList = vec![Stock::new(String::from("MSFT"), StockType::Cash, make_date(2021, 1, 4), 100.0, 200.0)];
+    stocks[0].drip = true;
+    assert_eq!(reinvest_dividends_from_ds(&mut stocks, &ds).unwrap(), 2);
+    assert_eq!(update_stocks_from_ds(&mut stocks, &ds).unwrap(), 3);
+
+    // Dividends are reinvested in shares only, none is counted as cash dividend
+    let qty1 = 100.0 * 0.56 / 244.0;
+    let qty2 = (100.0 + qty1) * 0.56 / 245.0;
+    let div_plus_value = stocks.iter().fold(0.0, |total, s| total + s.cum_dividend + s.latest_notional());
+    assert!(stocks.iter().all(|s| s.cum_dividend == 0.0));
+    assert!(price_eql(div_plus_value, (100.0 + qty1 + qty2) * 250.0));
+
+    assert!(ds.delete().is_ok());
+}
+
+#[test]
+fn test_reinvest_dividends_splits() {
+    let ds = make_datastore("sp_test_drip_splits_ds", &[
+        (history::tag(), "MSFT", "Date,Open,High,Low,Close,Adj Close,Volume\n\
+                                  2021-02-17,240.0,245.0,238.0,244.0,243.0,1000\n\
+                                  2021-05-18,123.0,124.5,122.0,122.5,122.25,1000\n\
+                                  2021-06-30,124.5,125.5,124.0,125.0,125.0,1000\n"),
+        (dividends::tag(), "MSFT", "Date,Dividends\n\
+                                    2021-02-17,0.56\n\
+                                    2021-05-19,0.28\n"),
+        (splits::tag(), "MSFT", "Date,Stock Splits\n\
+                                 2021-03-01,2:1\n")]);
+
+    let mut stocks: StockList = vec![Stock::new(String::from("MSFT"), StockType::Cash, make_date(2021, 1, 4), 100.0, 200.0)];
+    stocks[0].drip = true;
+    assert_eq!(reinvest_dividends_from_ds(&mut stocks, &ds).unwrap(), 2);
+    assert_eq!(split_adjust_from_ds(&mut stocks, &ds).unwrap(), 2);
+    assert_eq!(update_stocks_from_ds(&mut stocks, &ds).unwrap(), 3);
+
+    // The lot reinvested before the split is bought in pre-split shares and split adjusted with the stock lot
+    let qty1 = 100.0 * 0.56 / 244.0;
+    let qty2 = (100.0 + qty1) * 2.0 * 0.28 / 122.5;
+    assert!(price_eql(stocks[0].quantity, 200.0));
+    assert!(price_eql(stocks[1].quantity, 2.0 * qty1));
+    assert!(price_eql(stocks[1].base_price, 122.0));
+    assert!(price_eql(stocks[2].quantity, qty2));
+    assert!(price_eql(stocks[2].base_price, 122.5));
+    assert!(price_eql(latest_notional(&stocks), (200.0 + 2.0 * qty1 + qty2) * 125.0));
+
+    assert!(ds.delete().is_ok());
+}
+
+#[test]
+fn test_split_dividends() {
+    let ds = make_datastore("sp_test_split_div_ds", &[
+        (history::tag(), "AAPL", "Date,Open,High,Low,Close,Adj Close,Volume\n\
+                                  2021-06-30,124.0,126.0,123.0,125.0,125.0,1000\n"),
+        (dividends::tag(), "AAPL", "Date,Dividends\n\
+                                    2021-02-05,0.40\n\
+                                    2021-05-07,0.22\n"),
+        (splits::tag(), "AAPL", "Date,Stock Splits\n\
+                                 2021-03-01,2:1\n"),
+        (fx_rates::tag(), "EURUSD", "Date,Rate\n\
+                                     2021-01-04,1.2\n")]);
+
+    let mut stocks: StockList = vec![Stock::new(String::from("AAPL"), StockType::Cash, make_date(2021, 1, 4), 100.0, 200.0)];
+    assert_eq!(split_adjust_from_ds(&mut stocks, &ds).unwrap(), 1);
+    assert_eq!(update_stocks_from_ds(&mut stocks, &ds).unwrap(), 1);
+
+    // 100 shares get the dividend before the split, 200 shares the one after
+    assert!(price_eql(stocks[0].quantity, 200.0));
+    assert!(price_eql(stocks[0].cum_dividend, 84.0));
+
+    let flows = cash_flow::dividend_cash_flows_from_ds(&stocks, &ds).unwrap();
+    assert_eq!(flows.iter().map(|f| f.amount).collect::<Vec<FixedPrice>>(),
+               vec![FixedPrice::from_string("40.00"), FixedPrice::from_string("44.00")]);
+
+    stocks[0].currency = String::from("EUR");
+    assert_eq!(currency::convert_stocks_from_ds(&mut stocks, &ds, "USD").unwrap(), 1);
+    assert!(price_eql(stocks[0].cum_dividend, 100.8));
+
+    assert!(ds.delete().is_ok());
+}
+
 #[test]
 fn test_stock_config_from_file() {
     let temp_name = "sp_test_stocks_config.cfg";
@@ -498,8 +1124,8 @@ fn test_stock_config_from_file() {
                vec![StockType::Cash, StockType::Cash, StockType::Cash]);
     assert_eq!(list.iter().map(|s| s.date).collect::<Vec<SPDate>>(),
                vec![make_date(2020, 9, 20), make_date(2020, 11, 12), make_date(2021, 02, 10)]);
-    assert_eq!(list.iter().map(|s| s.quantity).collect::<Vec<u32>>(),
-               vec![100, 100, 100]);
+    assert_eq!(list.iter().map(|s| s.quantity).collect::<Vec<f64>>(),
+               vec![100.0, 100.0, 100.0]);
     assert_eq!(list.iter().map(|s| s.base_price).collect::<Vec<f64>>(),
                vec![115.0, 118.50, 75.50]);
 
@@ -539,8 +1165,8 @@ fn test_stock_config_from_file2() {
                vec![StockType::Cash, StockType::Cash, StockType::Cash]);
     assert_eq!(list.iter().map(|s| s.date).collect::<Vec<SPDate>>(),
                vec![make_date(2020, 9, 20), make_date(2020, 11, 12), make_date(2021, 02, 10)]);
-    assert_eq!(list.iter().map(|s| s.quantity).collect::<Vec<u32>>(),
-               vec![100, 100, 100]);
+    assert_eq!(list.iter().map(|s| s.quantity).collect::<Vec<f64>>(),
+               vec![100.0, 100.0, 100.0]);
     assert_eq!(list.iter().map(|s| s.base_price).collect::<Vec<f64>>(),
                vec![115.0, 118.50, 75.50]);
 
@@ -581,8 +1207,8 @@ fn test_stock_config_from_file3() {
                vec![StockType::Cash, StockType::Cash, StockType::Cash]);
     assert_eq!(list.iter().map(|s| s.date).collect::<Vec<SPDate>>(),
                vec![make_date(2020, 9, 20), make_date(2020, 11, 12), make_date(2021, 02, 10)]);
-    assert_eq!(list.iter().map(|s| s.quantity).collect::<Vec<u32>>(),
-               vec![100, 100, 100]);
+    assert_eq!(list.iter().map(|s| s.quantity).collect::<Vec<f64>>(),
+               vec![100.0, 100.0, 100.0]);
     assert_eq!(list.iter().map(|s| s.base_price).collect::<Vec<f64>>(),
                vec![115.0, 118.50, 75.50]);
 
@@ -595,8 +1221,8 @@ fn test_stock_config_from_file3() {
                vec![make_date(2021, 2, 10), make_date(2021, 2, 10)]);
     assert_eq!(positions.iter().map(|p| p.exit_date).collect::<Vec<SPDate>>(),
                vec![make_date(2022, 4, 5), make_date(2022, 5, 18)]);
-    assert_eq!(positions.iter().map(|p| p.quantity).collect::<Vec<u32>>(),
-               vec![100, 100]);
+    assert_eq!(positions.iter().map(|p| p.quantity).collect::<Vec<FixedPrice>>(),
+               vec![FixedPrice::from_unsigned(100), FixedPrice::from_unsigned(100)]);
     assert_eq!(positions.iter().map(|p| p.base_price).collect::<Vec<FixedPrice>>(),
                vec![FixedPrice::from_string("75.50"), FixedPrice::from_string("75.50")]);
     assert_eq!(positions.iter().map(|p| p.exit_price).collect::<Vec<FixedPrice>>(),
@@ -656,8 +1282,8 @@ fn test_stock_config_from_file4() {
                vec![StockType::Cash, StockType::Cash, StockType::Cash]);
     assert_eq!(list.iter().map(|s| s.date).collect::<Vec<SPDate>>(),
                vec![make_date(2020, 9, 20), make_date(2020, 11, 12), make_date(2021, 02, 10)]);
-    assert_eq!(list.iter().map(|s| s.quantity).collect::<Vec<u32>>(),
-               vec![100, 100, 100]);
+    assert_eq!(list.iter().map(|s| s.quantity).collect::<Vec<f64>>(),
+               vec![100.0, 100.0, 100.0]);
     assert_eq!(list.iter().map(|s| s.base_price).collect::<Vec<f64>>(),
                vec![115.0, 118.50, 75.50]);
 
@@ -670,8 +1296,8 @@ fn test_stock_config_from_file4() {
                vec![make_date(2021, 2, 10), make_date(2021, 2, 10)]);
     assert_eq!(positions.iter().map(|p| p.exit_date).collect::<Vec<SPDate>>(),
                vec![make_date(2022, 4, 5), make_date(2022, 5, 18)]);
-    assert_eq!(positions.iter().map(|p| p.quantity).collect::<Vec<u32>>(),
-               vec![100, 100]);
+    assert_eq!(positions.iter().map(|p| p.quantity).collect::<Vec<FixedPrice>>(),
+               vec![FixedPrice::from_unsigned(100), FixedPrice::from_unsigned(100)]);
     assert_eq!(positions.iter().map(|p| p.base_price).collect::<Vec<FixedPrice>>(),
                vec![FixedPrice::from_string("75.50"), FixedPrice::from_string("75.50")]);
     assert_eq!(positions.iter().map(|p| p.exit_price).collect::<Vec<FixedPrice>>(),
@@ -693,6 +1319,7 @@ fn test_stock_config_from_str() {
     let content: &str = "ds_root: sp_root\n\
                          ds_name: sp_name\n\
                          cash: 1025.00\n\
+                         provider: yahoo\n\
                          stocks: csv{\n\
                          symbol,type,date,quantity,base_price\n\
                          AAPL,cash,2020-09-20,100,115.00\n\
@@ -706,6 +1333,7 @@ fn test_stock_config_from_str() {
     assert_eq!(cfg.stocks().len(), 3);
     assert_eq!(cfg.closed_positions().len(), 0);
     assert_eq!(cfg.cash(), 1025.00);
+    assert_eq!(cfg.provider(), ProviderType::Yahoo);
 
     let list = cfg.stocks();
     assert_eq!(list.iter().map(|s| s.symbol.as_str()).collect::<Vec<&str>>(),
@@ -714,12 +1342,352 @@ fn test_stock_config_from_str() {
                vec![StockType::Cash, StockType::Cash, StockType::Cash]);
     assert_eq!(list.iter().map(|s| s.date).collect::<Vec<SPDate>>(),
                vec![make_date(2020, 9, 20), make_date(2020, 11, 12), make_date(2021, 02, 10)]);
-    assert_eq!(list.iter().map(|s| s.quantity).collect::<Vec<u32>>(),
-               vec![100, 100, 100]);
+    assert_eq!(list.iter().map(|s| s.quantity).collect::<Vec<f64>>(),
+               vec![100.0, 100.0, 100.0]);
     assert_eq!(list.iter().map(|s| s.base_price).collect::<Vec<f64>>(),
                vec![115.0, 118.50, 75.50]);
 }
 
+#[test]
+fn test_stock_config_base_currency() {
+    let content: &str = "base_currency: eur\n\
+                         stocks: csv{\n\
+                         symbol,type,date,quantity,base_price,drip,currency\n\
+                         AAPL,cash,2020-09-20,100,115.00,,USD\n\
+                         SAP,cash,2021-02-10,100,105.50,,\n\
+                         }\n\
+                         closed_positions: csv{\n\
+                         symbol,type,base_date,exit_date,quantity,base_price,exit_price,base_fee,exit_fee,dividend,currency\n\
+                         TM,cash,2020-06-01,2021-01-04,10,130.00,140.00,0.00,0.00,0.00,jpy\n\
+                         }\n";
+
+    let cfg = StocksConfig::from_str(content).unwrap();
+    assert_eq!(cfg.base_currency(), "EUR");
+    assert_eq!(cfg.stocks().iter().map(|s| s.currency.as_str()).collect::<Vec<&str>>(), vec!["USD", ""]);
+    assert_eq!(cfg.closed_positions()[0].currency, "JPY");
+}
+
+#[test]
+fn test_stock_config_local_provider() {
+    let content: &str = "provider: local\n\
+                         provider_root: /path/to/vendor/drop\n\
+                         stocks: csv{\n\
+                         }\n";
+
+    let cfg = StocksConfig::from_str(content).unwrap();
+    assert_eq!(cfg.provider(), ProviderType::Local);
+    assert_eq!(cfg.provider_root(), "/path/to/vendor/drop");
+    assert!(providers::make_provider(&cfg).is_ok());
+
+    let content: &str = "provider: local\n\
+                         stocks: csv{\n\
+                         }\n";
+
+    let cfg = StocksConfig::from_str(content).unwrap();
+    assert_eq!(cfg.provider_root(), "");
+    assert!(providers::make_provider(&cfg).is_err());
+}
+
+#[test]
+fn test_stock_config_http() {
+    let content: &str = "http_connect_timeout: 5\n\
+                         http_timeout: 60\n\
+                         http_retries: 5\n\
+                         http_backoff: 1000\n\
+                         http_rate_limit: 2000\n\
+                         http_proxy: http://proxy.local:8080\n\
+                         http_user_agent: Mozilla/5.0 (X11; Linux x86_64)\n\
+                         stocks: csv{\n\
+                         }\n";
+
+    let cfg = StocksConfig::from_str(content).unwrap();
+    let http = cfg.http_config();
+    assert_eq!(http.connect_timeout(), 5);
+    assert_eq!(http.timeout(), 60);
+    assert_eq!(http.retries(), 5);
+    assert_eq!(http.backoff(), 1000);
+    assert_eq!(http.rate_limit(), 2000);
+    assert_eq!(http.proxy(), "http://proxy.local:8080");
+    assert_eq!(http.user_agent(), "Mozilla/5.0 (X11; Linux x86_64)");
+}
+
+#[test]
+fn test_stock_config_transactions() {
+    let content: &str = "lot_method: lifo\n\
+                         stocks: csv{\n\
+                         symbol,type,date,quantity,base_price\n\
+                         DELL,cash,2021-02-10,100,75.50\n\
+                         }\n\
+                         transactions: csv{\n\
+                         date,action,symbol,type,quantity,price,fee,lot\n\
+                         2020-09-20,buy,AAPL,cash,100,115.00,1.00,\n\
+                         2020-11-12,buy,AAPL,cash,100,118.50,1.00,\n\
+                         2021-03-01,sell,AAPL,cash,150,125.00,1.50,\n\
+                         }\n";
+
+    let cfg = StocksConfig::from_str(content).unwrap();
+    assert_eq!(cfg.lot_method(), LotMethod::Lifo);
+    assert_eq!(cfg.transactions().len(), 3);
+
+    let list = cfg.stocks();
+    assert_eq!(list.iter().map(|s| s.symbol.as_str()).collect::<Vec<&str>>(), vec!["DELL", "AAPL"]);
+    assert_eq!(list.iter().map(|s| s.date).collect::<Vec<SPDate>>(), vec![make_date(2021, 2, 10), make_date(2020, 9, 20)]);
+    assert_eq!(list.iter().map(|s| s.quantity).collect::<Vec<f64>>(), vec![100.0, 50.0]);
+    // Half of the 1.00 buy fee is left in the open lot
+    assert!(zip(list.iter().map(|s| s.base_price), [75.50, 115.01]).all(|(price, expected)| price_eql(price, expected)));
+
+    let list = cfg.closed_positions();
+    assert_eq!(list.len(), 2);
+    assert_eq!(list.iter().map(|p| p.base_date).collect::<Vec<SPDate>>(), vec![make_date(2020, 11, 12), make_date(2020, 9, 20)]);
+    assert_eq!(list.iter().map(|p| p.exit_date).collect::<Vec<SPDate>>(), vec![make_date(2021, 3, 1), make_date(2021, 3, 1)]);
+    assert_eq!(list.iter().map(|p| p.quantity).collect::<Vec<FixedPrice>>(), vec![FixedPrice::from_unsigned(100), FixedPrice::from_unsigned(50)]);
+    assert_eq!(list.iter().map(|p| p.base_price).collect::<Vec<FixedPrice>>(),
+               vec![FixedPrice::from_string("118.50"), FixedPrice::from_string("115.00")]);
+    assert_eq!(list.iter().map(|p| p.base_fee).collect::<Vec<FixedPrice>>(),
+               vec![FixedPrice::from_string("1.00"), FixedPrice::from_string("0.50")]);
+    assert_eq!(list.iter().map(|p| p.exit_fee).collect::<Vec<FixedPrice>>(),
+               vec![FixedPrice::from_string("1.00"), FixedPrice::from_string("0.50")]);
+}
+
+#[test]
+fn test_stock_config_cash_flows() {
+    let content: &str = "cash: 100.00\n\
+                         cash_flows: csv{\n\
+                         date,type,amount,symbol\n\
+                         2021-06-01,withdrawal,500.00,\n\
+                         2020-09-01,deposit,20000.00,\n\
+                         2021-02-18,dividend,82.00,AAPL\n\
+                         2021-03-31,fee,5.00,\n\
+                         }\n\
+                         transactions: csv{\n\
+                         date,action,symbol,type,quantity,price,fee,lot\n\
+                         2020-09-20,buy,AAPL,cash,100,115.00,1.00,\n\
+                         2021-03-01,sell,AAPL,cash,50,125.00,1.00,\n\
+                         }\n";
+
+    let cfg = StocksConfig::from_str(content).unwrap();
+    assert_eq!(cfg.cash(), 100.0);
+
+    let flows = cfg.cash_flows();
+    assert_eq!(flows.len(), 6);
+    assert_eq!(flows.iter().map(|f| f.date).collect::<Vec<SPDate>>(),
+               vec![make_date(2020, 9, 1), make_date(2020, 9, 20), make_date(2021, 2, 18),
+                    make_date(2021, 3, 1), make_date(2021, 3, 31), make_date(2021, 6, 1)]);
+    assert_eq!(flows.iter().map(|f| f.signed_amount()).collect::<Vec<FixedPrice>>(),
+               vec![FixedPrice::from_string("20000.00"), FixedPrice::from_string("-11501.00"), FixedPrice::from_string("82.00"),
+                    FixedPrice::from_string("6249.00"), FixedPrice::from_string("-5.00"), FixedPrice::from_string("-500.00")]);
+
+    assert!(price_eql(cfg.cash_balance(&make_date(2020, 8, 31)), 100.0));
+    assert!(price_eql(cfg.cash_balance(&make_date(2020, 9, 20)), 8599.0));
+    assert!(price_eql(cfg.cash_balance(&make_date(2021, 3, 1)), 14930.0));
+    assert!(price_eql(cfg.cash_balance(&make_date(2021, 12, 31)), 14425.0));
+}
+
+#[test]
+fn test_stock_config_position_cash_flows() {
+    let content: &str = "closed_positions: csv{\n\
+                         symbol,type,base_date,exit_date,quantity,base_price,exit_price,base_fee,exit_fee,dividend\n\
+                         AAPL,cash,2020-09-20,2021-03-01,100,115.00,125.00,1.00,2.00,82.00\n\
+                         DELL,cash,2020-10-01,2021-02-01,10,80.00,75.00,0.00,0.00,0.00\n\
+                         }\n";
+
+    let cfg = StocksConfig::from_str(content).unwrap();
+    assert!(cfg.cash_flows().is_empty());
+
+    let flows = cfg.holding_cash_flows();
+    assert_eq!(flows.len(), 3);
+    assert!(flows.iter().map(|f| f.ftype).eq([CashFlowType::Proceeds, CashFlowType::Proceeds, CashFlowType::Dividend]));
+    assert_eq!(flows.iter().map(|f| f.date).collect::<Vec<SPDate>>(),
+               vec![make_date(2021, 2, 1), make_date(2021, 3, 1), make_date(2021, 3, 1)]);
+    assert_eq!(flows.iter().map(|f| f.signed_amount()).collect::<Vec<FixedPrice>>(),
+               vec![FixedPrice::from_string("750.00"), FixedPrice::from_string("12498.00"), FixedPrice::from_string("82.00")]);
+    assert!(price_eql(cfg.cash_balance(&make_date(2021, 12, 31)), 0.0));
+    assert_eq!(cfg.series_cash_flows().len(), 3);
+}
+
+#[test]
+fn test_stock_config_baseline_cash_balance() {
+    let content: &str = "cash: 1250.00
+                         stocks: csv{
+                         symbol,type,date,quantity,base_price
+                         AAPL,cash,2020-09-20,100,115.00
+                         }
+                         closed_positions: csv{
+                         symbol,type,base_date,exit_date,quantity,base_price,exit_price,base_fee,exit_fee,dividend
+                         MSFT,cash,2020-09-20,2021-03-01,50,200.00,225.00,1.00,1.00,82.00
+                         }
+";
+
+    // Proceeds and dividends of closed positions without purchases in the cash flows leave the cash balance as is
+    let cfg = StocksConfig::from_str(content).unwrap();
+    assert!(price_eql(cfg.cash_balance(&make_date(2021, 12, 31)), cfg.cash()));
+    assert!(price_eql(cfg.cash_balance(&make_date(2021, 12, 31)), 1250.0));
+    assert_eq!(cfg.holding_cash_flows().len(), 2);
+}
+
+#[test]
+fn test_dividend_cash_flows_from_ds() {
+    let ds = make_datastore("sp_test_div_flows_ds", &[
+        (dividends::tag(), "AAPL", "Date,Dividends\n\
+                                    2021-02-05,0.205\n\
+                                    2021-05-07,0.22\n"),
+        (dividends::tag(), "MSFT", "Date,Dividends\n\
+                                    2021-05-19,0.56\n")]);
+
+    let mut stocks: StockList = vec![
+        Stock::new(String::from("AAPL"), StockType::Cash, make_date(2021, 3, 1), 100.0, 120.0),
+        Stock::new(String::from("MSFT"), StockType::Cash, make_date(2021, 1, 4), 10.0, 200.0),
+        Stock::new(String::from("DELL"), StockType::Cash, make_date(2021, 1, 4), 10.0, 80.0),
+        Stock::new(String::from("MSFT"), StockType::Cash, make_date(2021, 1, 4), 10.0, 200.0)
+    ];
+    stocks[0].currency = String::from("EUR");
+    stocks[3].drip = true;
+
+    // Dividends before the buy date, of DRIP lots and of symbols without dividends are skipped
+    let flows = cash_flow::dividend_cash_flows_from_ds(&stocks, &ds).unwrap();
+    assert_eq!(flows.len(), 2);
+    assert!(flows.iter().all(|f| f.ftype == CashFlowType::Dividend));
+    assert_eq!((flows[0].symbol.as_str(), flows[0].date, flows[0].amount, flows[0].currency.as_str()),
+               ("AAPL", make_date(2021, 5, 7), FixedPrice::from_string("22.00"), "EUR"));
+    assert_eq!((flows[1].symbol.as_str(), flows[1].date, flows[1].amount, flows[1].currency.as_str()),
+               ("MSFT", make_date(2021, 5, 19), FixedPrice::from_string("5.60"), ""));
+
+    assert!(ds.delete().is_ok());
+}
+
+#[test]
+fn test_stock_config_accounts() {
+    let ira_name = "sp_test_stocks_config_ira.cfg";
+    let ira_filename = temp_file::make_path(ira_name);
+    assert!(temp_file::create_file(ira_name,
+                                   "ds_name: ira_name\n\
+                                    cash: 50.00\n\
+                                    stocks: csv{\n\
+                                    symbol,type,date,quantity,base_price,drip,currency,account\n\
+                                    DELL,cash,2021-02-10,100,75.50\n\
+                                    ICLN,etf,2021-02-11,100,24.10,,,roth\n\
+                                    }\n\
+                                    closed_positions: csv{\n\
+                                    symbol,type,base_date,exit_date,quantity,base_price,exit_price,base_fee,exit_fee,dividend\n\
+                                    MSFT,cash,2020-09-20,2021-01-10,50,200.00,225.00,0.0,1.0,0.0\n\
+                                    }\n\
+                                    cash_flows: csv{\n\
+                                    date,type,amount,symbol\n\
+                                    2020-09-01,deposit,1000.00,\n\
+                                    }\n"));
+
+    let content = format!("ds_name: main_name\n\
+                           cash: 100.00\n\
+                           stocks: csv{{\n\
+                           symbol,type,date,quantity,base_price\n\
+                           AAPL,cash,2020-09-20,100,115.00\n\
+                           }}\n\
+                           cash_flows: csv{{\n\
+                           date,type,amount,symbol\n\
+                           2021-06-01,deposit,500.00,\n\
+                           }}\n\
+                           accounts: csv{{\n\
+                           account,config_file\n\
+                           ira,{}\n\
+                           }}\n", ira_filename.to_str().unwrap());
+
+    let cfg = StocksConfig::from_str(&content).unwrap();
+    assert_eq!(cfg.ds_name(), "main_name");
+    assert_eq!(cfg.cash(), 150.0);
+
+    let list = cfg.stocks();
+    assert_eq!(list.iter().map(|s| s.symbol.as_str()).collect::<Vec<&str>>(), vec!["AAPL", "DELL", "ICLN"]);
+    assert_eq!(list.iter().map(|s| s.account.as_str()).collect::<Vec<&str>>(), vec!["", "ira", "roth"]);
+
+    let positions = cfg.closed_positions();
+    assert_eq!(positions.len(), 1);
+    assert_eq!(positions[0].account, "ira");
+
+    assert_eq!(cfg.cash_flows().iter().map(|f| f.date).collect::<Vec<SPDate>>(),
+               vec![make_date(2020, 9, 1), make_date(2021, 6, 1)]);
+    assert_eq!(cfg.holding_cash_flows().iter().map(|f| f.date).collect::<Vec<SPDate>>(),
+               vec![make_date(2021, 1, 10)]);
+
+    assert!(temp_file::remove_file(ira_name));
+
+    assert_eq!(format!("{}", StocksConfig::from_str("accounts: csv{\naccount,config_file\nira\n}\n").err().unwrap()),
+               "StocksConfig::parse - Invalid account line 'ira'");
+    assert_eq!(format!("{}", StocksConfig::from_str("accounts: csv_file{\n}\n").err().unwrap()),
+               "StocksConfig::parse - Unsupported block type 'csv_file{'");
+}
+
+#[test]
+fn test_stock_config_account_includes() {
+    // Relative config files are read from the directory of the including config
+    let main_name = "sp_test_stocks_config_incl_main.cfg";
+    let ira_name = "sp_test_stocks_config_incl_ira.cfg";
+    assert!(temp_file::create_file(main_name,
+                                   &format!("stocks: csv{{\n\
+                                             symbol,type,date,quantity,base_price\n\
+                                             AAPL,cash,2020-09-20,100,115.00\n\
+                                             }}\n\
+                                             accounts: csv{{\n\
+                                             account,config_file\n\
+                                             ira,{}\n\
+                                             }}\n", ira_name)));
+    assert!(temp_file::create_file(ira_name,
+                                   "stocks: csv{\n\
+                                    symbol,type,date,quantity,base_price\n\
+                                    DELL,cash,2021-02-10,100,75.50\n\
+                                    }\n"));
+
+    let cfg = StocksConfig::from_file(temp_file::make_path(main_name).to_str().unwrap()).unwrap();
+    assert_eq!(cfg.stocks().iter().map(|s| s.symbol.as_str()).collect::<Vec<&str>>(), vec!["AAPL", "DELL"]);
+
+    // Includes back to a config being read are circular
+    assert!(temp_file::create_file(ira_name,
+                                   &format!("accounts: csv{{\n\
+                                             account,config_file\n\
+                                             main,{}\n\
+                                             }}\n", main_name)));
+    let main_path = temp_file::make_path(main_name);
+    assert_eq!(format!("{}", StocksConfig::from_file(main_path.to_str().unwrap()).err().unwrap()),
+               format!("StocksConfig::parse - Circular account include '{}'", main_path.display()));
+
+    assert!(temp_file::remove_file(main_name));
+    assert!(temp_file::remove_file(ira_name));
+}
+
+#[test]
+fn test_stock_config_relative_csv_file() {
+    // Relative CSV files are read from the directory of the config
+    let config_name = "sp_test_stocks_config_rel_csv.cfg";
+    let stocks_name = "sp_test_stocks_config_rel_stocks.csv";
+    assert!(temp_file::create_file(config_name,
+                                   &format!("stocks: csv_file{{\n\
+                                             {}\n\
+                                             }}\n", stocks_name)));
+    assert!(temp_file::create_file(stocks_name,
+                                   "symbol,type,date,quantity,base_price\n\
+                                    DELL,cash,2021-02-10,100,75.50\n"));
+
+    let cfg = StocksConfig::from_file(temp_file::make_path(config_name).to_str().unwrap()).unwrap();
+    assert_eq!(cfg.stocks().iter().map(|s| s.symbol.as_str()).collect::<Vec<&str>>(), vec!["DELL"]);
+
+    assert!(temp_file::remove_file(config_name));
+    assert!(temp_file::remove_file(stocks_name));
+}
+
+#[test]
+fn test_stock_config_targets() {
+    let content: &str = "targets: csv{\n\
+                         target,weight,tolerance\n\
+                         AAPL,25,5\n\
+                         type:etf,60,\n\
+                         }\n";
+
+    let cfg = StocksConfig::from_str(content).unwrap();
+    let targets = cfg.targets();
+    assert_eq!(targets.iter().map(|t| t.name()).collect::<Vec<String>>(), vec!["AAPL", "type:etf"]);
+    assert_eq!(targets.iter().map(|t| t.weight).collect::<Vec<f64>>(), vec![25.0, 60.0]);
+    assert_eq!(targets.iter().map(|t| t.tolerance).collect::<Vec<f64>>(), vec![5.0, 0.0]);
+}
+
 #[test]
 fn test_stock_config_mut() {
     let mut cfg = StocksConfig::new();
@@ -728,7 +1696,7 @@ fn test_stock_config_mut() {
     assert_eq!(cfg.stocks().len(), 0);
 
     let stocks = cfg.stocks_mut();
-    stocks.push(make_stock("AAPL", StockType::Cash, make_date(2020, 9, 20), 100, 120.25, 125.25));
+    stocks.push(make_stock("AAPL", StockType::Cash, make_date(2020, 9, 20), 100.0, 120.25, 125.25));
 
     assert_eq!(cfg.ds_root(), "");
     assert_eq!(cfg.ds_name(), "");
@@ -738,7 +1706,7 @@ fn test_stock_config_mut() {
     assert_eq!(list.iter().map(|s| s.symbol.as_str()).collect::<Vec<&str>>(), vec!["AAPL"]);
     assert_eq!(list.iter().map(|s| s.stype).collect::<Vec<StockType>>(), vec![StockType::Cash]);
     assert_eq!(list.iter().map(|s| s.date).collect::<Vec<SPDate>>(), vec![make_date(2020, 9, 20)]);
-    assert_eq!(list.iter().map(|s| s.quantity).collect::<Vec<u32>>(), vec![100]);
+    assert_eq!(list.iter().map(|s| s.quantity).collect::<Vec<f64>>(), vec![100.0]);
     assert_eq!(list.iter().map(|s| s.base_price).collect::<Vec<f64>>(), vec![120.25]);
 }
 
@@ -750,6 +1718,10 @@ fn test_stock_config_default() {
         assert_eq!(c.stocks().len(), 0);
         assert_eq!(c.closed_positions().len(), 0);
         assert_eq!(c.cash(), 0.0);
+        assert_eq!(c.base_currency(), "USD");
+        assert_eq!(c.provider(), ProviderType::Yahoo);
+        assert_eq!(c.provider_root(), "");
+        assert_eq!(c.http_config(), &HttpConfig::new());
     }
 
     let content: &str = "ds_root: $default\n\
@@ -784,21 +1756,43 @@ fn test_stock_config_errors() {
     check(&cfg("csv[\n]\n"), "StocksConfig::parse - Unsupported block type 'csv['");
     check(&cfg("csv{\n}\nwhat: who\n"), "StocksConfig::parse - Unknown config name 'what'");
     check(&cfg("csv{\n}\ncash: amnt\n"), "StocksConfig::parse - invalid float literal");
+    check(&cfg("csv{\n}\nprovider: foobar\n"), "StocksConfig::parse - Unknown provider type 'foobar'");
+    check(&cfg("csv{\n}\nlot_method: hifo\n"), "Unknown lot method 'hifo'");
+    check(&cfg("csv{\n}\nbase_currency: dollar\n"), "Invalid currency 'dollar'");
+    check(&cfg("csv{\n}\ntransactions: csv{\ndate,action,symbol,type,quantity,price,fee,lot\n2021-03-01,sell,AAPL,cash,1,1.00,,\n}\n"),
+          "Ledger: Sell quantity 1 exceeds open quantity 0 for AAPL on 2021-03-01");
+    check(&cfg("csv{\n}\nhttp_timeout: -1\n"), "StocksConfig::parse - Invalid http_timeout value '-1'");
+    check(&cfg("csv{\n}\nhttp_retries: many\n"), "StocksConfig::parse - Invalid http_retries value 'many'");
+    check(&cfg("csv{\n}\nprovider_root: /a:/b\n"), "StocksConfig::parse - Invalid line 'provider_root: /a:/b'");
+    check(&cfg("csv{\n}\ntargets: csv{\ntarget,weight\nAAPL,60\ntype:etf,50\n}\n"),
+          "TargetsReader::parse_content - Target weights sum to 110 percent");
 }
 
 // --------------------------------------------------------------------------------
 // Helpers
 
-fn make_stock(sym: &str, stype: StockType, date: SPDate, qty: u32, base: Price, latest: Price) -> Stock {
+fn make_stock(sym: &str, stype: StockType, date: SPDate, qty: Quantity, base: Price, latest: Price) -> Stock {
     let symbol = String::from(sym);
     let mut stock = Stock::new(symbol, stype, date, qty, base);
     stock.set_latest_price(latest, today_plus_days(0));
     stock
 }
 
+fn make_datastore(name: &str, symbols: &[(&str, &str, &str)]) -> DataStore {
+    let ds = DataStore::new(&format!("{}", env::temp_dir().display()), name);
+    if ds.exists() {
+        assert!(ds.delete().is_ok());
+    }
+    assert!(ds.create().is_ok());
+    for (tag, symbol, csv) in symbols.iter() {
+        assert!(ds.insert_symbol(tag, symbol, csv).is_ok());
+    }
+    ds
+}
+
 fn make_position(sym: &str, stype: StockType,
                  base_date: SPDate, exit_date: SPDate,
-                 quantity: u32, base_price: FixedPrice, exit_price: FixedPrice,
+                 quantity: FixedPrice, base_price: FixedPrice, exit_price: FixedPrice,
                  exit_fee: FixedPrice, dividend: FixedPrice) -> ClosedPosition {
     ClosedPosition::new(
         String::from(sym),
@@ -38,3 +38,64 @@
 //         assert!((act - expect[i-1]).abs() < 0.5);
 //     }
 // }
+
+use std::fs;
+use std::path::PathBuf;
+use sp_lib::util::datetime;
+use sp_lib::yfinance::chart::Chart;
+
+#[test]
+fn test_chart_split_fixture() {
+    let chart = Chart::parse_json("AAPL", &read_fixture("yahoo_chart_aapl_split.json")).unwrap();
+
+    let hist = chart.history();
+    assert_eq!(hist.symbol(), "AAPL");
+    assert_eq!(hist.count(), 3);
+    assert_eq!(hist.entries().iter().map(|e| e.date).collect::<Vec<datetime::SPDate>>(),
+               vec![datetime::make_date(2020, 8, 27), datetime::make_date(2020, 8, 28), datetime::make_date(2020, 8, 31)]);
+    assert_eq!(hist.entries().iter().map(|e| e.close).collect::<Vec<f64>>(), vec![500.04, 499.23, 129.04]);
+    assert_eq!(hist.entries().iter().map(|e| e.adj_close).collect::<Vec<f64>>(), vec![122.72, 122.52, 126.67]);
+    assert_eq!(hist.entries().iter().map(|e| e.volume).collect::<Vec<u64>>(), vec![38888100, 46907500, 225702700]);
+
+    let entry = &hist.entries()[2];
+    assert_eq!((entry.open, entry.high, entry.low), (127.58, 131.0, 126.0));
+
+    assert_eq!(chart.dividends().count(), 0);
+
+    let splt = chart.splits();
+    assert_eq!(splt.count(), 1);
+    assert_eq!(splt.entries()[0].date, datetime::make_date(2020, 8, 31));
+    assert_eq!(splt.entries()[0].split, "4:1");
+}
+
+#[test]
+fn test_chart_dividend_fixture() {
+    let chart = Chart::parse_json("MSFT", &read_fixture("yahoo_chart_msft_dividend.json")).unwrap();
+
+    let hist = chart.history();
+    assert_eq!(hist.count(), 3);
+    assert_eq!(hist.entries()[0].date, datetime::make_date(2021, 2, 16));
+    assert_eq!(hist.entries()[2].date, datetime::make_date(2021, 2, 18));
+
+    let div = chart.dividends();
+    assert_eq!(div.symbol(), "MSFT");
+    assert_eq!(div.count(), 1);
+    assert_eq!(div.entries()[0].date, datetime::make_date(2021, 2, 17));
+    assert_eq!(div.entries()[0].price, 0.56);
+
+    assert_eq!(chart.splits().count(), 0);
+}
+
+#[test]
+fn test_chart_not_found_fixture() {
+    let err = Chart::parse_json("FOOBAR", &read_fixture("yahoo_chart_not_found.json")).err().unwrap();
+    assert_eq!(format!("{}", err), "Chart: Query error 'No data found, symbol may be delisted'");
+}
+
+fn read_fixture(name: &str) -> String {
+    let mut path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    path.push("tests");
+    path.push("fixtures");
+    path.push(name);
+    fs::read_to_string(path).unwrap()
+}